@@ -0,0 +1,3731 @@
+//! Bitcoin Key Generator - Simple Rust Implementation
+//!
+//! Generates BIP39 mnemonic seed phrases for use with Bitcoin hardware
+//! wallets (Coldcard, Trezor, Ledger, etc.) and formats them for printing
+//! and metal plate storage. The `bitcoin-keygen` binary is a thin CLI over
+//! this library.
+//!
+//! ```
+//! use bitcoin_keygen::{
+//!     create_printable_output, derive_addresses, derive_master_key, generate_mnemonic,
+//!     generate_seed, get_hardware_wallet_fingerprint, Language, Network,
+//! };
+//!
+//! let mnemonic = generate_mnemonic(24, Language::English)?;
+//! let seed = generate_seed(&mnemonic, "");
+//! let master = derive_master_key(&seed, Network::Bitcoin)?;
+//! let fingerprint = get_hardware_wallet_fingerprint(&master);
+//! assert_eq!(fingerprint.len(), 8);
+//!
+//! let addresses = derive_addresses(&master, Network::Bitcoin, 1)?;
+//! let printable = create_printable_output(
+//!     &mnemonic,
+//!     &fingerprint,
+//!     "My Wallet",
+//!     Network::Bitcoin,
+//!     &addresses,
+//! );
+//! assert!(printable.contains(&fingerprint));
+//! # Ok::<(), bitcoin_keygen::KeygenError>(())
+//! ```
+
+pub mod seed_core;
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+pub use bip39::{Language, Mnemonic};
+use bitcoin::base64::prelude::{Engine as _, BASE64_STANDARD};
+use bitcoin::bip32::{ChildNumber, DerivationPath, Xpriv, Xpub};
+use bitcoin::hashes::{hmac, sha256, sha512, Hash, HashEngine};
+use bitcoin::secp256k1::{Message, Secp256k1};
+use bitcoin::sign_message::{signed_msg_hash, MessageSignature};
+pub use bitcoin::Network;
+use bitcoin::{Address, CompressedPublicKey, NetworkKind, ScriptBuf};
+use chrono::{DateTime, FixedOffset, Local};
+use printpdf::{BuiltinFont, Mm, PdfDocument};
+pub use qrcode::EcLevel;
+use qrcode::QrCode;
+use rayon::prelude::*;
+pub use seed_core::{derive_master_key, generate_seed, get_hardware_wallet_fingerprint};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
+use unicode_normalization::UnicodeNormalization;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Word counts accepted by BIP39 (and by `--words`)
+pub const SUPPORTED_WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// Default word count (24 words for maximum security)
+pub const DEFAULT_WORD_COUNT: usize = 24;
+
+/// Number of receive addresses shown per script type
+pub const ADDRESS_PREVIEW_COUNT: usize = 3;
+
+/// Errors returned by the key generator, grouped by failure mode so callers
+/// can match on them
+#[derive(Debug, thiserror::Error)]
+pub enum KeygenError {
+    /// RNG failure or unusable entropy input (self-test, dice, coins, hex)
+    #[error("{0}")]
+    Entropy(String),
+    /// Word count outside 12/15/18/21/24
+    #[error("Unsupported word count: {0} (expected one of 12, 15, 18, 21, 24)")]
+    InvalidWordCount(usize),
+    /// Mnemonic could not be parsed or built
+    #[error("{0}")]
+    MnemonicParse(String),
+    /// BIP32 derivation or path error
+    #[error("Key derivation failed: {0}")]
+    Derivation(#[from] bitcoin::bip32::Error),
+    /// Passphrase input rejected
+    #[error("{0}")]
+    Passphrase(String),
+    /// Invalid command-line argument or option value
+    #[error("{0}")]
+    InvalidArgument(String),
+    /// SLIP39 / Seed XOR splitting failed
+    #[error("{0}")]
+    SecretSharing(String),
+    /// Output could not be rendered (QR, PNG, PDF, JSON)
+    #[error("{0}")]
+    Render(String),
+    /// Output file encryption or decryption failed
+    #[error("{0}")]
+    Encryption(String),
+    /// Integrity manifest verification failed
+    #[error("{0}")]
+    Integrity(String),
+    /// `--require-offline` detected a network connection
+    #[error(
+        "Network connection detected: refusing to generate keys \
+         (--require-offline). Disconnect this machine and retry"
+    )]
+    Online,
+    /// One wallet of a `--count` batch failed
+    #[error("wallet {index}: {source}")]
+    Batch {
+        index: usize,
+        source: Box<KeygenError>,
+    },
+    /// `--verify-quiz` answers did not match the mnemonic
+    #[error("Backup verification failed: {wrong} of {total} words did not match")]
+    QuizFailed { wrong: usize, total: usize },
+    /// A derived output script has no address form
+    #[error("Address encoding failed: {0}")]
+    Address(#[from] bitcoin::address::FromScriptError),
+    /// Filesystem or terminal I/O error
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl From<getrandom::Error> for KeygenError {
+    fn from(e: getrandom::Error) -> Self {
+        KeygenError::Entropy(format!("System RNG failed: {}", e))
+    }
+}
+
+impl From<bip39::Error> for KeygenError {
+    fn from(e: bip39::Error) -> Self {
+        KeygenError::MnemonicParse(format!("Invalid mnemonic: {}", e))
+    }
+}
+
+impl From<qrcode::types::QrError> for KeygenError {
+    fn from(e: qrcode::types::QrError) -> Self {
+        KeygenError::Render(format!("QR code encoding failed: {}", e))
+    }
+}
+
+impl From<image::ImageError> for KeygenError {
+    fn from(e: image::ImageError) -> Self {
+        KeygenError::Render(format!("PNG encoding failed: {}", e))
+    }
+}
+
+impl From<printpdf::Error> for KeygenError {
+    fn from(e: printpdf::Error) -> Self {
+        KeygenError::Render(format!("PDF rendering failed: {}", e))
+    }
+}
+
+impl From<serde_json::Error> for KeygenError {
+    fn from(e: serde_json::Error) -> Self {
+        KeygenError::Render(format!("JSON serialization failed: {}", e))
+    }
+}
+
+/// Map a BIP39 word count to its entropy size in bytes
+/// 12/15/18/21/24 words = 128/160/192/224/256 bits of entropy
+pub fn entropy_bytes_for_word_count(word_count: usize) -> Option<usize> {
+    if SUPPORTED_WORD_COUNTS.contains(&word_count) {
+        Some(word_count * 4 / 3)
+    } else {
+        None
+    }
+}
+
+/// Bits of entropy encoded by a mnemonic of the given word count
+pub fn entropy_bits_for_word_count(word_count: usize) -> usize {
+    word_count * 32 / 3
+}
+
+/// Generate a new BIP39 mnemonic with the requested number of words
+/// Most hardware wallets support 12, 18, or 24 word seeds - 24 gives maximum entropy
+pub fn generate_mnemonic(word_count: usize, language: Language) -> Result<Mnemonic, KeygenError> {
+    mnemonic_from_entropy(random_entropy(word_count)?, language)
+}
+
+/// Longest `--vanity-fingerprint` prefix: 4 hex digits take about 65,536
+/// attempts, each a full PBKDF2 seed derivation
+pub const VANITY_MAX_PREFIX_LEN: usize = 4;
+
+/// Attempts before a vanity search gives up (about 15x the expected number
+/// for the longest prefix)
+pub const VANITY_MAX_ATTEMPTS: usize = 1_000_000;
+
+/// Candidates derived in parallel per round of a vanity search
+const VANITY_BATCH: usize = 64;
+
+/// Validate a `--vanity-fingerprint` prefix: 1 to [`VANITY_MAX_PREFIX_LEN`]
+/// hex digits, returned lowercase to match fingerprint formatting
+pub fn parse_vanity_prefix(prefix: &str) -> Result<String, KeygenError> {
+    let prefix = prefix.trim().to_lowercase();
+    if prefix.is_empty()
+        || prefix.len() > VANITY_MAX_PREFIX_LEN
+        || !prefix.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        return Err(KeygenError::InvalidArgument(format!(
+            "Invalid vanity fingerprint prefix '{}': expected 1-{} hex digits",
+            prefix, VANITY_MAX_PREFIX_LEN
+        )));
+    }
+    Ok(prefix)
+}
+
+/// Generate RNG mnemonics until the master fingerprint (no passphrase) starts
+/// with `prefix`, giving up after `max_attempts`. Returns the mnemonic and the
+/// number of attempts it took. Candidates are derived in parallel batches but
+/// counted in order, so the count is the same as a sequential search
+pub fn find_vanity_mnemonic(
+    prefix: &str,
+    word_count: usize,
+    language: Language,
+    network: Network,
+    max_attempts: usize,
+) -> Result<(Mnemonic, usize), KeygenError> {
+    let mut attempts = 0;
+    while attempts < max_attempts {
+        let batch = VANITY_BATCH.min(max_attempts - attempts);
+        let candidates = (0..batch)
+            .into_par_iter()
+            .map(|_| {
+                let mnemonic = generate_mnemonic(word_count, language)?;
+                let mut master = derive_master_key(&generate_seed(&mnemonic, ""), network)?;
+                let fingerprint = get_hardware_wallet_fingerprint(&master);
+                master.private_key.non_secure_erase();
+                Ok((mnemonic, fingerprint))
+            })
+            .collect::<Result<Vec<_>, KeygenError>>()?;
+        if let Some((position, (mnemonic, _))) = candidates
+            .into_iter()
+            .enumerate()
+            .find(|(_, (_, fingerprint))| fingerprint.starts_with(prefix))
+        {
+            return Ok((mnemonic, attempts + position + 1));
+        }
+        attempts += batch;
+    }
+    Err(KeygenError::Entropy(format!(
+        "No fingerprint starting with '{}' after {} attempts",
+        prefix, max_attempts
+    )))
+}
+
+/// Size of each sample drawn by [`rng_health_check`]
+pub const RNG_HEALTH_SAMPLE_BYTES: usize = 32;
+
+/// Draw two independent samples from `fill` and fail if they are identical.
+/// A working RNG repeats 256 bits with probability 2^-256, so equality means
+/// it is stuck or returning a constant. The RNG is injected for testing
+pub fn rng_health_check<F>(mut fill: F) -> Result<(), KeygenError>
+where
+    F: FnMut(&mut [u8]) -> Result<(), KeygenError>,
+{
+    let mut first = Zeroizing::new([0u8; RNG_HEALTH_SAMPLE_BYTES]);
+    let mut second = Zeroizing::new([0u8; RNG_HEALTH_SAMPLE_BYTES]);
+    fill(&mut first[..])?;
+    fill(&mut second[..])?;
+    if first == second {
+        return Err(KeygenError::Entropy(
+            "RNG health check failed: two independent draws were identical".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// SHA-256 of the canonical BIP39 `english.txt` (one word per line, trailing
+/// newline), as published in the bitcoin/bips repository
+pub const BIP39_ENGLISH_SHA256: &str =
+    "2f5eed53a4727b4bf8880d8f3f199efc90e58503646d9ff8eff3a2ed3b24dbda";
+
+/// SHA-256 of a wordlist laid out like the BIP39 reference files
+pub fn wordlist_sha256(language: Language) -> sha256::Hash {
+    let mut engine = sha256::Hash::engine();
+    for word in language.word_list() {
+        engine.input(word.as_bytes());
+        engine.input(b"\n");
+    }
+    sha256::Hash::from_engine(engine)
+}
+
+/// Fail if the English wordlist bundled by the `bip39` crate differs from the
+/// canonical list, so a tampered dependency cannot silently change the words
+pub fn check_english_wordlist() -> Result<(), KeygenError> {
+    let actual = wordlist_sha256(Language::English).to_string();
+    if actual != BIP39_ENGLISH_SHA256 {
+        return Err(KeygenError::Integrity(format!(
+            "BIP39 English wordlist integrity check failed: SHA-256 is {}, expected {}",
+            actual, BIP39_ENGLISH_SHA256
+        )));
+    }
+    Ok(())
+}
+
+/// Entropy for `word_count` words from the OS RNG, health- and sanity-checked
+fn random_entropy(word_count: usize) -> Result<Zeroizing<Vec<u8>>, KeygenError> {
+    let entropy_len = entropy_bytes_for_word_count(word_count)
+        .ok_or(KeygenError::InvalidWordCount(word_count))?;
+    rng_health_check(|buf| Ok(getrandom::fill(buf)?))?;
+
+    // up to 256 bits = 24 words, wiped on drop
+    let mut entropy = Zeroizing::new(vec![0u8; entropy_len]);
+    getrandom::fill(&mut entropy)?;
+    entropy_sanity_check(&entropy).map_err(KeygenError::Entropy)?;
+    Ok(entropy)
+}
+
+/// XOR user-supplied entropy into RNG output. The result is at least as
+/// unpredictable as either input, so a compromised RNG alone cannot
+/// determine the seed
+pub fn mix_entropy(random: &[u8], supplied: &[u8]) -> Result<Zeroizing<Vec<u8>>, KeygenError> {
+    if random.len() != supplied.len() {
+        return Err(KeygenError::Entropy(format!(
+            "Mixed entropy must be {} bytes to match the word count, got {}",
+            random.len(),
+            supplied.len()
+        )));
+    }
+    Ok(Zeroizing::new(
+        random.iter().zip(supplied).map(|(a, b)| a ^ b).collect(),
+    ))
+}
+
+/// Generate a mnemonic from OS RNG entropy XORed with `supplied` (`--mix-entropy`)
+pub fn generate_mixed_mnemonic(
+    word_count: usize,
+    language: Language,
+    supplied: &[u8],
+) -> Result<Mnemonic, KeygenError> {
+    let random = random_entropy(word_count)?;
+    mnemonic_from_entropy(mix_entropy(&random, supplied)?, language)
+}
+
+/// Basic sanity check of RNG output before trusting it with a seed.
+/// Rejects constant all-0x00/all-0xFF buffers and fails a monobit frequency
+/// test when the ones/zeros balance is more than 6 standard deviations off
+/// (a false alarm for a healthy RNG is ~1 in 500 million)
+pub fn entropy_sanity_check(bytes: &[u8]) -> Result<(), String> {
+    if bytes.is_empty() {
+        return Err("Entropy self-test failed: empty buffer".to_string());
+    }
+    if bytes.iter().all(|&b| b == 0x00) || bytes.iter().all(|&b| b == 0xFF) {
+        return Err("Entropy self-test failed: RNG returned a constant buffer".to_string());
+    }
+
+    let bits = (bytes.len() * 8) as f64;
+    let ones: u32 = bytes.iter().map(|b| b.count_ones()).sum();
+    let zeros = bits - ones as f64;
+    let z = (ones as f64 - zeros).abs() / bits.sqrt();
+    if z > 6.0 {
+        return Err(format!(
+            "Entropy self-test failed: {} of {} bits set (monobit test)",
+            ones, bits
+        ));
+    }
+
+    Ok(())
+}
+
+/// Number of dice rolls required for a word count (log2(6) ≈ 2.585 bits per roll).
+/// Matches Coldcard: 50 rolls for 12 words, 99 rolls for 24 words
+pub fn dice_rolls_required(word_count: usize) -> usize {
+    match word_count {
+        12 => 50,
+        15 => 62,
+        18 => 75,
+        21 => 87,
+        _ => 99,
+    }
+}
+
+/// Turn dice rolls (digits 1-6, whitespace ignored) into entropy the way
+/// Coldcard does: SHA-256 of the ASCII roll string, truncated to the entropy size
+pub fn dice_entropy(rolls: &str, word_count: usize) -> Result<Zeroizing<Vec<u8>>, KeygenError> {
+    let entropy_len = entropy_bytes_for_word_count(word_count)
+        .ok_or(KeygenError::InvalidWordCount(word_count))?;
+
+    let digits: Zeroizing<String> = Zeroizing::new(
+        rolls
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>(),
+    );
+    if let Some(bad) = digits.chars().find(|c| !('1'..='6').contains(c)) {
+        return Err(KeygenError::Entropy(format!(
+            "Invalid dice roll '{}' (expected digits 1-6)",
+            bad
+        )));
+    }
+
+    let required = dice_rolls_required(word_count);
+    if digits.len() != required {
+        return Err(KeygenError::Entropy(format!(
+            "Wrong number of dice rolls: got {}, need exactly {} for {} words",
+            digits.len(),
+            required,
+            word_count
+        )));
+    }
+
+    let hash = sha256::Hash::hash(digits.as_bytes());
+    Ok(Zeroizing::new(hash.as_byte_array()[..entropy_len].to_vec()))
+}
+
+/// Pack coin flips (`0`/`1`, whitespace ignored) directly into entropy bytes,
+/// most significant bit first. Exactly one flip per entropy bit is required
+pub fn coin_entropy(flips: &str, word_count: usize) -> Result<Zeroizing<Vec<u8>>, KeygenError> {
+    let entropy_len = entropy_bytes_for_word_count(word_count)
+        .ok_or(KeygenError::InvalidWordCount(word_count))?;
+
+    let bits: Zeroizing<String> = Zeroizing::new(
+        flips
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>(),
+    );
+    if let Some(bad) = bits.chars().find(|c| *c != '0' && *c != '1') {
+        return Err(KeygenError::Entropy(format!(
+            "Invalid coin flip '{}' (expected 0 or 1)",
+            bad
+        )));
+    }
+
+    let required = entropy_len * 8;
+    if bits.len() != required {
+        return Err(KeygenError::Entropy(format!(
+            "Wrong number of coin flips: got {}, need exactly {} for {} words",
+            bits.len(),
+            required,
+            word_count
+        )));
+    }
+
+    let mut entropy = Zeroizing::new(vec![0u8; entropy_len]);
+    for (i, bit) in bits.bytes().enumerate() {
+        if bit == b'1' {
+            entropy[i / 8] |= 0x80 >> (i % 8);
+        }
+    }
+    Ok(entropy)
+}
+
+/// Decode `--from-entropy` hex (16/20/24/28/32 bytes) into an entropy buffer
+pub fn parse_entropy_hex(hex: &str) -> Result<Zeroizing<Vec<u8>>, KeygenError> {
+    let hex = hex.trim();
+    if !hex.len().is_multiple_of(2) || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(KeygenError::Entropy(
+            "Invalid entropy: expected an even number of hex digits".to_string(),
+        ));
+    }
+
+    let entropy = Zeroizing::new(
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|e| KeygenError::Entropy(format!("Invalid entropy: {}", e)))?,
+    );
+    if !SUPPORTED_WORD_COUNTS
+        .iter()
+        .any(|&words| entropy_bytes_for_word_count(words) == Some(entropy.len()))
+    {
+        return Err(KeygenError::Entropy(format!(
+            "Invalid entropy length: {} bytes (expected 16, 20, 24, 28 or 32)",
+            entropy.len()
+        )));
+    }
+    Ok(entropy)
+}
+
+/// Build a mnemonic from an entropy buffer, consuming it so the buffer is
+/// zeroized as soon as the mnemonic exists
+pub fn mnemonic_from_entropy<B>(
+    entropy: Zeroizing<B>,
+    language: Language,
+) -> Result<Mnemonic, KeygenError>
+where
+    B: Zeroize + AsRef<[u8]>,
+{
+    let mnemonic = Mnemonic::from_entropy_in(language, (*entropy).as_ref())?;
+    Ok(mnemonic)
+}
+
+/// Parse an existing mnemonic for recovery/verification, validating the checksum
+pub fn recover_mnemonic(phrase: &str, language: Language) -> Result<Mnemonic, KeygenError> {
+    let mut normalized = Cow::Owned(phrase.trim().to_lowercase());
+    Mnemonic::normalize_utf8_cow(&mut normalized);
+    if language != Language::English {
+        normalized = Cow::Owned(resolve_tolerant_words(&normalized, language));
+    }
+
+    Mnemonic::parse_in_normalized(language, &normalized).map_err(|e| {
+        let message = match e {
+            bip39::Error::BadWordCount(count) => format!(
+                "Invalid mnemonic: {} words (expected 12, 15, 18, 21 or 24)",
+                count
+            ),
+            bip39::Error::UnknownWord(index) => unknown_word_message(
+                index,
+                normalized.split_whitespace().nth(index).unwrap_or_default(),
+                language,
+            ),
+            bip39::Error::InvalidChecksum => {
+                let mut message = "Invalid mnemonic: checksum does not match \
+                     (a word is mistyped or out of order)"
+                    .to_string();
+                let words: Vec<&str> = normalized.split_whitespace().collect();
+                let swaps: Vec<String> = find_adjacent_swaps(&normalized, language)
+                    .into_iter()
+                    .map(|i| {
+                        format!(
+                            "words {} '{}' and {} '{}'",
+                            i,
+                            words[i - 1],
+                            i + 1,
+                            words[i]
+                        )
+                    })
+                    .collect();
+                if !swaps.is_empty() {
+                    message.push_str(&format!(
+                        "; swapping {} gives a valid checksum (confirm the \
+                         fingerprint, a wrong swap can pass by chance)",
+                        swaps.join(" or ")
+                    ));
+                }
+                message
+            }
+            other => format!("Invalid mnemonic: {}", other),
+        };
+        KeygenError::MnemonicParse(message)
+    })
+}
+
+/// `word` with its accents and other combining marks removed, so `élève`
+/// compares equal to `eleve`
+fn strip_diacritics(word: &str) -> String {
+    word.nfd()
+        .filter(|&c| !unicode_normalization::char::is_combining_mark(c))
+        .collect()
+}
+
+/// Map each word of a non-English phrase typed without accents, or cut down to
+/// a prefix of at least 4 letters, onto the wordlist entry it uniquely names.
+/// Words that are already valid, or that match no entry or several, are left
+/// as typed so parsing reports them
+pub fn resolve_tolerant_words(phrase: &str, language: Language) -> String {
+    let word_list = language.word_list();
+    let stripped_list: Vec<String> = word_list.iter().map(|w| strip_diacritics(w)).collect();
+    phrase
+        .split_whitespace()
+        .map(|word| {
+            if language.find_word(word).is_some() {
+                return word.to_string();
+            }
+            let typed = strip_diacritics(word);
+            let mut matches: Vec<usize> = (0..word_list.len())
+                .filter(|&i| stripped_list[i] == typed)
+                .collect();
+            if matches.is_empty() && typed.chars().count() >= 4 {
+                matches = (0..word_list.len())
+                    .filter(|&i| stripped_list[i].starts_with(typed.as_str()))
+                    .collect();
+            }
+            match matches.as_slice() {
+                [index] => word_list[*index].nfkd().collect(),
+                _ => word.to_string(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Word count of a phrase to recover, if it is one BIP39 allows. Recovery
+/// never assumes the 24-word default: the phrase decides
+pub fn infer_word_count(phrase: &str) -> Option<usize> {
+    let count = phrase.split_whitespace().count();
+    entropy_bytes_for_word_count(count).map(|_| count)
+}
+
+/// 1-based positions `i` where swapping words `i` and `i + 1` of a phrase with
+/// a bad checksum yields a valid mnemonic (a transposed backup). Any change
+/// passes a 4-8 bit checksum by chance, so these are candidates, not proof
+pub fn find_adjacent_swaps(phrase: &str, language: Language) -> Vec<usize> {
+    let mut normalized = Cow::Owned(phrase.trim().to_lowercase());
+    Mnemonic::normalize_utf8_cow(&mut normalized);
+    let words: Vec<&str> = normalized.split_whitespace().collect();
+
+    (0..words.len().saturating_sub(1))
+        .filter(|&i| words[i] != words[i + 1])
+        .filter(|&i| {
+            let mut swapped = words.clone();
+            swapped.swap(i, i + 1);
+            let candidate = Zeroizing::new(swapped.join(" "));
+            Mnemonic::parse_in_normalized(language, &candidate).is_ok()
+        })
+        .map(|i| i + 1)
+        .collect()
+}
+
+/// Error message for a word missing from the wordlist, with close suggestions
+fn unknown_word_message(index: usize, word: &str, language: Language) -> String {
+    let mut message = format!(
+        "Invalid mnemonic: word {} '{}' is not in the {} wordlist",
+        index + 1,
+        word,
+        language_display_name(language)
+    );
+    let suggestions = suggest_words(word, language);
+    if !suggestions.is_empty() {
+        message.push_str(&format!("; did you mean: {}?", suggestions.join(", ")));
+    }
+    message
+}
+
+/// Every valid final word for a phrase missing its last word (e.g. 23 of 24).
+/// The last word carries the remaining entropy bits plus the checksum, so
+/// there are 2^(free bits) candidates: 8 for 24 words up to 128 for 12 words
+pub fn last_word_candidates(
+    partial: &str,
+    language: Language,
+) -> Result<Vec<&'static str>, KeygenError> {
+    let mut normalized = Cow::Owned(partial.trim().to_lowercase());
+    Mnemonic::normalize_utf8_cow(&mut normalized);
+    let indices = normalized
+        .split_whitespace()
+        .enumerate()
+        .map(|(i, word)| {
+            language
+                .find_word(word)
+                .ok_or_else(|| KeygenError::MnemonicParse(unknown_word_message(i, word, language)))
+        })
+        .collect::<Result<Vec<u16>, _>>()?;
+
+    let entropy_len = entropy_bytes_for_word_count(indices.len() + 1).ok_or_else(|| {
+        KeygenError::MnemonicParse(format!(
+            "Need 11, 14, 17, 20 or 23 words to compute the last word, got {}",
+            indices.len()
+        ))
+    })?;
+    let free_bits = entropy_len * 8 - indices.len() * 11;
+
+    let mut candidates = Vec::new();
+    for candidate in 0u32..(1 << free_bits) {
+        let bits = indices
+            .iter()
+            .flat_map(|&index| (0..11).rev().map(move |bit| (index >> bit) & 1 == 1))
+            .chain((0..free_bits).rev().map(|bit| (candidate >> bit) & 1 == 1));
+        let mut entropy = Zeroizing::new(vec![0u8; entropy_len]);
+        for (i, bit) in bits.enumerate() {
+            if bit {
+                entropy[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        let mnemonic = Mnemonic::from_entropy_in(language, &entropy)?;
+        candidates.extend(mnemonic.words().last());
+    }
+    Ok(candidates)
+}
+
+/// Extract the phrase from a saved word list such as `seed_words_simple.txt`
+/// (`" 1. abandon"` lines) or `seed_words_for_coldcard.txt` (one word per
+/// line). Words may be separated by newlines or spaces; `#` comment lines and
+/// numbered prefixes like `1.`, `1)` or `1:` are skipped
+pub fn phrase_from_word_list(contents: &str) -> Zeroizing<String> {
+    let mut phrase = Zeroizing::new(String::new());
+    let lines = contents
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('#'));
+    for token in lines.flat_map(str::split_whitespace) {
+        let word = token
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .trim_start_matches(['.', ')', ':']);
+        if word.is_empty() {
+            continue;
+        }
+        if !phrase.is_empty() {
+            phrase.push(' ');
+        }
+        phrase.push_str(word);
+    }
+    phrase
+}
+
+/// Levenshtein edit distance between two words (insertions, deletions, substitutions)
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Closest wordlist entries to a mistyped word: up to 3 words within an edit
+/// distance of 2, nearest first
+pub fn suggest_words(word: &str, language: Language) -> Vec<&'static str> {
+    let mut candidates: Vec<(usize, &'static str)> = language
+        .word_list()
+        .iter()
+        .map(|candidate| (levenshtein(word, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .collect();
+    candidates.sort();
+    candidates.into_iter().take(3).map(|(_, w)| w).collect()
+}
+
+/// Electrum seed version prefix for native segwit wallets
+pub const ELECTRUM_SEGWIT_PREFIX: &str = "100";
+
+/// Electrum seed version: hex of HMAC-SHA512(key = "Seed version", msg = seed)
+pub fn electrum_seed_version(seed: &str) -> String {
+    let mut engine = hmac::HmacEngine::<sha512::Hash>::new(b"Seed version");
+    engine.input(seed.as_bytes());
+    hmac::Hmac::<sha512::Hash>::from_engine(engine).to_string()
+}
+
+/// Whether a phrase is an Electrum "new style" native segwit seed
+pub fn is_electrum_segwit_seed(seed: &str) -> bool {
+    electrum_seed_version(seed).starts_with(ELECTRUM_SEGWIT_PREFIX)
+}
+
+/// Generate a 12-word Electrum segwit seed the way Electrum does: take a random
+/// 132-bit number, encode it in the English wordlist as base-2048 digits (least
+/// significant first) and increment it until the seed version prefix is `100`.
+/// Phrases that also happen to be valid BIP39 are skipped to avoid ambiguity.
+/// (Electrum's pre-2.0 "old seed" wordlist check is omitted: a random phrase
+/// from the BIP39 list essentially never consists solely of old-list words.)
+/// The result is NOT a BIP39 mnemonic
+pub fn generate_electrum_seed() -> Result<Zeroizing<String>, KeygenError> {
+    let word_list = Language::English.word_list();
+
+    let mut digits = Zeroizing::new([0u16; 12]);
+    let mut random = Zeroizing::new([0u8; 24]);
+    // Electrum requires the number to use the full 12 words (top digit non-zero)
+    while digits[11] == 0 {
+        getrandom::fill(&mut random[..])?;
+        for (digit, pair) in digits.iter_mut().zip(random.chunks(2)) {
+            *digit = u16::from_be_bytes([pair[0], pair[1]]) & 0x07ff;
+        }
+    }
+
+    loop {
+        // entropy + nonce, with carry across base-2048 digits
+        for digit in digits.iter_mut() {
+            *digit = (*digit + 1) & 0x07ff;
+            if *digit != 0 {
+                break;
+            }
+        }
+
+        let seed = Zeroizing::new(
+            digits
+                .iter()
+                .map(|&d| word_list[d as usize])
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+        if Mnemonic::parse_in_normalized(Language::English, &seed).is_ok() {
+            continue;
+        }
+        if is_electrum_segwit_seed(&seed) {
+            return Ok(seed);
+        }
+    }
+}
+
+/// Generation time written into the output files
+pub type Timestamp = DateTime<FixedOffset>;
+
+/// The current local time as a [`Timestamp`]
+pub fn now_timestamp() -> Timestamp {
+    Local::now().fixed_offset()
+}
+
+/// `Generated:` line format used by the text and PDF outputs
+pub fn format_timestamp(timestamp: &Timestamp) -> String {
+    timestamp.format("%Y-%m-%d %H:%M:%S").to_string()
+}
+
+/// Create the Electrum seed file
+pub fn create_electrum_seed_output(seed: &str, label: &str, generated: &Timestamp) -> String {
+    let mut output = String::new();
+    output.push_str("ELECTRUM SEED (segwit) - NOT A BIP39 MNEMONIC\n");
+    output.push_str(&format!("Label: {}\n", label));
+    output.push_str(&format!("Generated: {}\n\n", format_timestamp(generated)));
+    output.push_str("Restore in Electrum with \"Standard wallet\" > \"I already have a seed\".\n");
+    output.push_str("Do NOT enter these words into a BIP39 hardware wallet.\n\n");
+    for (i, word) in seed.split(' ').enumerate() {
+        output.push_str(&format!("{:2}. {}\n", i + 1, word));
+    }
+    output
+}
+
+/// Lowercase hex of secret bytes (wiped on drop)
+fn secret_hex(bytes: &[u8]) -> Zeroizing<String> {
+    let mut hex = Zeroizing::new(String::with_capacity(bytes.len() * 2));
+    for byte in bytes {
+        hex.push_str(&format!("{:02x}", byte));
+    }
+    hex
+}
+
+/// Lowercase hex of the mnemonic's raw entropy (wiped on drop)
+pub fn entropy_hex(mnemonic: &Mnemonic) -> Zeroizing<String> {
+    secret_hex(&Zeroizing::new(mnemonic.to_entropy()))
+}
+
+/// Bits per row of [`format_entropy_grid`] (256 bits make a 16x16 square)
+pub const ENTROPY_GRID_WIDTH: usize = 16;
+
+/// Draw entropy as a grid of cells, most significant bit first, `#` for a 1
+/// bit and `.` for a 0, for comparing two generations by eye
+pub fn format_entropy_grid(entropy: &[u8]) -> Zeroizing<String> {
+    let mut output = Zeroizing::new(String::new());
+    let bits: Zeroizing<Vec<bool>> = Zeroizing::new(
+        entropy
+            .iter()
+            .flat_map(|byte| (0..8).rev().map(move |bit| byte >> bit & 1 == 1))
+            .collect(),
+    );
+    for row in bits.chunks(ENTROPY_GRID_WIDTH) {
+        let cells: Vec<&str> = row.iter().map(|&set| if set { "#" } else { "." }).collect();
+        output.push_str(&cells.join(" "));
+        output.push('\n');
+    }
+    output
+}
+
+/// Create the entropy grid file (as sensitive as the seed words)
+pub fn create_entropy_grid_output(mnemonic: &Mnemonic) -> Zeroizing<String> {
+    let entropy = Zeroizing::new(mnemonic.to_entropy());
+    let mut output = Zeroizing::new(format!(
+        "Entropy grid: {} bits, {} per row, first bit top left (# = 1, . = 0)\n",
+        entropy.len() * 8,
+        ENTROPY_GRID_WIDTH
+    ));
+    output.push_str("SECRET: this encodes the seed - keep it as secure as the words\n\n");
+    output.push_str(&format_entropy_grid(&entropy));
+    output
+}
+
+/// Lowercase hex of the 64-byte BIP39 seed (wiped on drop)
+pub fn seed_hex(seed: &[u8; 64]) -> Zeroizing<String> {
+    secret_hex(seed)
+}
+
+/// BIP39 checksum for `entropy`: returns (number of checksum bits, value),
+/// where the value is the first ENT/32 bits of SHA-256(entropy)
+pub fn mnemonic_checksum_bits(entropy: &[u8]) -> (u8, u8) {
+    let bits = (entropy.len() * 8 / 32) as u8;
+    let hash = sha256::Hash::hash(entropy);
+    (bits, hash.to_byte_array()[0] >> (8 - bits))
+}
+
+/// PBKDF2-HMAC-SHA512 iterations mandated by BIP39 for seed derivation
+pub const BIP39_PBKDF2_ROUNDS: u32 = 2048;
+
+/// Recompute the seed with a standalone PBKDF2-HMAC-SHA512 (password = NFKD
+/// sentence, salt = "mnemonic" + NFKD passphrase, 2048 rounds, a single 64-byte
+/// block) and check it equals [`generate_seed`], guarding against a bug in either
+pub fn verify_seed_derivation(mnemonic: &Mnemonic, passphrase: &str) -> bool {
+    let words: Vec<&str> = mnemonic.words().collect();
+    let password: Zeroizing<String> = Zeroizing::new(words.join(" ").nfkd().collect());
+    let mut salt: Zeroizing<Vec<u8>> = Zeroizing::new(
+        format!("mnemonic{}", passphrase)
+            .nfkd()
+            .collect::<String>()
+            .into_bytes(),
+    );
+    salt.extend_from_slice(&1u32.to_be_bytes());
+
+    let keyed = hmac::HmacEngine::<sha512::Hash>::new(password.as_bytes());
+    let prf = |data: &[u8]| {
+        let mut engine = keyed.clone();
+        engine.input(data);
+        hmac::Hmac::from_engine(engine).to_byte_array()
+    };
+
+    let mut block = Zeroizing::new(prf(&salt));
+    let mut seed = Zeroizing::new(*block);
+    for _ in 1..BIP39_PBKDF2_ROUNDS {
+        *block = prf(&*block);
+        for (out, byte) in seed.iter_mut().zip(block.iter()) {
+            *out ^= byte;
+        }
+    }
+
+    *seed == *generate_seed(mnemonic, passphrase)
+}
+
+/// BIP39 seed of the all-`abandon` 12-word test vector (no passphrase)
+pub const SELFTEST_SEED_HEX: &str =
+    "5eb00bbddcf069084889a8ab9155568165f5c453ccb85e70811aaed6f6da5fc1\
+     9a5ac40b389cd370d086206dec8aa6c43daea6690f20ad3d8d48b2d2ce9e38e4";
+
+/// First BIP84 receive address (m/84'/0'/0'/0/0) of the all-`abandon` vector
+pub const SELFTEST_BIP84_ADDRESS: &str = "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu";
+
+/// One line of the `--selftest` report
+#[derive(Debug)]
+pub struct SelfTestCheck {
+    pub name: &'static str,
+    pub result: Result<(), KeygenError>,
+}
+
+/// Run the pre-flight checks behind `--selftest`: wordlist integrity, RNG
+/// distinctness, PBKDF2 against the all-`abandon` vector and BIP84 address
+/// derivation from it. Every check runs even if an earlier one fails
+pub fn run_selftest() -> Vec<SelfTestCheck> {
+    let vector = || mnemonic_from_entropy(Zeroizing::new([0u8; 16]), Language::English);
+    let pbkdf2 = || {
+        let mnemonic = vector()?;
+        let seed = generate_seed(&mnemonic, "");
+        if *secret_hex(&seed[..]) != SELFTEST_SEED_HEX || !verify_seed_derivation(&mnemonic, "") {
+            return Err(KeygenError::Integrity(
+                "seed does not match the BIP39 test vector".to_string(),
+            ));
+        }
+        Ok(())
+    };
+    let bip84 = || {
+        let mnemonic = vector()?;
+        let mut master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin)?;
+        let addresses = derive_addresses_for(&master, Network::Bitcoin, &[84], 1);
+        master.private_key.non_secure_erase();
+        match addresses?.first() {
+            Some((_, address)) if address == SELFTEST_BIP84_ADDRESS => Ok(()),
+            other => Err(KeygenError::Integrity(format!(
+                "derived {}, expected {}",
+                other.map_or("nothing", |(_, address)| address.as_str()),
+                SELFTEST_BIP84_ADDRESS
+            ))),
+        }
+    };
+
+    vec![
+        SelfTestCheck {
+            name: "BIP39 English wordlist integrity",
+            result: check_english_wordlist(),
+        },
+        SelfTestCheck {
+            name: "RNG distinctness",
+            result: rng_health_check(|buf| Ok(getrandom::fill(buf)?)),
+        },
+        SelfTestCheck {
+            name: "PBKDF2 seed derivation (all-abandon vector)",
+            result: pbkdf2(),
+        },
+        SelfTestCheck {
+            name: "BIP84 address derivation (all-abandon vector)",
+            result: bip84(),
+        },
+    ]
+}
+
+/// Rough strength class of a BIP39 passphrase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strength {
+    /// Under 50 bits: guessable by an offline brute force on the seed
+    Weak,
+    /// 50 to 80 bits
+    Moderate,
+    /// 80 bits or more
+    Strong,
+}
+
+/// Estimated brute-force entropy in bits: length times log2 of the alphabet
+/// implied by the character classes used. This overestimates dictionary words
+/// and patterns, so it only flags passphrases that are weak even in the best case
+pub fn passphrase_entropy_bits(pw: &str) -> f64 {
+    let mut pool = 0u32;
+    if pw.chars().any(|c| c.is_ascii_lowercase()) {
+        pool += 26;
+    }
+    if pw.chars().any(|c| c.is_ascii_uppercase()) {
+        pool += 26;
+    }
+    if pw.chars().any(|c| c.is_ascii_digit()) {
+        pool += 10;
+    }
+    if pw.chars().any(|c| c.is_ascii_punctuation() || c == ' ') {
+        pool += 33;
+    }
+    if !pw.is_ascii() {
+        pool += 100;
+    }
+    if pool == 0 {
+        return 0.0;
+    }
+    pw.chars().count() as f64 * f64::from(pool).log2()
+}
+
+/// True when the passphrase is not already in Unicode NFKD form. BIP39
+/// hashes the NFKD-normalized passphrase, but a wallet that skips or gets
+/// normalization wrong derives a different seed from visually identical
+/// text (e.g. a precomposed `é` versus `e` + combining accent)
+pub fn passphrase_needs_normalization(pw: &str) -> bool {
+    !unicode_normalization::is_nfkd(pw)
+}
+
+/// Classify a passphrase by its estimated entropy
+pub fn assess_passphrase_strength(pw: &str) -> Strength {
+    let bits = passphrase_entropy_bits(pw);
+    if bits < 50.0 {
+        Strength::Weak
+    } else if bits < 80.0 {
+        Strength::Moderate
+    } else {
+        Strength::Strong
+    }
+}
+
+/// Compare two secrets (quiz answers, passphrase confirmations) in constant
+/// time for equal lengths, so the position of the first mismatch does not leak
+/// through timing. Only the lengths are compared in variable time
+pub fn secure_compare(a: &[u8], b: &[u8]) -> bool {
+    bool::from(a.ct_eq(b))
+}
+
+/// Magic bytes at the start of every `.enc` file
+pub const ENCRYPTION_MAGIC: &[u8; 8] = b"BKGENC01";
+pub const ENCRYPTION_SALT_LEN: usize = 16;
+pub const ENCRYPTION_NONCE_LEN: usize = 12;
+pub const ENCRYPTION_HEADER_LEN: usize =
+    ENCRYPTION_MAGIC.len() + ENCRYPTION_SALT_LEN + ENCRYPTION_NONCE_LEN;
+
+/// Stretch a password into an AES-256 key with Argon2id (default parameters)
+pub fn derive_encryption_key(
+    password: &str,
+    salt: &[u8],
+) -> Result<Zeroizing<[u8; 32]>, KeygenError> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    argon2::Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, &mut key[..])
+        .map_err(|e| KeygenError::Encryption(format!("Key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+/// Encrypt a payload with AES-256-GCM under an Argon2id password key.
+/// Layout: magic (8) | salt (16) | nonce (12) | ciphertext + tag; the header is
+/// authenticated as associated data
+pub fn encrypt_payload(plaintext: &[u8], password: &str) -> Result<Vec<u8>, KeygenError> {
+    let mut header = Vec::with_capacity(ENCRYPTION_HEADER_LEN);
+    header.extend_from_slice(ENCRYPTION_MAGIC);
+    let mut salt_and_nonce = [0u8; ENCRYPTION_SALT_LEN + ENCRYPTION_NONCE_LEN];
+    getrandom::fill(&mut salt_and_nonce)?;
+    header.extend_from_slice(&salt_and_nonce);
+
+    let (salt, nonce) = salt_and_nonce.split_at(ENCRYPTION_SALT_LEN);
+    let key = derive_encryption_key(password, salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key[..]));
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: plaintext,
+                aad: &header,
+            },
+        )
+        .map_err(|_| KeygenError::Encryption("Encryption failed".to_string()))?;
+
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+/// Decrypt a payload produced by `encrypt_payload`
+pub fn decrypt_payload(data: &[u8], password: &str) -> Result<Zeroizing<Vec<u8>>, KeygenError> {
+    if data.len() < ENCRYPTION_HEADER_LEN || !data.starts_with(ENCRYPTION_MAGIC) {
+        return Err(KeygenError::Encryption(
+            "Not a bitcoin-keygen encrypted file".to_string(),
+        ));
+    }
+    let (header, ciphertext) = data.split_at(ENCRYPTION_HEADER_LEN);
+    let salt = &header[ENCRYPTION_MAGIC.len()..ENCRYPTION_MAGIC.len() + ENCRYPTION_SALT_LEN];
+    let nonce = &header[ENCRYPTION_MAGIC.len() + ENCRYPTION_SALT_LEN..];
+
+    let key = derive_encryption_key(password, salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key[..]));
+    let plaintext = cipher
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| {
+            KeygenError::Encryption(
+                "Decryption failed: wrong password or corrupted file".to_string(),
+            )
+        })?;
+    Ok(Zeroizing::new(plaintext))
+}
+
+/// Parse an age X25519 recipient public key (`age1...`)
+pub fn parse_age_recipient(recipient: &str) -> Result<age::x25519::Recipient, KeygenError> {
+    recipient.trim().parse().map_err(|e| {
+        KeygenError::InvalidArgument(format!("Invalid age recipient '{}': {}", recipient, e))
+    })
+}
+
+/// Encrypt a payload to an age recipient (binary age format, readable by `age -d`)
+pub fn encrypt_to_age(
+    plaintext: &[u8],
+    recipient: &age::x25519::Recipient,
+) -> Result<Vec<u8>, KeygenError> {
+    age::encrypt(recipient, plaintext)
+        .map_err(|e| KeygenError::Encryption(format!("age encryption failed: {}", e)))
+}
+
+/// Decrypt a payload produced by `encrypt_to_age` with the matching identity
+pub fn decrypt_age(
+    data: &[u8],
+    identity: &age::x25519::Identity,
+) -> Result<Zeroizing<Vec<u8>>, KeygenError> {
+    age::decrypt(identity, data)
+        .map(Zeroizing::new)
+        .map_err(|e| KeygenError::Encryption(format!("age decryption failed: {}", e)))
+}
+
+/// Parse an existing master xprv/tprv, rejecting account-level keys (depth > 0)
+/// and keys whose version bytes do not match `network`
+pub fn parse_master_xprv(xprv: &str, network: Network) -> Result<Xpriv, KeygenError> {
+    let key = Xpriv::from_str(xprv.trim()).map_err(|e| {
+        KeygenError::InvalidArgument(format!("Invalid extended private key: {}", e))
+    })?;
+    if key.network != NetworkKind::from(network) {
+        let kind = match key.network {
+            NetworkKind::Main => "mainnet (xprv)",
+            NetworkKind::Test => "a test network (tprv)",
+        };
+        return Err(KeygenError::InvalidArgument(format!(
+            "Extended private key is for {} but --network is {}",
+            kind,
+            network_display_name(network)
+        )));
+    }
+    if key.depth != 0 {
+        return Err(KeygenError::InvalidArgument(format!(
+            "Extended private key must be a master key (depth 0), got depth {}",
+            key.depth
+        )));
+    }
+    Ok(key)
+}
+
+/// Fingerprint of the key at `path` (e.g. the account key at m/84'/0'/0'),
+/// in the same format as the master fingerprint. Some wallets show this one
+/// instead of (or next to) the master fingerprint
+pub fn account_fingerprint(master: &Xpriv, path: &DerivationPath) -> Result<String, KeygenError> {
+    let account = master.derive_priv(&Secp256k1::new(), path)?;
+    Ok(get_hardware_wallet_fingerprint(&account))
+}
+
+/// Purpose of LND's BIP32 key chain (m/1017'/coin'/family'/0/index)
+pub const LIGHTNING_PURPOSE: u32 = 1017;
+
+/// LND key family of the node identity key
+pub const LIGHTNING_NODE_KEY_FAMILY: u32 = 6;
+
+/// Node-level key of the LND-style derivation m/1017'/coin'/6'
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LightningNodeKey {
+    /// Key family path m/1017'/coin'/6'
+    pub path: DerivationPath,
+    /// Fingerprint of the key family xpriv
+    pub fingerprint: String,
+    /// Compressed node identity public key (family key /0/0) in hex
+    pub node_pubkey: String,
+}
+
+/// Derive the lightning node key summary that some BIP32-based lightning
+/// setups use. This is NOT an LND aezeed: LND's own 24-word seeds use a
+/// different scheme and cannot be created or restored from BIP39 words
+pub fn lightning_node_key(
+    master: &Xpriv,
+    network: Network,
+) -> Result<LightningNodeKey, KeygenError> {
+    let path: DerivationPath = format!(
+        "m/{}'/{}'/{}'",
+        LIGHTNING_PURPOSE,
+        coin_type(network),
+        LIGHTNING_NODE_KEY_FAMILY
+    )
+    .parse()?;
+    let secp = Secp256k1::new();
+    let family = master.derive_priv(&secp, &path)?;
+    let node = family.derive_priv(
+        &secp,
+        &[
+            ChildNumber::from_normal_idx(0)?,
+            ChildNumber::from_normal_idx(0)?,
+        ],
+    )?;
+
+    Ok(LightningNodeKey {
+        fingerprint: get_hardware_wallet_fingerprint(&family),
+        node_pubkey: Xpub::from_priv(&secp, &node).public_key.to_string(),
+        path,
+    })
+}
+
+/// BIP44 coin type: 0' for mainnet, 1' for every test network
+pub fn coin_type(network: Network) -> u32 {
+    match network {
+        Network::Bitcoin => 0,
+        _ => 1,
+    }
+}
+
+/// Standard single-sig script types as BIP purposes, in output order: BIP84
+/// (native segwit), BIP49 (wrapped segwit), BIP44 (legacy) and BIP86 (taproot)
+pub const ALL_PURPOSES: [u32; 4] = [84, 49, 44, 86];
+
+/// Derive the first `count` receive addresses for every script type in
+/// [`ALL_PURPOSES`], returned as (path, address)
+pub fn derive_addresses(
+    xpriv: &Xpriv,
+    network: Network,
+    count: usize,
+) -> Result<Vec<(String, String)>, KeygenError> {
+    derive_addresses_for(xpriv, network, &ALL_PURPOSES, count)
+}
+
+/// Derive the first `count` receive addresses for each of `purposes`,
+/// returned as (path, address)
+pub fn derive_addresses_for(
+    xpriv: &Xpriv,
+    network: Network,
+    purposes: &[u32],
+    count: usize,
+) -> Result<Vec<(String, String)>, KeygenError> {
+    let secp = Secp256k1::new();
+    let mut addresses = Vec::new();
+
+    for &purpose in purposes {
+        for index in 0..count {
+            let path_str = format!("m/{}'/{}'/0'/0/{}", purpose, coin_type(network), index);
+            let path: DerivationPath = path_str.parse()?;
+            let child = xpriv.derive_priv(&secp, &path)?;
+            let address = address_for_purpose(&Xpub::from_priv(&secp, &child), purpose, network);
+            addresses.push((path_str, address.to_string()));
+        }
+    }
+
+    Ok(addresses)
+}
+
+/// Time [`generate_seed`] (PBKDF2-HMAC-SHA512, 2048 rounds), the dominant
+/// cost of every wallet
+pub fn time_seed_derivation(mnemonic: &Mnemonic, passphrase: &str) -> Duration {
+    let start = Instant::now();
+    let seed = generate_seed(mnemonic, passphrase);
+    let elapsed = start.elapsed();
+    drop(seed);
+    elapsed
+}
+
+/// Time the [`derive_addresses`] loop for `count` addresses per script type
+pub fn time_address_derivation(
+    master: &Xpriv,
+    network: Network,
+    count: usize,
+) -> Result<Duration, KeygenError> {
+    let start = Instant::now();
+    derive_addresses(master, network, count)?;
+    Ok(start.elapsed())
+}
+
+/// Single-key address for an address-level key under a standard purpose
+/// (84 = p2wpkh, 49 = p2sh-p2wpkh, 86 = p2tr, anything else = p2pkh)
+pub fn address_for_purpose(xpub: &Xpub, purpose: u32, network: Network) -> Address {
+    let secp = Secp256k1::verification_only();
+    let pubkey = CompressedPublicKey(xpub.public_key);
+
+    match purpose {
+        84 => Address::p2wpkh(&pubkey, network),
+        49 => Address::p2shwpkh(&pubkey, network),
+        // BIP86 key-path spend: p2tr applies the TapTweak to the x-only
+        // internal key with no script tree (merkle root = None)
+        86 => Address::p2tr(&secp, pubkey.0.x_only_public_key().0, None, network),
+        _ => Address::p2pkh(pubkey, network),
+    }
+}
+
+/// Output script (scriptPubKey) paying to an address-level key under a
+/// standard purpose, using the same purpose mapping as [`address_for_purpose`]
+pub fn script_for_purpose(xpub: &Xpub, purpose: u32) -> ScriptBuf {
+    let pubkey = CompressedPublicKey(xpub.public_key);
+
+    match purpose {
+        84 => ScriptBuf::new_p2wpkh(&pubkey.wpubkey_hash()),
+        49 => ScriptBuf::new_p2sh(&ScriptBuf::new_p2wpkh(&pubkey.wpubkey_hash()).script_hash()),
+        86 => ScriptBuf::new_p2tr(
+            &Secp256k1::verification_only(),
+            pubkey.0.x_only_public_key().0,
+            None,
+        ),
+        _ => ScriptBuf::new_p2pkh(&pubkey.pubkey_hash()),
+    }
+}
+
+/// Script type name for a standard single-sig purpose
+pub fn script_type_name(purpose: u32) -> &'static str {
+    match purpose {
+        84 => "p2wpkh",
+        49 => "p2sh-p2wpkh",
+        86 => "p2tr",
+        _ => "p2pkh",
+    }
+}
+
+/// The first `count` addresses on one chain (0 = receive, 1 = change) of
+/// account 0 for `purpose`, as (path, address) pairs. Addresses are built
+/// from each key's output script
+pub fn derive_chain_addresses(
+    xpriv: &Xpriv,
+    network: Network,
+    purpose: u32,
+    chain: u32,
+    count: usize,
+) -> Result<Vec<(String, String)>, KeygenError> {
+    let secp = Secp256k1::new();
+    let account = xpriv.derive_priv(&secp, &account_derivation_path(network, purpose, 0)?)?;
+    let chain_key = account.derive_priv(&secp, &[ChildNumber::from_normal_idx(chain)?])?;
+
+    (0..count)
+        .map(|index| {
+            let child =
+                chain_key.derive_priv(&secp, &[ChildNumber::from_normal_idx(index as u32)?])?;
+            let script = script_for_purpose(&Xpub::from_priv(&secp, &child), purpose);
+            let address = Address::from_script(&script, network)?;
+            let path = format!(
+                "m/{}'/{}'/0'/{}/{}",
+                purpose,
+                coin_type(network),
+                chain,
+                index
+            );
+            Ok((path, address.to_string()))
+        })
+        .collect()
+}
+
+/// Create `address_labels.csv` for `--label-addresses`: a header, then one
+/// `address,` row per address with the label left empty for the user to fill
+/// in before importing it into a wallet that supports address labels
+pub fn create_address_labels_csv(addresses: &[(String, String)]) -> String {
+    let mut output = String::from("address,label\n");
+    for (_, address) in addresses {
+        output.push_str(address);
+        output.push_str(",\n");
+    }
+    output
+}
+
+/// Table of receive and change addresses for `--preview-addresses`, to compare
+/// against the hardware wallet's address list before importing
+pub fn create_address_preview(
+    purpose: u32,
+    receive: &[(String, String)],
+    change: &[(String, String)],
+) -> String {
+    let width = receive
+        .iter()
+        .chain(change)
+        .map(|(path, _)| path.len())
+        .max()
+        .unwrap_or(0)
+        .max("Path".len());
+
+    let mut output = String::new();
+    output.push_str(&format!(
+        "Address preview: BIP{} {} account 0\n",
+        purpose,
+        script_type_name(purpose)
+    ));
+    for (title, addresses) in [("Receive", receive), ("Change", change)] {
+        output.push_str(&format!("\n{}:\n", title));
+        output.push_str(&format!("  {:<width$}  Address\n", "Path", width = width));
+        for (path, address) in addresses {
+            output.push_str(&format!("  {:<width$}  {}\n", path, address, width = width));
+        }
+    }
+    output
+}
+
+/// Keys derived at a user-supplied `--derive` path
+pub struct CustomDerivation {
+    pub path: DerivationPath,
+    pub xprv: Xpriv,
+    pub xpub: Xpub,
+    /// Present when the path is a standard BIP44/49/84/86 address-level path
+    pub address: Option<String>,
+}
+
+impl Drop for CustomDerivation {
+    fn drop(&mut self) {
+        self.xprv.private_key.non_secure_erase();
+    }
+}
+
+/// Parse a `--derive` path such as `m/48'/0'/0'/2'`
+pub fn parse_derivation_path(path: &str) -> Result<DerivationPath, KeygenError> {
+    DerivationPath::from_str(path.trim()).map_err(|e| {
+        KeygenError::InvalidArgument(format!(
+            "Invalid derivation path '{}': {} (expected e.g. m/48'/0'/0'/2')",
+            path, e
+        ))
+    })
+}
+
+/// Derive the xprv/xpub at an arbitrary path, plus the address when the path
+/// is m/purpose'/coin'/account'/change/index for a standard single-sig purpose
+pub fn derive_custom_path(
+    master: &Xpriv,
+    network: Network,
+    path: &DerivationPath,
+) -> Result<CustomDerivation, KeygenError> {
+    let secp = Secp256k1::new();
+    let xprv = master.derive_priv(&secp, path)?;
+    let xpub = Xpub::from_priv(&secp, &xprv);
+
+    let address = match path.as_ref() {
+        [ChildNumber::Hardened { index: purpose }, ChildNumber::Hardened { .. }, ChildNumber::Hardened { .. }, ChildNumber::Normal { .. }, ChildNumber::Normal { .. }]
+            if [44, 49, 84, 86].contains(purpose) =>
+        {
+            Some(address_for_purpose(&xpub, *purpose, network).to_string())
+        }
+        _ => None,
+    };
+
+    Ok(CustomDerivation {
+        path: path.clone(),
+        xprv,
+        xpub,
+        address,
+    })
+}
+
+/// Derive the account-level xpub at m/purpose'/coin'/account' for watch-only wallets
+pub fn derive_account_xpub(
+    master: &Xpriv,
+    network: Network,
+    purpose: u32,
+    account: u32,
+) -> Result<Xpub, KeygenError> {
+    let secp = Secp256k1::new();
+    let path = account_derivation_path(network, purpose, account)?;
+    let account_key = master.derive_priv(&secp, &path)?;
+    Ok(Xpub::from_priv(&secp, &account_key))
+}
+
+/// Account-level derivation path m/purpose'/coin'/account'
+pub fn account_derivation_path(
+    network: Network,
+    purpose: u32,
+    account: u32,
+) -> Result<DerivationPath, KeygenError> {
+    let path = format!("m/{}'/{}'/{}'", purpose, coin_type(network), account).parse()?;
+    Ok(path)
+}
+
+/// Encode an xpub with SLIP-132 version bytes (ypub/zpub on mainnet, upub/vpub on
+/// test networks) so wallets that rely on the prefix pick the right script type.
+/// Purposes without a SLIP-132 prefix fall back to the standard xpub/tpub encoding
+pub fn slip132_encode(xpub: &Xpub, purpose: u32) -> String {
+    let version: [u8; 4] = match (purpose, xpub.network.is_mainnet()) {
+        (49, true) => [0x04, 0x9d, 0x7c, 0xb2],  // ypub
+        (49, false) => [0x04, 0x4a, 0x52, 0x62], // upub
+        (84, true) => [0x04, 0xb2, 0x47, 0x46],  // zpub
+        (84, false) => [0x04, 0x5f, 0x1c, 0xf6], // vpub
+        _ => return xpub.to_string(),
+    };
+
+    let mut data = xpub.encode();
+    data[..4].copy_from_slice(&version);
+    bitcoin::base58::encode_check(&data)
+}
+
+/// Create the watch-only account xpub file
+pub fn create_account_xpub_output(
+    xpub: &Xpub,
+    path: &DerivationPath,
+    fingerprint: &str,
+    purpose: u32,
+) -> String {
+    let mut output = String::new();
+    output.push_str("Account xpub (watch-only - cannot spend funds)\n");
+    output.push_str(&format!("Master fingerprint: {}\n", fingerprint));
+    output.push_str(&format!("Derivation path: m/{}\n", path));
+    output.push_str(&format!("xpub: {}\n", xpub));
+    let slip132 = slip132_encode(xpub, purpose);
+    if slip132 != xpub.to_string() {
+        output.push_str(&format!("{}: {}\n", &slip132[..4], slip132));
+    }
+    output
+}
+
+/// Account-level keys for one standard purpose (BIP44/49/84/86, account 0)
+pub struct AccountInfo {
+    pub purpose: u32,
+    pub path: String,
+    pub xprv: Xpriv,
+    pub xpub: Xpub,
+}
+
+impl Drop for AccountInfo {
+    fn drop(&mut self) {
+        // `Xpriv` is `Copy` and has no `Drop` of its own
+        self.xprv.private_key.non_secure_erase();
+    }
+}
+
+/// Derive the account-level xprv/xpub at m/purpose'/coin'/0' for purposes
+/// 44 (legacy), 49 (nested segwit), 84 (native segwit) and 86 (taproot)
+pub fn account_summary(master: &Xpriv, network: Network) -> Vec<AccountInfo> {
+    let secp = Secp256k1::new();
+
+    [44, 49, 84, 86]
+        .into_iter()
+        .map(|purpose| {
+            let path = DerivationPath::from(vec![
+                ChildNumber::Hardened { index: purpose },
+                ChildNumber::Hardened {
+                    index: coin_type(network),
+                },
+                ChildNumber::Hardened { index: 0 },
+            ]);
+            let xprv = master
+                .derive_priv(&secp, &path)
+                .expect("hardened derivation from a valid master key");
+            AccountInfo {
+                purpose,
+                path: format!("m/{}", path),
+                xprv,
+                xpub: Xpub::from_priv(&secp, &xprv),
+            }
+        })
+        .collect()
+}
+
+/// Create the account summary file (contains xprvs - as sensitive as the seed)
+pub fn create_accounts_output(accounts: &[AccountInfo], fingerprint: &str) -> String {
+    let mut output = String::new();
+    output.push_str("Account keys (xprv CAN SPEND FUNDS - keep as secure as the seed)\n");
+    output.push_str(&format!("Master fingerprint: {}\n", fingerprint));
+    for account in accounts {
+        output.push('\n');
+        output.push_str(&format!("BIP{} {}\n", account.purpose, account.path));
+        output.push_str(&format!("xprv: {}\n", account.xprv));
+        output.push_str(&format!("xpub: {}\n", account.xpub));
+    }
+    output
+}
+
+/// Build BIP380 output descriptors (wpkh, sh(wpkh), pkh, tr) for account 0
+/// receive addresses, each with a `[fingerprint/purpose'/coin'/0']` key origin
+/// and a `#checksum` suffix
+pub fn build_descriptors(master: &Xpriv, network: Network) -> Result<Vec<String>, KeygenError> {
+    build_descriptors_for(master, network, &ALL_PURPOSES)
+}
+
+/// Build the account 0 receive descriptor for each of `purposes`
+pub fn build_descriptors_for(
+    master: &Xpriv,
+    network: Network,
+    purposes: &[u32],
+) -> Result<Vec<String>, KeygenError> {
+    build_chain_descriptors(master, network, purposes, 0)
+}
+
+/// Build the account 0 descriptor for each of `purposes` on one chain: `0`
+/// for receive (`/0/*`) or `1` for change (`/1/*`) addresses
+pub fn build_chain_descriptors(
+    master: &Xpriv,
+    network: Network,
+    purposes: &[u32],
+    chain: u32,
+) -> Result<Vec<String>, KeygenError> {
+    let fingerprint = get_hardware_wallet_fingerprint(master);
+    let mut descriptors = Vec::new();
+
+    for &purpose in purposes {
+        let wrap = match purpose {
+            84 => "wpkh({})",
+            49 => "sh(wpkh({}))",
+            86 => "tr({})",
+            _ => "pkh({})",
+        };
+        let xpub = derive_account_xpub(master, network, purpose, 0)?;
+        let key = format!(
+            "[{}/{}h/{}h/0h]{}/{}/*",
+            fingerprint,
+            purpose,
+            coin_type(network),
+            xpub,
+            chain
+        );
+        let descriptor = wrap.replace("{}", &key);
+        let checksum = descriptor_checksum(&descriptor)?;
+        descriptors.push(format!("{}#{}", descriptor, checksum));
+    }
+
+    Ok(descriptors)
+}
+
+/// Watch-only descriptor for just the first BIP84 receive address
+/// (`.../0/0`, no range), with checksum. Importing it exposes one address for
+/// a small test deposit before the wallet is trusted with real funds
+pub fn build_first_address_descriptor(
+    master: &Xpriv,
+    network: Network,
+) -> Result<String, KeygenError> {
+    let xpub = derive_account_xpub(master, network, 84, 0)?;
+    let descriptor = format!(
+        "wpkh([{}/84h/{}h/0h]{}/0/0)",
+        get_hardware_wallet_fingerprint(master),
+        coin_type(network),
+        xpub
+    );
+    let checksum = descriptor_checksum(&descriptor)?;
+    Ok(format!("{}#{}", descriptor, checksum))
+}
+
+/// Create the first-address watch-only file: the descriptor and the address it
+/// encodes, so the import can be checked before sending the test deposit
+pub fn create_first_address_watchonly_output(descriptor: &str, address: &str) -> String {
+    let mut output = String::new();
+    output.push_str("# Watch-only descriptor for the first receive address only (BIP84 /0/0)\n");
+    output.push_str("# Import it, send a small test amount, and confirm it arrives\n");
+    output.push_str(&format!("# Address: {}\n", address));
+    output.push_str(descriptor);
+    output.push('\n');
+    output
+}
+
+/// Create the descriptors file (watch-only, one descriptor per line)
+pub fn create_descriptors_output(descriptors: &[String]) -> String {
+    let mut output = String::new();
+    output.push_str("# Output descriptors (BIP380) - watch-only, cannot spend funds\n");
+    output.push_str("# Receive addresses (/0/*) for account 0, one script type per line\n");
+    for descriptor in descriptors {
+        output.push_str(descriptor);
+        output.push('\n');
+    }
+    output
+}
+
+/// Create a `--split-descriptors` file holding the descriptors of one chain
+/// (`0` receive, `1` change), for wallets that import the two separately
+pub fn create_chain_descriptors_output(descriptors: &[String], chain: u32) -> String {
+    let kind = if chain == 1 { "Change" } else { "Receive" };
+    let mut output = String::new();
+    output.push_str("# Output descriptors (BIP380) - watch-only, cannot spend funds\n");
+    output.push_str(&format!(
+        "# {} addresses (/{}/*) for account 0, one script type per line\n",
+        kind, chain
+    ));
+    for descriptor in descriptors {
+        output.push_str(descriptor);
+        output.push('\n');
+    }
+    output
+}
+
+/// Character set of the BIP380 descriptor checksum input. Position & 31 is fed
+/// to the checksum directly, position >> 5 in groups of three
+const DESCRIPTOR_INPUT_CHARSET: &str = "0123456789()[],'/*abcdefgh@:$%{}\
+                                        IJKLMNOPQRSTUVWXYZ&+-.;<=>?!^_|~\
+                                        ijklmnopqrstuvwxyzABCDEFGH`#\"\\ ";
+
+/// Bech32 alphabet used for the checksum characters
+const DESCRIPTOR_CHECKSUM_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+fn descriptor_polymod(c: u64, value: u64) -> u64 {
+    const GENERATOR: [u64; 5] = [
+        0xf5dee51989,
+        0xa9fdca3312,
+        0x1bab10e32d,
+        0x3706b1677a,
+        0x644d626ffd,
+    ];
+    let top = c >> 35;
+    let mut c = ((c & 0x7ffffffff) << 5) ^ value;
+    for (i, generator) in GENERATOR.iter().enumerate() {
+        if (top >> i) & 1 == 1 {
+            c ^= generator;
+        }
+    }
+    c
+}
+
+/// BIP380 descriptor checksum (the 8 characters after `#`), required by
+/// Bitcoin Core's `importdescriptors`
+pub fn descriptor_checksum(descriptor: &str) -> Result<String, KeygenError> {
+    let mut c = 1u64;
+    let mut class = 0u64;
+    let mut class_count = 0;
+    for ch in descriptor.chars() {
+        let position = DESCRIPTOR_INPUT_CHARSET.find(ch).ok_or_else(|| {
+            KeygenError::InvalidArgument(format!("Invalid descriptor character: {:?}", ch))
+        })? as u64;
+        c = descriptor_polymod(c, position & 31);
+        class = class * 3 + (position >> 5);
+        class_count += 1;
+        if class_count == 3 {
+            c = descriptor_polymod(c, class);
+            class = 0;
+            class_count = 0;
+        }
+    }
+    if class_count > 0 {
+        c = descriptor_polymod(c, class);
+    }
+    for _ in 0..8 {
+        c = descriptor_polymod(c, 0);
+    }
+    c ^= 1;
+
+    Ok((0..8)
+        .map(|i| DESCRIPTOR_CHECKSUM_CHARSET[((c >> (5 * (7 - i))) & 31) as usize] as char)
+        .collect())
+}
+
+/// Number of addresses Bitcoin Core pre-derives per imported descriptor
+pub const CORE_IMPORT_RANGE: [u32; 2] = [0, 999];
+
+/// One request of a Bitcoin Core `importdescriptors` call
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CoreImportDescriptor {
+    pub desc: String,
+    pub active: bool,
+    pub range: [u32; 2],
+    pub timestamp: String,
+    pub internal: bool,
+}
+
+/// Watch-only BIP84 receive (/0/*) and change (/1/*) descriptors, with
+/// checksums, as a ready-to-paste `importdescriptors` request array.
+/// `"timestamp": "now"` skips the rescan, which is right for a fresh wallet
+pub fn create_core_import_json(master: &Xpriv, network: Network) -> Result<String, KeygenError> {
+    let xpub = derive_account_xpub(master, network, 84, 0)?;
+    let origin = format!(
+        "[{}/84h/{}h/0h]{}",
+        get_hardware_wallet_fingerprint(master),
+        coin_type(network),
+        xpub
+    );
+
+    let mut requests = Vec::new();
+    for (chain, internal) in [(0, false), (1, true)] {
+        let descriptor = format!("wpkh({}/{}/*)", origin, chain);
+        requests.push(CoreImportDescriptor {
+            desc: format!("{}#{}", descriptor, descriptor_checksum(&descriptor)?),
+            active: true,
+            range: CORE_IMPORT_RANGE,
+            timestamp: "now".to_string(),
+            internal,
+        });
+    }
+    Ok(serde_json::to_string_pretty(&requests)?)
+}
+
+/// One account section of a Coldcard generic JSON export
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColdcardAccount {
+    /// Coldcard's script type name (`p2pkh`, `p2wpkh-p2sh`, `p2wpkh`)
+    pub name: String,
+    pub deriv: String,
+    pub xpub: String,
+    /// SLIP-132 ypub/zpub encoding, absent for BIP44
+    #[serde(rename = "_pub", default, skip_serializing_if = "Option::is_none")]
+    pub slip132: Option<String>,
+    /// First receive address
+    pub first: String,
+}
+
+/// Coldcard "generic JSON" wallet export (Advanced > Export Wallet > Generic
+/// JSON), which Sparrow, Specter and others import for watch-only wallets
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ColdcardExport {
+    /// `BTC` on mainnet, `XTN` on test networks, `XRT` on regtest
+    pub chain: String,
+    /// Master fingerprint in uppercase hex
+    pub xfp: String,
+    pub account: u32,
+    pub bip44: ColdcardAccount,
+    pub bip49: ColdcardAccount,
+    pub bip84: ColdcardAccount,
+}
+
+/// Build `coldcard-export.json` with the BIP44/49/84 account 0 xpubs
+pub fn create_coldcard_export(master: &Xpriv, network: Network) -> Result<String, KeygenError> {
+    let account = |purpose: u32| -> Result<ColdcardAccount, KeygenError> {
+        let xpub = derive_account_xpub(master, network, purpose, 0)?;
+        let (_, first) = derive_chain_addresses(master, network, purpose, 0, 1)?.remove(0);
+        Ok(ColdcardAccount {
+            name: match purpose {
+                49 => "p2wpkh-p2sh",
+                _ => script_type_name(purpose),
+            }
+            .to_string(),
+            deriv: format!("m/{}", account_derivation_path(network, purpose, 0)?),
+            xpub: xpub.to_string(),
+            slip132: (purpose != 44).then(|| slip132_encode(&xpub, purpose)),
+            first,
+        })
+    };
+
+    let export = ColdcardExport {
+        chain: match network {
+            Network::Bitcoin => "BTC",
+            Network::Regtest => "XRT",
+            _ => "XTN",
+        }
+        .to_string(),
+        xfp: get_hardware_wallet_fingerprint(master).to_uppercase(),
+        account: 0,
+        bip44: account(44)?,
+        bip49: account(49)?,
+        bip84: account(84)?,
+    };
+    Ok(serde_json::to_string_pretty(&export)?)
+}
+
+/// Sparrow Wallet keystore import: one single-sig account's xpub and key
+/// origin, with the script type Sparrow should create the wallet with
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparrowExport {
+    pub label: String,
+    /// Sparrow's script type name (`P2WPKH` for BIP84)
+    pub script_type: String,
+    /// Master fingerprint in uppercase hex
+    pub master_fingerprint: String,
+    pub derivation_path: String,
+    pub extended_public_key: String,
+}
+
+/// Build `sparrow.json` for the BIP84 account 0 (watch-only)
+pub fn create_sparrow_export(
+    master: &Xpriv,
+    network: Network,
+    label: &str,
+) -> Result<String, KeygenError> {
+    let export = SparrowExport {
+        label: label.to_string(),
+        script_type: "P2WPKH".to_string(),
+        master_fingerprint: get_hardware_wallet_fingerprint(master).to_uppercase(),
+        derivation_path: format!("m/{}", account_derivation_path(network, 84, 0)?),
+        extended_public_key: derive_account_xpub(master, network, 84, 0)?.to_string(),
+    };
+    Ok(serde_json::to_string_pretty(&export)?)
+}
+
+/// CBOR tag of a BCR-2020-007 `crypto-keypath` (key origin)
+pub const CRYPTO_KEYPATH_TAG: u64 = 304;
+/// CBOR tag of a BCR-2020-007 `crypto-coininfo` (coin type and network)
+pub const CRYPTO_COININFO_TAG: u64 = 305;
+
+/// BIP84 account xpub as a single-part `ur:crypto-hdkey` string (BCR-2020-007)
+/// with key origin and parent fingerprint, for Keystone and other UR scanners
+pub fn create_ur_hdkey(master: &Xpriv, network: Network) -> Result<String, KeygenError> {
+    let path = account_derivation_path(network, 84, 0)?;
+    let xpub = derive_account_xpub(master, network, 84, 0)?;
+    let master_fingerprint = u32::from_be_bytes(master.fingerprint(&Secp256k1::new()).to_bytes());
+
+    let mut cbor = minicbor::Encoder::new(Vec::new());
+    // Map keys: 3 key-data, 4 chain-code, 5 use-info, 6 origin, 8 parent-fingerprint
+    let encoded: Result<_, minicbor::encode::Error<_>> = (|| {
+        cbor.map(5)?;
+        cbor.u8(3)?.bytes(&xpub.public_key.serialize())?;
+        cbor.u8(4)?.bytes(&xpub.chain_code[..])?;
+        cbor.u8(5)?
+            .tag(minicbor::data::Tag::new(CRYPTO_COININFO_TAG))?;
+        cbor.map(2)?.u8(1)?.u8(0)?;
+        cbor.u8(2)?.u8(u8::from(network != Network::Bitcoin))?;
+        cbor.u8(6)?
+            .tag(minicbor::data::Tag::new(CRYPTO_KEYPATH_TAG))?;
+        cbor.map(3)?.u8(1)?.array(2 * path.len() as u64)?;
+        for child in path.as_ref() {
+            let (index, hardened) = match *child {
+                ChildNumber::Hardened { index } => (index, true),
+                ChildNumber::Normal { index } => (index, false),
+            };
+            cbor.u32(index)?.bool(hardened)?;
+        }
+        cbor.u8(2)?.u32(master_fingerprint)?;
+        cbor.u8(3)?.u8(path.len() as u8)?;
+        cbor.u8(8)?
+            .u32(u32::from_be_bytes(xpub.parent_fingerprint.to_bytes()))?;
+        Ok(())
+    })();
+    encoded.map_err(|e| KeygenError::Render(format!("CBOR encoding failed: {}", e)))?;
+
+    Ok(ur::encode(
+        &cbor.into_writer(),
+        &ur::Type::Custom("crypto-hdkey"),
+    ))
+}
+
+/// Single-key descriptor parsed by [`parse_descriptor`]:
+/// `<script>([fingerprint/origin path]xpub/<chain>/*)`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParsedDescriptor {
+    /// Script type as its BIP purpose (84 = wpkh, 49 = sh(wpkh), 44 = pkh, 86 = tr)
+    pub purpose: u32,
+    /// Master fingerprint from the key origin
+    pub fingerprint: String,
+    /// Account path from the key origin
+    pub origin: DerivationPath,
+    /// Account-level extended public key
+    pub xpub: Xpub,
+    /// Chain below the account (0 = receive, 1 = change)
+    pub chain: u32,
+}
+
+/// Parse a single-key `wpkh`, `sh(wpkh)`, `pkh` or `tr` descriptor with a key
+/// origin, checking the `#checksum` when present
+pub fn parse_descriptor(descriptor: &str) -> Result<ParsedDescriptor, KeygenError> {
+    let invalid = |reason: &str| {
+        KeygenError::InvalidArgument(format!("Invalid descriptor '{}': {}", descriptor, reason))
+    };
+
+    let descriptor_body = match descriptor.trim().split_once('#') {
+        Some((body, checksum)) => {
+            if descriptor_checksum(body)? != checksum {
+                return Err(invalid("checksum mismatch"));
+            }
+            body
+        }
+        None => descriptor.trim(),
+    };
+
+    let (purpose, key) = [
+        (49, "sh(wpkh(", "))"),
+        (84, "wpkh(", ")"),
+        (44, "pkh(", ")"),
+        (86, "tr(", ")"),
+    ]
+    .iter()
+    .find_map(|(purpose, prefix, suffix)| {
+        descriptor_body
+            .strip_prefix(prefix)
+            .and_then(|rest| rest.strip_suffix(suffix))
+            .map(|key| (*purpose, key))
+    })
+    .ok_or_else(|| invalid("expected wpkh(..), sh(wpkh(..)), pkh(..) or tr(..)"))?;
+
+    let (origin, key) = key
+        .strip_prefix('[')
+        .and_then(|key| key.split_once(']'))
+        .ok_or_else(|| invalid("missing [fingerprint/path] key origin"))?;
+    let (fingerprint, origin_path) = origin
+        .split_once('/')
+        .ok_or_else(|| invalid("key origin has no derivation path"))?;
+    if fingerprint.len() != 8 || !fingerprint.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid("fingerprint must be 8 hex digits"));
+    }
+
+    let (xpub, chain) = key
+        .strip_suffix("/*")
+        .and_then(|key| key.rsplit_once('/'))
+        .ok_or_else(|| invalid("expected xpub/<chain>/* key"))?;
+
+    Ok(ParsedDescriptor {
+        purpose,
+        fingerprint: fingerprint.to_ascii_lowercase(),
+        origin: DerivationPath::from_str(&format!("m/{}", origin_path))
+            .map_err(|e| invalid(&e.to_string()))?,
+        xpub: Xpub::from_str(xpub).map_err(|e| invalid(&e.to_string()))?,
+        chain: chain
+            .parse()
+            .map_err(|_| invalid("chain must be a number"))?,
+    })
+}
+
+/// Outcome of [`match_descriptor`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DescriptorMatch {
+    /// Fingerprint in the descriptor's key origin
+    pub descriptor_fingerprint: String,
+    /// Fingerprint of the seed being checked
+    pub seed_fingerprint: String,
+    /// First address (index 0) of the descriptor
+    pub descriptor_address: String,
+    /// First address the seed derives at the descriptor's path and script type
+    pub seed_address: String,
+}
+
+impl DescriptorMatch {
+    pub fn fingerprint_matches(&self) -> bool {
+        secure_compare(
+            self.descriptor_fingerprint.as_bytes(),
+            self.seed_fingerprint.as_bytes(),
+        )
+    }
+
+    pub fn address_matches(&self) -> bool {
+        secure_compare(
+            self.descriptor_address.as_bytes(),
+            self.seed_address.as_bytes(),
+        )
+    }
+}
+
+/// Check whether `master` produces the keys of a descriptor: derive at its
+/// origin path and script type and compare the fingerprint and first address
+pub fn match_descriptor(
+    master: &Xpriv,
+    network: Network,
+    descriptor: &str,
+) -> Result<DescriptorMatch, KeygenError> {
+    let parsed = parse_descriptor(descriptor)?;
+    let secp = Secp256k1::new();
+    let first = [
+        ChildNumber::from_normal_idx(parsed.chain)?,
+        ChildNumber::from_normal_idx(0)?,
+    ];
+
+    let descriptor_key = parsed.xpub.derive_pub(&secp, &first)?;
+    let seed_key = master.derive_priv(&secp, &parsed.origin.extend(first))?;
+
+    Ok(DescriptorMatch {
+        descriptor_fingerprint: parsed.fingerprint,
+        seed_fingerprint: get_hardware_wallet_fingerprint(master),
+        descriptor_address: address_for_purpose(&descriptor_key, parsed.purpose, network)
+            .to_string(),
+        seed_address: address_for_purpose(
+            &Xpub::from_priv(&secp, &seed_key),
+            parsed.purpose,
+            network,
+        )
+        .to_string(),
+    })
+}
+
+/// Parse an account xpub as shown by a wallet: `xpub`/`tpub`, or the SLIP-132
+/// `ypub`/`zpub`/`upub`/`vpub` forms, which are re-read with standard version bytes
+pub fn parse_account_xpub(xpub: &str) -> Result<Xpub, KeygenError> {
+    let invalid = |reason: &str| {
+        KeygenError::InvalidArgument(format!("Invalid xpub '{}': {}", xpub.trim(), reason))
+    };
+    let mut data =
+        bitcoin::base58::decode_check(xpub.trim()).map_err(|e| invalid(&e.to_string()))?;
+    if data.len() < 4 {
+        return Err(invalid("too short"));
+    }
+    let standard: [u8; 4] = match data[..4] {
+        [0x04, 0x9d, 0x7c, 0xb2] | [0x04, 0xb2, 0x47, 0x46] => [0x04, 0x88, 0xb2, 0x1e],
+        [0x04, 0x4a, 0x52, 0x62] | [0x04, 0x5f, 0x1c, 0xf6] => [0x04, 0x35, 0x87, 0xcf],
+        _ => return Xpub::decode(&data).map_err(|e| invalid(&e.to_string())),
+    };
+    data[..4].copy_from_slice(&standard);
+    Xpub::decode(&data).map_err(|e| invalid(&e.to_string()))
+}
+
+/// The BIP purpose (44, 49, 84 or 86, account 0) at which `master` derives
+/// `xpub`, if any. Confirms a device showing that xpub holds this seed
+pub fn find_xpub_purpose(
+    master: &Xpriv,
+    network: Network,
+    xpub: &Xpub,
+) -> Result<Option<u32>, KeygenError> {
+    for purpose in [44, 49, 84, 86] {
+        if derive_account_xpub(master, network, purpose, 0)? == *xpub {
+            return Ok(Some(purpose));
+        }
+    }
+    Ok(None)
+}
+
+/// This device as a multisig cosigner: its BIP48 native segwit account key
+/// (m/48'/coin'/0'/2') with key origin, and a `wsh(sortedmulti(..))` template
+/// descriptor with placeholders for the other cosigners. Returns (key, descriptor)
+pub fn build_multisig_cosigner(
+    master: &Xpriv,
+    network: Network,
+    threshold: usize,
+    total: usize,
+) -> Result<(String, String), KeygenError> {
+    let secp = Secp256k1::new();
+    let fingerprint = get_hardware_wallet_fingerprint(master);
+    let coin = coin_type(network);
+    let path: DerivationPath = format!("m/48'/{}'/0'/2'", coin).parse()?;
+    let xpub = Xpub::from_priv(&secp, &master.derive_priv(&secp, &path)?);
+
+    let key = format!("[{}/48h/{}h/0h/2h]{}", fingerprint, coin, xpub);
+    let mut keys = vec![format!("{}/0/*", key)];
+    for cosigner in 2..=total {
+        keys.push(format!(
+            "[<cosigner{0}-fingerprint>/48h/{1}h/0h/2h]<cosigner{0}-xpub>/0/*",
+            cosigner, coin
+        ));
+    }
+    let descriptor = format!("wsh(sortedmulti({},{}))", threshold, keys.join(","));
+    Ok((key, descriptor))
+}
+
+/// Create the multisig cosigner file
+pub fn create_multisig_output(
+    key: &str,
+    descriptor: &str,
+    threshold: usize,
+    total: usize,
+) -> String {
+    let mut output = String::new();
+    output.push_str(&format!(
+        "# {}-of-{} multisig cosigner (BIP48 native segwit) - watch-only\n",
+        threshold, total
+    ));
+    output.push_str("# Share this key with the coordinator (Sparrow, Nunchuk, ...):\n");
+    output.push_str(key);
+    output.push_str("\n\n");
+    output.push_str("# Template descriptor - replace the <cosignerN-...> placeholders\n");
+    output.push_str("# with the other cosigners' fingerprints and xpubs:\n");
+    output.push_str(descriptor);
+    output.push('\n');
+    output
+}
+
+/// BIP85 entropy for a derivation path:
+/// HMAC-SHA512(key = "bip-entropy-from-k", msg = derived child private key)
+pub fn bip85_entropy(
+    master: &Xpriv,
+    path: &DerivationPath,
+) -> Result<Zeroizing<[u8; 64]>, KeygenError> {
+    let secp = Secp256k1::new();
+    let mut child = master.derive_priv(&secp, path)?;
+
+    let mut engine = hmac::HmacEngine::<sha512::Hash>::new(b"bip-entropy-from-k");
+    engine.input(&Zeroizing::new(child.private_key.secret_bytes())[..]);
+    child.private_key.non_secure_erase();
+
+    Ok(Zeroizing::new(
+        hmac::Hmac::<sha512::Hash>::from_engine(engine).to_byte_array(),
+    ))
+}
+
+/// Derive a deterministic child BIP39 mnemonic per BIP85 (application 39,
+/// English) at m/83696968'/39'/0'/words'/index'
+pub fn derive_bip85_mnemonic(
+    master: &Xpriv,
+    words: u32,
+    index: u32,
+) -> Result<Mnemonic, KeygenError> {
+    let entropy_len = match words {
+        12 => 16,
+        18 => 24,
+        24 => 32,
+        _ => {
+            return Err(KeygenError::InvalidArgument(format!(
+                "Unsupported BIP85 word count: {} (expected 12, 18 or 24)",
+                words
+            )))
+        }
+    };
+
+    let path: DerivationPath = format!("m/83696968'/39'/0'/{}'/{}'", words, index).parse()?;
+    let entropy = bip85_entropy(master, &path)?;
+    mnemonic_from_entropy(
+        Zeroizing::new(entropy[..entropy_len].to_vec()),
+        Language::English,
+    )
+}
+
+/// Derive a BIP85 WIF private key (application 2) at m/83696968'/2'/index':
+/// the first 32 bytes of the entropy as a compressed-key WIF for `network`
+pub fn derive_bip85_wif(
+    master: &Xpriv,
+    network: Network,
+    index: u32,
+) -> Result<Zeroizing<String>, KeygenError> {
+    let path: DerivationPath = format!("m/83696968'/2'/{}'", index).parse()?;
+    let entropy = bip85_entropy(master, &path)?;
+    let secret = bitcoin::secp256k1::SecretKey::from_slice(&entropy[..32])
+        .map_err(bitcoin::bip32::Error::Secp256k1)?;
+    let mut key = bitcoin::PrivateKey::new(secret, NetworkKind::from(network));
+    let wif = Zeroizing::new(key.to_wif());
+    key.inner.non_secure_erase();
+    Ok(wif)
+}
+
+/// Derive BIP85 hex entropy (application 128169) of `bytes` bytes (16-64) at
+/// m/83696968'/128169'/bytes'/index'
+pub fn derive_bip85_hex(
+    master: &Xpriv,
+    bytes: usize,
+    index: u32,
+) -> Result<Zeroizing<String>, KeygenError> {
+    if !(16..=64).contains(&bytes) {
+        return Err(KeygenError::InvalidArgument(format!(
+            "Unsupported BIP85 hex length: {} bytes (expected 16-64)",
+            bytes
+        )));
+    }
+    let path: DerivationPath = format!("m/83696968'/128169'/{}'/{}'", bytes, index).parse()?;
+    let entropy = bip85_entropy(master, &path)?;
+    Ok(secret_hex(&entropy[..bytes]))
+}
+
+/// Parse a `--network` value into the matching `bitcoin::Network`
+pub fn parse_network(name: &str) -> Result<Network, KeygenError> {
+    match name {
+        "mainnet" | "bitcoin" => Ok(Network::Bitcoin),
+        "testnet" => Ok(Network::Testnet),
+        "testnet4" => Ok(Network::Testnet4),
+        "signet" => Ok(Network::Signet),
+        "regtest" => Ok(Network::Regtest),
+        _ => Err(KeygenError::InvalidArgument(format!(
+            "Unknown network: {} (expected mainnet, testnet, testnet4, signet or regtest)",
+            name
+        ))),
+    }
+}
+
+/// Parse a `--language` value into the matching BIP39 wordlist
+pub fn parse_language(name: &str) -> Result<Language, KeygenError> {
+    match name {
+        "english" => Ok(Language::English),
+        "japanese" => Ok(Language::Japanese),
+        "korean" => Ok(Language::Korean),
+        "spanish" => Ok(Language::Spanish),
+        "french" => Ok(Language::French),
+        "italian" => Ok(Language::Italian),
+        "czech" => Ok(Language::Czech),
+        "portuguese" => Ok(Language::Portuguese),
+        "chinese-simplified" => Ok(Language::SimplifiedChinese),
+        "chinese-traditional" => Ok(Language::TraditionalChinese),
+        _ => Err(KeygenError::InvalidArgument(format!(
+            "Unknown language: {}",
+            name
+        ))),
+    }
+}
+
+/// Human-readable wordlist name for printed output
+pub fn language_display_name(language: Language) -> &'static str {
+    match language {
+        Language::English => "English",
+        Language::Japanese => "Japanese",
+        Language::Korean => "Korean",
+        Language::Spanish => "Spanish",
+        Language::French => "French",
+        Language::Italian => "Italian",
+        Language::Czech => "Czech",
+        Language::Portuguese => "Portuguese",
+        Language::SimplifiedChinese => "Chinese (Simplified)",
+        Language::TraditionalChinese => "Chinese (Traditional)",
+    }
+}
+
+/// Short network name as accepted by `--network`
+pub fn network_name(network: Network) -> &'static str {
+    match network {
+        Network::Bitcoin => "mainnet",
+        Network::Testnet => "testnet",
+        Network::Testnet4 => "testnet4",
+        Network::Signet => "signet",
+        Network::Regtest => "regtest",
+    }
+}
+
+/// Human-readable network name for printed output
+pub fn network_display_name(network: Network) -> &'static str {
+    match network {
+        Network::Bitcoin => "Bitcoin Mainnet",
+        Network::Testnet => "Bitcoin Testnet",
+        Network::Testnet4 => "Bitcoin Testnet4",
+        Network::Signet => "Bitcoin Signet",
+        Network::Regtest => "Bitcoin Regtest",
+    }
+}
+
+/// Format words in numbered rows of 4 for easy reading and punching
+pub fn format_word_grid(words: &[&str]) -> String {
+    let mut output = String::new();
+
+    for (i, word) in words.iter().enumerate() {
+        let word_num = i + 1;
+        output.push_str(&format!("{:2}. {:12}", word_num, word));
+
+        // New line every 4 words
+        if word_num % 4 == 0 {
+            output.push('\n');
+        } else {
+            output.push_str("  ");
+        }
+    }
+
+    // Ensure last line ends properly
+    if !words.len().is_multiple_of(4) {
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Mask all but the first and last letter of a word (`abandon` -> `a*****n`)
+pub fn redact_word(word: &str) -> String {
+    let chars: Vec<char> = word.chars().collect();
+    match chars.as_slice() {
+        [] => String::new(),
+        [only] => format!("{}*", only),
+        [first, middle @ .., last] => {
+            format!("{}{}{}", first, "*".repeat(middle.len().max(1)), last)
+        }
+    }
+}
+
+/// Word grid with every word passed through [`redact_word`] plus the full
+/// fingerprint, to confirm the right seed without exposing it on screen
+pub fn create_redacted_preview(mnemonic: &Mnemonic, fingerprint: &str) -> String {
+    let redacted: Vec<String> = mnemonic.words().map(redact_word).collect();
+    let redacted: Vec<&str> = redacted.iter().map(String::as_str).collect();
+    let mut output = String::from("Redacted seed words (first and last letter only):\n");
+    output.push_str(&format_word_grid(&redacted));
+    output.push_str(&format!("Fingerprint: {}\n", fingerprint));
+    output
+}
+
+/// Format words as a numbered single column
+pub fn format_word_column(words: &[&str]) -> String {
+    let mut output = String::new();
+    for (i, word) in words.iter().enumerate() {
+        output.push_str(&format!("{:2}. {}\n", i + 1, word));
+    }
+    output
+}
+
+/// Format words as a numbered single column with each word's 4-digit BIP39
+/// wordlist index (0000-2047, as in SeedQR) for numeric cross-checking
+pub fn format_indexed_word_column(mnemonic: &Mnemonic) -> String {
+    mnemonic
+        .words()
+        .zip(mnemonic.word_indices())
+        .enumerate()
+        .map(|(i, (word, index))| format!("{:2}. {:8} [{:04}]\n", i + 1, word, index))
+        .collect()
+}
+
+/// NATO phonetic alphabet, `a` to `z`
+pub const NATO_ALPHABET: [&str; 26] = [
+    "Alpha", "Bravo", "Charlie", "Delta", "Echo", "Foxtrot", "Golf", "Hotel", "India", "Juliett",
+    "Kilo", "Lima", "Mike", "November", "Oscar", "Papa", "Quebec", "Romeo", "Sierra", "Tango",
+    "Uniform", "Victor", "Whiskey", "X-ray", "Yankee", "Zulu",
+];
+
+/// Spell a word letter by letter in the NATO alphabet ("abandon" = "Alpha Bravo
+/// Alpha ..."). Letters outside `a`-`z` (accents, kana) are kept as they are
+pub fn phonetic_spelling(word: &str) -> String {
+    word.chars()
+        .map(|c| match c.to_ascii_lowercase() {
+            letter @ 'a'..='z' => NATO_ALPHABET[(letter as u8 - b'a') as usize].to_string(),
+            other => other.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Format words as a numbered single column with their [`phonetic_spelling`]
+pub fn format_phonetic_column(mnemonic: &Mnemonic) -> String {
+    phonetic_column(&mnemonic.words().collect::<Vec<_>>())
+}
+
+fn phonetic_column(words: &[&str]) -> String {
+    words
+        .iter()
+        .enumerate()
+        .map(|(i, word)| format!("{:2}. {:8} = {}\n", i + 1, word, phonetic_spelling(word)))
+        .collect()
+}
+
+/// Language of the static instructions in the printable output
+/// (`--instructions-lang`); the seed words themselves are never translated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InstructionLanguage {
+    #[default]
+    English,
+    Spanish,
+    French,
+    German,
+    Japanese,
+}
+
+/// Static text of the printable output in one language. `{n}` is replaced
+/// by the word count and `{fp}` by the fingerprint
+struct PrintableText {
+    security_warning: &'static str,
+    warning: [&'static str; 3],
+    share_warning: [&'static str; 4],
+    seed_words: &'static str,
+    share_words: &'static str,
+    checklist: &'static str,
+    checklist_items: [&'static str; 5],
+    single_column: &'static str,
+    phonetic: &'static str,
+    addresses: &'static str,
+    import: &'static str,
+    compatible: [&'static str; 2],
+    example: &'static str,
+    coldcard_steps: [&'static str; 7],
+    other_wallets: &'static str,
+    passphrase_notice: [&'static str; 7],
+}
+
+const ENGLISH_TEXT: PrintableText = PrintableText {
+    security_warning: "⚠️  SECURITY WARNING ⚠️",
+    warning: [
+        "This seed phrase provides full access to your Bitcoin wallet.",
+        "Store this metal plate in a secure, fireproof location.",
+        "Never share this seed phrase with anyone.",
+    ],
+    share_warning: [
+        "This share alone cannot recover the wallet, but enough shares can.",
+        "Store each share in a separate, secure location.",
+        "The shares encode the BIP39 entropy: combine them with a SLIP39",
+        "tool to recover the original BIP39 words before importing.",
+    ],
+    seed_words: "SEED WORDS (Punch these in order):",
+    share_words: "SHARE WORDS (Punch these in order):",
+    checklist: "VERIFICATION CHECKLIST:",
+    checklist_items: [
+        "All {n} words are clearly readable",
+        "Words are in correct numerical order (1-{n})",
+        "Fingerprint matches hardware wallet device",
+        "Metal plate is stored in secure location",
+        "Backup copy exists in separate location",
+    ],
+    single_column: "SINGLE COLUMN FORMAT (Alternative punching reference):",
+    phonetic: "PHONETIC SPELLING (for reading aloud)",
+    addresses: "RECEIVE ADDRESSES (Verify against your hardware wallet):",
+    import: "HARDWARE WALLET IMPORT INSTRUCTIONS:",
+    compatible: [
+        "This seed phrase is compatible with all BIP39 hardware wallets",
+        "(Coldcard, Trezor, Ledger, BitBox, etc.).",
+    ],
+    example: "Example - Coldcard:",
+    coldcard_steps: [
+        "Power on your Coldcard device",
+        "Navigate to: Advanced > Danger Zone > Seed Functions > Import Existing",
+        "Select '{n} words' when prompted",
+        "Enter the {n} words in order (1-{n})",
+        "Verify the fingerprint matches: {fp}",
+        "Set a secure PIN code",
+        "Test with a small transaction before storing large amounts",
+    ],
+    other_wallets: "For other hardware wallets, follow their specific recovery/import process.",
+    passphrase_notice: [
+        "*** PASSPHRASE REQUIRED ***",
+        "These words alone do NOT restore this wallet. A BIP39 passphrase",
+        "(the \"25th word\") is also required and is deliberately NOT",
+        "written on this backup. Keep it memorized or stored separately.",
+        "Fingerprint WITH passphrase: {fp}",
+        "Words without the passphrase (or with a typo in it) open a",
+        "different, empty wallet with a different fingerprint.",
+    ],
+};
+
+const SPANISH_TEXT: PrintableText = PrintableText {
+    security_warning: "⚠️  ADVERTENCIA DE SEGURIDAD ⚠️",
+    warning: [
+        "Esta frase semilla da acceso total a su billetera Bitcoin.",
+        "Guarde esta placa metálica en un lugar seguro e ignífugo.",
+        "Nunca comparta esta frase semilla con nadie.",
+    ],
+    share_warning: [
+        "Esta parte sola no recupera la billetera, pero suficientes partes sí.",
+        "Guarde cada parte en un lugar distinto y seguro.",
+        "Las partes codifican la entropía BIP39: combínelas con una herramienta",
+        "SLIP39 para recuperar las palabras BIP39 originales antes de importar.",
+    ],
+    seed_words: "PALABRAS SEMILLA (Perfórelas en orden):",
+    share_words: "PALABRAS DE LA PARTE (Perfórelas en orden):",
+    checklist: "LISTA DE VERIFICACIÓN:",
+    checklist_items: [
+        "Las {n} palabras son claramente legibles",
+        "Las palabras están en el orden numérico correcto (1-{n})",
+        "La huella coincide con la billetera de hardware",
+        "La placa metálica está guardada en un lugar seguro",
+        "Existe una copia de respaldo en otro lugar",
+    ],
+    single_column: "FORMATO DE UNA COLUMNA (Referencia alternativa para perforar):",
+    phonetic: "DELETREO FONÉTICO (para leer en voz alta)",
+    addresses: "DIRECCIONES DE RECEPCIÓN (Compárelas con su billetera de hardware):",
+    import: "INSTRUCCIONES DE IMPORTACIÓN EN BILLETERA DE HARDWARE:",
+    compatible: [
+        "Esta frase semilla es compatible con todas las billeteras de hardware BIP39",
+        "(Coldcard, Trezor, Ledger, BitBox, etc.).",
+    ],
+    example: "Ejemplo - Coldcard:",
+    coldcard_steps: [
+        "Encienda su dispositivo Coldcard",
+        "Vaya a: Advanced > Danger Zone > Seed Functions > Import Existing",
+        "Seleccione '{n} words' cuando se le solicite",
+        "Introduzca las {n} palabras en orden (1-{n})",
+        "Compruebe que la huella coincide: {fp}",
+        "Configure un código PIN seguro",
+        "Pruebe con una transacción pequeña antes de guardar grandes cantidades",
+    ],
+    other_wallets:
+        "Para otras billeteras de hardware, siga su proceso específico de recuperación/importación.",
+    passphrase_notice: [
+        "*** SE REQUIERE FRASE DE CONTRASEÑA ***",
+        "Estas palabras solas NO restauran esta billetera. También se requiere",
+        "una frase de contraseña BIP39 (la \"palabra 25\"), que deliberadamente",
+        "NO está escrita en esta copia. Memorícela o guárdela por separado.",
+        "Huella CON frase de contraseña: {fp}",
+        "Las palabras sin la frase de contraseña (o con un error en ella) abren",
+        "otra billetera vacía con una huella distinta.",
+    ],
+};
+
+const FRENCH_TEXT: PrintableText = PrintableText {
+    security_warning: "⚠️  AVERTISSEMENT DE SÉCURITÉ ⚠️",
+    warning: [
+        "Cette phrase de récupération donne un accès total à votre portefeuille Bitcoin.",
+        "Conservez cette plaque métallique dans un endroit sûr et ignifugé.",
+        "Ne partagez jamais cette phrase de récupération avec qui que ce soit.",
+    ],
+    share_warning: [
+        "Cette part seule ne restaure pas le portefeuille, mais assez de parts le peuvent.",
+        "Conservez chaque part dans un lieu sûr et distinct.",
+        "Les parts encodent l'entropie BIP39 : combinez-les avec un outil SLIP39",
+        "pour retrouver les mots BIP39 d'origine avant l'importation.",
+    ],
+    seed_words: "MOTS DE RÉCUPÉRATION (À poinçonner dans l'ordre) :",
+    share_words: "MOTS DE LA PART (À poinçonner dans l'ordre) :",
+    checklist: "LISTE DE VÉRIFICATION :",
+    checklist_items: [
+        "Les {n} mots sont clairement lisibles",
+        "Les mots sont dans le bon ordre numérique (1-{n})",
+        "L'empreinte correspond au portefeuille matériel",
+        "La plaque métallique est conservée en lieu sûr",
+        "Une copie de sauvegarde existe dans un autre lieu",
+    ],
+    single_column: "FORMAT EN UNE COLONNE (Référence alternative pour le poinçonnage) :",
+    phonetic: "ÉPELLATION PHONÉTIQUE (pour la lecture à voix haute)",
+    addresses: "ADRESSES DE RÉCEPTION (À comparer avec votre portefeuille matériel) :",
+    import: "INSTRUCTIONS D'IMPORTATION DANS UN PORTEFEUILLE MATÉRIEL :",
+    compatible: [
+        "Cette phrase est compatible avec tous les portefeuilles matériels BIP39",
+        "(Coldcard, Trezor, Ledger, BitBox, etc.).",
+    ],
+    example: "Exemple - Coldcard :",
+    coldcard_steps: [
+        "Allumez votre Coldcard",
+        "Allez dans : Advanced > Danger Zone > Seed Functions > Import Existing",
+        "Sélectionnez '{n} words' lorsque demandé",
+        "Saisissez les {n} mots dans l'ordre (1-{n})",
+        "Vérifiez que l'empreinte correspond : {fp}",
+        "Définissez un code PIN sûr",
+        "Faites un test avec une petite transaction avant de stocker des montants importants",
+    ],
+    other_wallets: "Pour les autres portefeuilles matériels, suivez leur procédure de restauration/importation.",
+    passphrase_notice: [
+        "*** PHRASE SECRÈTE REQUISE ***",
+        "Ces mots seuls NE restaurent PAS ce portefeuille. Une phrase secrète",
+        "BIP39 (le « 25e mot ») est aussi requise et n'est volontairement PAS",
+        "écrite sur cette sauvegarde. Mémorisez-la ou conservez-la à part.",
+        "Empreinte AVEC la phrase secrète : {fp}",
+        "Les mots sans la phrase secrète (ou avec une faute de frappe) ouvrent",
+        "un autre portefeuille, vide, avec une autre empreinte.",
+    ],
+};
+
+const GERMAN_TEXT: PrintableText = PrintableText {
+    security_warning: "⚠️  SICHERHEITSWARNUNG ⚠️",
+    warning: [
+        "Diese Seed-Phrase gewährt vollen Zugriff auf Ihre Bitcoin-Wallet.",
+        "Bewahren Sie diese Metallplatte an einem sicheren, feuerfesten Ort auf.",
+        "Geben Sie diese Seed-Phrase niemals an Dritte weiter.",
+    ],
+    share_warning: [
+        "Dieser Anteil allein stellt die Wallet nicht wieder her, genügend Anteile schon.",
+        "Bewahren Sie jeden Anteil an einem eigenen, sicheren Ort auf.",
+        "Die Anteile kodieren die BIP39-Entropie: Kombinieren Sie sie vor dem Import",
+        "mit einem SLIP39-Werkzeug, um die ursprünglichen BIP39-Wörter zu erhalten.",
+    ],
+    seed_words: "SEED-WÖRTER (In dieser Reihenfolge einstanzen):",
+    share_words: "ANTEILSWÖRTER (In dieser Reihenfolge einstanzen):",
+    checklist: "PRÜFLISTE:",
+    checklist_items: [
+        "Alle {n} Wörter sind gut lesbar",
+        "Die Wörter stehen in der richtigen Reihenfolge (1-{n})",
+        "Der Fingerabdruck stimmt mit der Hardware-Wallet überein",
+        "Die Metallplatte ist an einem sicheren Ort verwahrt",
+        "Eine Sicherungskopie existiert an einem anderen Ort",
+    ],
+    single_column: "EINSPALTIGES FORMAT (Alternative Vorlage zum Einstanzen):",
+    phonetic: "PHONETISCHE BUCHSTABIERUNG (zum Vorlesen)",
+    addresses: "EMPFANGSADRESSEN (Mit Ihrer Hardware-Wallet abgleichen):",
+    import: "ANLEITUNG ZUM IMPORT IN EINE HARDWARE-WALLET:",
+    compatible: [
+        "Diese Seed-Phrase ist mit allen BIP39-Hardware-Wallets kompatibel",
+        "(Coldcard, Trezor, Ledger, BitBox usw.).",
+    ],
+    example: "Beispiel - Coldcard:",
+    coldcard_steps: [
+        "Schalten Sie Ihre Coldcard ein",
+        "Navigieren Sie zu: Advanced > Danger Zone > Seed Functions > Import Existing",
+        "Wählen Sie '{n} words', wenn Sie dazu aufgefordert werden",
+        "Geben Sie die {n} Wörter der Reihe nach ein (1-{n})",
+        "Prüfen Sie, ob der Fingerabdruck übereinstimmt: {fp}",
+        "Legen Sie einen sicheren PIN-Code fest",
+        "Testen Sie mit einer kleinen Transaktion, bevor Sie größere Beträge speichern",
+    ],
+    other_wallets:
+        "Bei anderen Hardware-Wallets folgen Sie deren Wiederherstellungs-/Importanleitung.",
+    passphrase_notice: [
+        "*** PASSPHRASE ERFORDERLICH ***",
+        "Diese Wörter allein stellen die Wallet NICHT wieder her. Zusätzlich ist",
+        "eine BIP39-Passphrase (das \"25. Wort\") nötig, die absichtlich NICHT",
+        "auf dieser Sicherung steht. Merken Sie sie sich oder bewahren Sie sie getrennt auf.",
+        "Fingerabdruck MIT Passphrase: {fp}",
+        "Die Wörter ohne Passphrase (oder mit einem Tippfehler darin) öffnen eine",
+        "andere, leere Wallet mit einem anderen Fingerabdruck.",
+    ],
+};
+
+const JAPANESE_TEXT: PrintableText = PrintableText {
+    security_warning: "⚠️  セキュリティ警告 ⚠️",
+    warning: [
+        "このシードフレーズはビットコインウォレットへの完全なアクセスを提供します。",
+        "この金属プレートは安全な耐火性の場所に保管してください。",
+        "このシードフレーズを決して他人と共有しないでください。",
+    ],
+    share_warning: [
+        "このシェア単体ではウォレットを復元できませんが、必要数のシェアが揃うと復元できます。",
+        "各シェアはそれぞれ別の安全な場所に保管してください。",
+        "シェアはBIP39エントロピーを符号化しています。インポート前にSLIP39ツールで",
+        "組み合わせ、元のBIP39単語を復元してください。",
+    ],
+    seed_words: "シードワード（順番通りに刻印してください）:",
+    share_words: "シェアワード（順番通りに刻印してください）:",
+    checklist: "確認チェックリスト:",
+    checklist_items: [
+        "{n}個の単語すべてがはっきり読める",
+        "単語が正しい番号順（1-{n}）に並んでいる",
+        "フィンガープリントがハードウェアウォレットと一致する",
+        "金属プレートが安全な場所に保管されている",
+        "別の場所にバックアップがある",
+    ],
+    single_column: "1列形式（刻印用の別レイアウト）:",
+    phonetic: "フォネティックコード（読み上げ用）",
+    addresses: "受取アドレス（ハードウェアウォレットの表示と照合してください）:",
+    import: "ハードウェアウォレットへのインポート手順:",
+    compatible: [
+        "このシードフレーズはすべてのBIP39対応ハードウェアウォレットで使用できます",
+        "（Coldcard、Trezor、Ledger、BitBox など）。",
+    ],
+    example: "例 - Coldcard:",
+    coldcard_steps: [
+        "Coldcardの電源を入れます",
+        "次の順に進みます: Advanced > Danger Zone > Seed Functions > Import Existing",
+        "表示されたら「{n} words」を選択します",
+        "{n}個の単語を順番通り（1-{n}）に入力します",
+        "フィンガープリントが一致することを確認します: {fp}",
+        "安全なPINコードを設定します",
+        "多額を保管する前に少額の取引でテストします",
+    ],
+    other_wallets:
+        "他のハードウェアウォレットでは、それぞれの復元/インポート手順に従ってください。",
+    passphrase_notice: [
+        "*** パスフレーズが必要です ***",
+        "これらの単語だけではこのウォレットを復元できません。BIP39パスフレーズ",
+        "（「25番目の単語」）も必要で、このバックアップには意図的に記載していません。",
+        "暗記するか、別の場所に保管してください。",
+        "パスフレーズ込みのフィンガープリント: {fp}",
+        "パスフレーズなし（または入力ミスあり）で復元すると、別の空のウォレット",
+        "（別のフィンガープリント）が開きます。",
+    ],
+};
+
+impl InstructionLanguage {
+    fn text(self) -> &'static PrintableText {
+        match self {
+            InstructionLanguage::English => &ENGLISH_TEXT,
+            InstructionLanguage::Spanish => &SPANISH_TEXT,
+            InstructionLanguage::French => &FRENCH_TEXT,
+            InstructionLanguage::German => &GERMAN_TEXT,
+            InstructionLanguage::Japanese => &JAPANESE_TEXT,
+        }
+    }
+}
+
+/// Optional content of [`create_localized_printable_output`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrintableOptions {
+    /// Language of the warnings, checklist and import instructions
+    pub language: InstructionLanguage,
+    /// State that a BIP39 passphrase is also needed to restore (`--passphrase-required`)
+    pub passphrase_required: bool,
+    /// Add each word's wordlist index to the single-column list (`--with-word-indices`)
+    pub word_indices: bool,
+    /// Spell each word in the NATO alphabet after the single-column list (`--phonetic`)
+    pub phonetic: bool,
+}
+
+/// Create printable output optimized for metal plate punching
+pub fn create_printable_output(
+    mnemonic: &Mnemonic,
+    fingerprint: &str,
+    label: &str,
+    network: Network,
+    addresses: &[(String, String)],
+) -> String {
+    create_localized_printable_output(
+        mnemonic,
+        fingerprint,
+        label,
+        network,
+        addresses,
+        &PrintableOptions::default(),
+        &now_timestamp(),
+    )
+}
+
+/// Parts of a printable that differ between a wallet and one of its SLIP39
+/// shares; everything else comes from [`PrintableText`] and [`PrintableOptions`]
+struct PrintableSheet<'a> {
+    title: String,
+    /// Metadata lines between `Generated:` and `Network:`
+    details: Vec<String>,
+    warning: &'a [&'a str],
+    words_heading: &'a str,
+    words: &'a [&'a str],
+    /// Single column with BIP39 wordlist indices, used with `word_indices`
+    indexed_column: Option<String>,
+    import_instructions: bool,
+}
+
+/// [`create_printable_output`] with the warnings, checklist and import
+/// instructions in `options.language`, the optional sections in `options`
+/// and a fixed `generated` time, so the output is byte-for-byte reproducible
+pub fn create_localized_printable_output(
+    mnemonic: &Mnemonic,
+    fingerprint: &str,
+    label: &str,
+    network: Network,
+    addresses: &[(String, String)],
+    options: &PrintableOptions,
+    generated: &Timestamp,
+) -> String {
+    let text = options.language.text();
+    let words: Vec<&str> = mnemonic.words().collect();
+    let sheet = PrintableSheet {
+        title: "           BITCOIN SEED PHRASE - METAL PLATE BACKUP".to_string(),
+        details: vec![
+            format!("Fingerprint: {}", fingerprint),
+            format!(
+                "Word Count: {} words ({} bits entropy)",
+                words.len(),
+                entropy_bits_for_word_count(words.len())
+            ),
+        ],
+        warning: &text.warning,
+        words_heading: text.seed_words,
+        words: &words,
+        indexed_column: Some(format_indexed_word_column(mnemonic)),
+        import_instructions: true,
+    };
+    render_printable(
+        &sheet,
+        fingerprint,
+        label,
+        network,
+        addresses,
+        options,
+        generated,
+    )
+}
+
+/// Lay out a [`PrintableSheet`] as a metal-plate printable
+fn render_printable(
+    sheet: &PrintableSheet,
+    fingerprint: &str,
+    label: &str,
+    network: Network,
+    addresses: &[(String, String)],
+    options: &PrintableOptions,
+    generated: &Timestamp,
+) -> String {
+    let text = options.language.text();
+    let words = sheet.words;
+    let word_count = words.len();
+    let timestamp = format_timestamp(generated);
+    let fill = |template: &str| {
+        template
+            .replace("{n}", &word_count.to_string())
+            .replace("{fp}", fingerprint)
+    };
+
+    let mut output = String::new();
+
+    // Header
+    output.push_str("═══════════════════════════════════════════════════════════════\n");
+    output.push_str(&format!("{}\n", sheet.title));
+    output.push_str("═══════════════════════════════════════════════════════════════\n\n");
+
+    // Label and metadata
+    output.push_str(&format!("Label: {}\n", label));
+    output.push_str(&format!("Generated: {}\n", timestamp));
+    for line in &sheet.details {
+        output.push_str(&format!("{}\n", line));
+    }
+    output.push_str(&format!("Network: {}\n\n", network_display_name(network)));
+
+    if options.passphrase_required {
+        output.push_str(&passphrase_required_notice(text, fingerprint));
+    }
+
+    // Warning
+    output.push_str(&format!("{}\n", text.security_warning));
+    output.push_str("─────────────────────────────────────────────────────────────\n");
+    for line in sheet.warning {
+        output.push_str(&format!("{}\n", line));
+    }
+    output.push_str("─────────────────────────────────────────────────────────────\n\n");
+
+    // Seed words in large, clear format for punching
+    output.push_str(&format!("{}\n", sheet.words_heading));
+    output.push_str("═══════════════════════════════════════════════════════════════\n\n");
+
+    output.push_str(&format_word_grid(words));
+    output.push('\n');
+    output.push_str("═══════════════════════════════════════════════════════════════\n");
+    output.push_str(&format!("{}\n", text.checklist));
+    output.push_str("─────────────────────────────────────────────────────────────\n");
+    for item in text.checklist_items {
+        output.push_str(&format!("□ {}\n", fill(item)));
+    }
+    output.push_str("═══════════════════════════════════════════════════════════════\n\n");
+
+    // Additional format: Single column for easier punching reference
+    // (with `word_indices` the 4-across grid stays plain so it still fits the page)
+    output.push_str(&format!("\n\n{}\n", text.single_column));
+    output.push_str("═══════════════════════════════════════════════════════════════\n");
+    match &sheet.indexed_column {
+        Some(column) if options.word_indices => output.push_str(column),
+        _ => output.push_str(&format_word_column(words)),
+    }
+    output.push_str("═══════════════════════════════════════════════════════════════\n\n");
+
+    // Phonetic spelling for reading the words aloud during verification
+    if options.phonetic {
+        output.push_str(text.phonetic);
+        output.push('\n');
+        output.push_str("═══════════════════════════════════════════════════════════════\n");
+        output.push_str(&phonetic_column(words));
+        output.push_str("═══════════════════════════════════════════════════════════════\n\n");
+    }
+
+    // Receive addresses for checking against the hardware wallet display
+    if !addresses.is_empty() {
+        output.push_str(&format!("{}\n", text.addresses));
+        output.push_str("─────────────────────────────────────────────────────────────\n");
+        output
+            .push_str("84' = Native SegWit, 49' = Wrapped SegWit, 44' = Legacy, 86' = Taproot\n\n");
+        for (path, address) in addresses {
+            output.push_str(&format!("{:20} {}\n", path, address));
+        }
+        output.push_str("─────────────────────────────────────────────────────────────\n\n");
+    }
+
+    // Hardware wallet import instructions
+    if sheet.import_instructions {
+        output.push_str(&format!("{}\n", text.import));
+        output.push_str("─────────────────────────────────────────────────────────────\n");
+        output.push_str(&format!(
+            "{}\n{}\n\n",
+            text.compatible[0], text.compatible[1]
+        ));
+        output.push_str(&format!("{}\n", text.example));
+        for (i, step) in text.coldcard_steps.iter().enumerate() {
+            output.push_str(&format!("{}. {}\n", i + 1, fill(step)));
+        }
+        output.push('\n');
+        output.push_str(&format!("{}\n", text.other_wallets));
+        output.push_str("─────────────────────────────────────────────────────────────\n\n");
+    }
+
+    // Footer
+    output.push_str("Generated by bitcoin-keygen (air-gapped system)\n");
+    output.push_str("═══════════════════════════════════════════════════════════════\n");
+
+    output
+}
+
+/// "Passphrase required" notice for backups of a "2FA seed" (words on metal,
+/// passphrase memorized), in the language of `text`. The passphrase itself is
+/// never written; `fingerprint` must be the one derived WITH the passphrase
+/// so the restore can be verified
+fn passphrase_required_notice(text: &PrintableText, fingerprint: &str) -> String {
+    let mut notice = String::new();
+    for (i, line) in text.passphrase_notice.iter().enumerate() {
+        notice.push_str(&format!("{}\n", line.replace("{fp}", fingerprint)));
+        if i == 0 {
+            notice.push_str("─────────────────────────────────────────────────────────────\n");
+        }
+    }
+    notice.push_str("─────────────────────────────────────────────────────────────\n\n");
+    notice
+}
+
+/// Split BIP39 entropy into `total` SLIP39 shares, any `threshold` of which
+/// reconstruct it. Returns the words of each share
+pub fn shamir_split(
+    entropy: &[u8],
+    threshold: u8,
+    total: u8,
+) -> Result<Vec<Vec<String>>, KeygenError> {
+    // Single group, no SLIP39 passphrase. Iteration exponent 0: sssmc39 does not
+    // round-trip shares generated with higher exponents
+    let groups = sssmc39::generate_mnemonics(1, &[(threshold, total)], entropy, "", 0)
+        .map_err(|e| KeygenError::SecretSharing(format!("SLIP39 split failed: {}", e)))?;
+
+    let mut shares = Vec::new();
+    for group in &groups {
+        shares.extend(
+            group.mnemonic_list().map_err(|e| {
+                KeygenError::SecretSharing(format!("SLIP39 encoding failed: {}", e))
+            })?,
+        );
+    }
+    Ok(shares)
+}
+
+/// Position of a SLIP39 share within its `threshold`-of-`total` split
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShamirShareNumber {
+    pub number: usize,
+    pub threshold: u8,
+    pub total: u8,
+}
+
+/// Create the metal-plate printable for one SLIP39 share: the
+/// [`create_localized_printable_output`] layout with a share header and
+/// warning, and no import instructions (the shares must be combined first).
+/// `fingerprint` is the wallet's. SLIP39 words have no BIP39 wordlist index,
+/// so `options.word_indices` leaves the single column plain
+pub fn create_shamir_share_output(
+    share_words: &[String],
+    share: ShamirShareNumber,
+    fingerprint: &str,
+    label: &str,
+    network: Network,
+    options: &PrintableOptions,
+    generated: &Timestamp,
+) -> String {
+    let text = options.language.text();
+    let words: Vec<&str> = share_words.iter().map(String::as_str).collect();
+    let sheet = PrintableSheet {
+        title: format!(
+            "        SLIP39 SHARE {} OF {} - METAL PLATE BACKUP",
+            share.number, share.total
+        ),
+        details: vec![
+            format!("Wallet Fingerprint: {}", fingerprint),
+            format!(
+                "Threshold: any {} of {} shares recover the seed",
+                share.threshold, share.total
+            ),
+            format!("Word Count: {} words", words.len()),
+        ],
+        warning: &text.share_warning,
+        words_heading: text.share_words,
+        words: &words,
+        indexed_column: None,
+        import_instructions: false,
+    };
+    render_printable(&sheet, fingerprint, label, network, &[], options, generated)
+}
+
+/// Split entropy into `parts` BIP39 mnemonics whose entropies XOR back to the
+/// original (Coldcard Seed XOR). The first `parts - 1` are random; the last is
+/// the XOR of the input with all of them. Every part is itself a valid wallet
+pub fn seed_xor_split(entropy: &[u8], parts: usize) -> Result<Vec<Mnemonic>, KeygenError> {
+    if parts < 2 {
+        return Err(KeygenError::SecretSharing(format!(
+            "Seed XOR needs at least 2 parts, got {}",
+            parts
+        )));
+    }
+
+    let mut last = Zeroizing::new(entropy.to_vec());
+    let mut mnemonics = Vec::with_capacity(parts);
+    for _ in 0..parts - 1 {
+        let mut random = Zeroizing::new(vec![0u8; entropy.len()]);
+        getrandom::fill(&mut random)?;
+        for (acc, byte) in last.iter_mut().zip(random.iter()) {
+            *acc ^= byte;
+        }
+        mnemonics.push(mnemonic_from_entropy(random, Language::English)?);
+    }
+    mnemonics.push(mnemonic_from_entropy(last, Language::English)?);
+
+    Ok(mnemonics)
+}
+
+/// Caption of a Seed XOR part's QR code: its position and its own fingerprint
+pub fn seed_xor_caption(part: usize, parts: usize, fingerprint: &str) -> String {
+    format!("Seed XOR part {} of {} - fp {}", part, parts, fingerprint)
+}
+
+/// Distinct values and repeats among a sample of master fingerprints
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FingerprintStats {
+    pub total: usize,
+    pub distinct: usize,
+    /// Fingerprints seen more than once, with their counts, sorted
+    pub collisions: Vec<(String, usize)>,
+}
+
+/// Count distinct fingerprints and collect any that repeat
+pub fn fingerprint_stats(fingerprints: &[String]) -> FingerprintStats {
+    let mut counts = std::collections::BTreeMap::new();
+    for fingerprint in fingerprints {
+        *counts.entry(fingerprint.as_str()).or_insert(0) += 1;
+    }
+    FingerprintStats {
+        total: fingerprints.len(),
+        distinct: counts.len(),
+        collisions: counts
+            .into_iter()
+            .filter(|&(_, count)| count > 1)
+            .map(|(fingerprint, count)| (fingerprint.to_string(), count))
+            .collect(),
+    }
+}
+
+/// Expected number of colliding pairs among `n` uniformly random 32-bit
+/// fingerprints (birthday bound): n(n-1)/2 / 2^32
+pub fn expected_fingerprint_collisions(n: usize) -> f64 {
+    let n = n as f64;
+    n * (n - 1.0) / 2.0 / 4_294_967_296.0
+}
+
+/// Public identifiers of one passphrase-derived wallet on the shared mnemonic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletKeys {
+    pub fingerprint: String,
+    pub account_xpub: String,
+    pub first_address: String,
+}
+
+/// Name of the `--audit-log` file inside the output directory
+pub const AUDIT_LOG_FILE: &str = "audit.log";
+
+/// One `--audit-log` line. Only public identifiers are recorded (never words,
+/// keys or xpubs); the label is quoted so spaces cannot shift the fields
+pub fn format_audit_log_line(
+    timestamp: &Timestamp,
+    label: &str,
+    network: Network,
+    word_count: usize,
+    fingerprint: &str,
+) -> String {
+    format!(
+        "{} label={:?} network={} words={} fingerprint={}\n",
+        timestamp.format("%Y-%m-%dT%H:%M:%S%:z"),
+        label,
+        network_name(network),
+        word_count,
+        fingerprint
+    )
+}
+
+/// Fingerprint, BIP84 account xpub and first BIP84 receive address of a master key
+pub fn wallet_keys(master: &Xpriv, network: Network) -> Result<WalletKeys, KeygenError> {
+    let (_, first_address) = derive_addresses(master, network, 1)?.remove(0);
+    Ok(WalletKeys {
+        fingerprint: get_hardware_wallet_fingerprint(master),
+        account_xpub: derive_account_xpub(master, network, 84, 0)?.to_string(),
+        first_address,
+    })
+}
+
+/// [`wallet_keys`] for each passphrase on the same mnemonic, to document a set
+/// of passphrase wallets at once. Each master key is wiped once summarized
+pub fn passphrase_matrix(
+    mnemonic: &Mnemonic,
+    network: Network,
+    passphrases: &[Zeroizing<String>],
+) -> Result<Vec<WalletKeys>, KeygenError> {
+    passphrases
+        .iter()
+        .map(|passphrase| {
+            let mut master = derive_master_key(&generate_seed(mnemonic, passphrase), network)?;
+            let keys = wallet_keys(&master, network);
+            master.private_key.non_secure_erase();
+            keys
+        })
+        .collect()
+}
+
+/// Console table of a [`passphrase_matrix`]: one row per passphrase, labelled
+/// by its line number so the passphrases themselves are never shown
+pub fn format_passphrase_matrix(rows: &[WalletKeys]) -> String {
+    let mut output = format!("{:<6} {:<11} {}\n", "Line", "Fingerprint", "First address");
+    for (i, keys) in rows.iter().enumerate() {
+        output.push_str(&format!(
+            "{:<6} {:<11} {}\n",
+            format!("#{}", i + 1),
+            keys.fingerprint,
+            keys.first_address
+        ));
+    }
+    output
+}
+
+/// 1-based positions where two mnemonics differ. When the word counts differ,
+/// every position past the shorter phrase counts as a mismatch
+pub fn mnemonic_differences(first: &Mnemonic, second: &Mnemonic) -> Vec<usize> {
+    let first: Vec<&str> = first.words().collect();
+    let second: Vec<&str> = second.words().collect();
+    (0..first.len().max(second.len()))
+        .filter(|&i| first.get(i) != second.get(i))
+        .map(|i| i + 1)
+        .collect()
+}
+
+/// Console table comparing two mnemonics word by word, followed by their
+/// fingerprints (derived with the same passphrase) and a one-line verdict
+pub fn format_mnemonic_comparison(
+    first: &Mnemonic,
+    second: &Mnemonic,
+    first_fingerprint: &str,
+    second_fingerprint: &str,
+) -> String {
+    let first_words: Vec<&str> = first.words().collect();
+    let second_words: Vec<&str> = second.words().collect();
+    let differences = mnemonic_differences(first, second);
+
+    let mut output = format!("{:>3}  {:<10} {:<10} {}\n", "#", "First", "Second", "Match");
+    for i in 0..first_words.len().max(second_words.len()) {
+        output.push_str(&format!(
+            "{:>3}  {:<10} {:<10} {}\n",
+            i + 1,
+            first_words.get(i).unwrap_or(&"-"),
+            second_words.get(i).unwrap_or(&"-"),
+            if differences.contains(&(i + 1)) {
+                "✗ MISMATCH"
+            } else {
+                "✓"
+            }
+        ));
+    }
+    output.push('\n');
+    output.push_str(&format!(
+        "Fingerprints: {} / {} ({})\n",
+        first_fingerprint,
+        second_fingerprint,
+        if first_fingerprint == second_fingerprint {
+            "match"
+        } else {
+            "DIFFERENT"
+        }
+    ));
+    if differences.is_empty() {
+        output.push_str("Result: the two phrases are identical\n");
+    } else {
+        output.push_str(&format!(
+            "Result: {} of {} positions differ\n",
+            differences.len(),
+            first_words.len().max(second_words.len())
+        ));
+    }
+    output
+}
+
+/// BIP137 header byte for a native segwit (P2WPKH) signature; the recovery id
+/// (0-3) is added to it
+const BIP137_P2WPKH_HEADER: u8 = 39;
+
+/// Sign `message` with the first BIP84 receive key (m/84'/coin'/0'/0/0) over
+/// the Bitcoin Signed Message hash, for proof of ownership.
+/// Returns the address and the base64 BIP137 signature
+pub fn sign_message(
+    master: &Xpriv,
+    network: Network,
+    message: &str,
+) -> Result<(String, String), KeygenError> {
+    let secp = Secp256k1::new();
+    let path: DerivationPath = format!("m/84'/{}'/0'/0/0", coin_type(network)).parse()?;
+    let mut child = master.derive_priv(&secp, &path)?;
+    let address = address_for_purpose(&Xpub::from_priv(&secp, &child), 84, network);
+
+    let digest = Message::from_digest(signed_msg_hash(message).to_byte_array());
+    let signature = secp.sign_ecdsa_recoverable(&digest, &child.private_key);
+    child.private_key.non_secure_erase();
+
+    let (recovery_id, compact) = signature.serialize_compact();
+    let mut bytes = [0u8; 65];
+    bytes[0] = BIP137_P2WPKH_HEADER + recovery_id.to_i32() as u8;
+    bytes[1..].copy_from_slice(&compact);
+    Ok((address.to_string(), BASE64_STANDARD.encode(bytes)))
+}
+
+/// Check a base64 BIP137 signature from [`sign_message`] against a P2WPKH address
+pub fn verify_message(address: &str, message: &str, signature: &str) -> Result<bool, KeygenError> {
+    let invalid = |e: &dyn std::fmt::Display| {
+        KeygenError::InvalidArgument(format!("Invalid message signature: {}", e))
+    };
+    let address = Address::from_str(address)
+        .map_err(|e| KeygenError::InvalidArgument(format!("Invalid address: {}", e)))?
+        .assume_checked();
+    let bytes = BASE64_STANDARD.decode(signature).map_err(|e| invalid(&e))?;
+    let signature = MessageSignature::from_slice(&bytes).map_err(|e| invalid(&e))?;
+    let pubkey = signature
+        .recover_pubkey(&Secp256k1::verification_only(), signed_msg_hash(message))
+        .map_err(|e| invalid(&e))?;
+    Ok(pubkey.compressed
+        && address.script_pubkey()
+            == ScriptBuf::new_p2wpkh(&CompressedPublicKey(pubkey.inner).wpubkey_hash()))
+}
+
+/// One-line `KEY=VALUE` summary for provisioning scripts (public values only)
+pub fn create_summary_line(keys: &WalletKeys, network: Network, word_count: usize) -> String {
+    format!(
+        "FINGERPRINT={} NETWORK={} WORDS={} XPUB={} FIRST_ADDRESS={}",
+        keys.fingerprint,
+        network_name(network),
+        word_count,
+        keys.account_xpub,
+        keys.first_address
+    )
+}
+
+/// Structured wallet export written to `wallet.json`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WalletJson {
+    pub label: String,
+    pub timestamp: String,
+    pub network: String,
+    pub word_count: usize,
+    pub words: Vec<String>,
+    pub primary: WalletKeys,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duress: Option<WalletKeys>,
+}
+
+/// Serialize the wallet as pretty-printed JSON for tooling integration
+pub fn create_wallet_json(
+    mnemonic: &Mnemonic,
+    label: &str,
+    network: Network,
+    primary: &WalletKeys,
+    duress: Option<&WalletKeys>,
+    generated: &Timestamp,
+) -> Result<String, KeygenError> {
+    let wallet = WalletJson {
+        label: label.to_string(),
+        timestamp: generated.to_rfc3339(),
+        network: network_name(network).to_string(),
+        word_count: mnemonic.word_count(),
+        words: mnemonic.words().map(str::to_string).collect(),
+        primary: primary.clone(),
+        duress: duress.cloned(),
+    };
+    Ok(serde_json::to_string_pretty(&wallet)?)
+}
+
+/// Swap box drawing and symbols for plain ASCII (`--ascii`) so output survives
+/// serial consoles and dot-matrix printers: `═`/`─` become `=`/`-`, `✓`/`✗`
+/// become `OK`/`X`, `□` becomes `[ ]` and the `⚠️` emoji becomes `!`.
+/// Other text, such as non-English words, is kept as is
+pub fn ascii_decorations(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '═' => output.push('='),
+            '─' => output.push('-'),
+            '✓' => output.push_str("OK"),
+            '✗' => output.push('X'),
+            '□' => output.push_str("[ ]"),
+            '≈' => output.push('~'),
+            '⚠' => output.push('!'),
+            '✂' => output.push_str("8<"),
+            '\u{fe0f}' => {}
+            _ => output.push(ch),
+        }
+    }
+    output
+}
+
+/// Render data as a printable ASCII-art QR code ('#' = dark module)
+pub fn render_qr_ascii(data: &str, ec_level: EcLevel) -> Result<String, KeygenError> {
+    let code = QrCode::with_error_correction_level(data.as_bytes(), ec_level)?;
+    Ok(code
+        .render::<char>()
+        .dark_color('#')
+        .light_color(' ')
+        .module_dimensions(2, 1)
+        .build())
+}
+
+/// Render data as a QR code PNG image, returned as encoded bytes
+pub fn render_qr_png(data: &str, ec_level: EcLevel) -> Result<Vec<u8>, KeygenError> {
+    let code = QrCode::with_error_correction_level(data.as_bytes(), ec_level)?;
+    let image = code
+        .render::<image::Luma<u8>>()
+        .min_dimensions(400, 400)
+        .build();
+
+    let mut png = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut png, image::ImageFormat::Png)?;
+    Ok(png.into_inner())
+}
+
+/// 5x7 bitmap of a caption character, one row per byte (bit 4 = leftmost
+/// column). Lowercase is drawn as uppercase; anything else outside `0-9`,
+/// `A-Z` and `-` is drawn blank
+fn caption_glyph(c: char) -> [u8; 7] {
+    match c.to_ascii_uppercase() {
+        '0' => [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e],
+        '1' => [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+        '2' => [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+        '3' => [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e],
+        '4' => [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+        '5' => [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e],
+        '6' => [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e],
+        '7' => [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e],
+        '9' => [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c],
+        'A' => [0x0e, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11],
+        'B' => [0x1e, 0x11, 0x11, 0x1e, 0x11, 0x11, 0x1e],
+        'C' => [0x0e, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0e],
+        'D' => [0x1c, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1c],
+        'E' => [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x1f],
+        'F' => [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x10],
+        'G' => [0x0e, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0f],
+        'H' => [0x11, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11],
+        'I' => [0x0e, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0c],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1f],
+        'M' => [0x11, 0x1b, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e],
+        'P' => [0x1e, 0x11, 0x11, 0x1e, 0x10, 0x10, 0x10],
+        'Q' => [0x0e, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0d],
+        'R' => [0x1e, 0x11, 0x11, 0x1e, 0x14, 0x12, 0x11],
+        'S' => [0x0f, 0x10, 0x10, 0x0e, 0x01, 0x01, 0x1e],
+        'T' => [0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0a, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0a],
+        'X' => [0x11, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0a, 0x04, 0x04, 0x04],
+        'Z' => [0x1f, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1f],
+        '-' => [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00],
+        _ => [0; 7],
+    }
+}
+
+/// [`render_qr_png`] with `caption` drawn in a white strip below the code,
+/// so printed codes can be told apart without scanning them
+pub fn render_captioned_qr_png(
+    data: &str,
+    ec_level: EcLevel,
+    caption: &str,
+) -> Result<Vec<u8>, KeygenError> {
+    const SCALE: u32 = 2;
+    const ADVANCE: u32 = 6 * SCALE;
+    const MARGIN: u32 = 16;
+
+    let code = QrCode::with_error_correction_level(data.as_bytes(), ec_level)?;
+    let qr = code
+        .render::<image::Luma<u8>>()
+        .min_dimensions(400, 400)
+        .build();
+
+    let text_width = caption.chars().count() as u32 * ADVANCE;
+    let width = qr.width().max(text_width + 2 * MARGIN);
+    let height = qr.height() + 7 * SCALE + 2 * MARGIN;
+    let mut image = image::GrayImage::from_pixel(width, height, image::Luma([255]));
+    image::imageops::overlay(&mut image, &qr, i64::from((width - qr.width()) / 2), 0);
+
+    let left = (width - text_width) / 2;
+    let top = qr.height() + MARGIN;
+    for (i, c) in caption.chars().enumerate() {
+        for (row, bits) in caption_glyph(c).iter().enumerate() {
+            for column in 0..5 {
+                if bits & (0x10 >> column) == 0 {
+                    continue;
+                }
+                let x = left + i as u32 * ADVANCE + column * SCALE;
+                let y = top + row as u32 * SCALE;
+                for (dx, dy) in (0..SCALE).flat_map(|dx| (0..SCALE).map(move |dy| (dx, dy))) {
+                    image.put_pixel(x + dx, y + dy, image::Luma([0]));
+                }
+            }
+        }
+    }
+
+    let mut png = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut png, image::ImageFormat::Png)?;
+    Ok(png.into_inner())
+}
+
+/// SeedQR digit string: each word's 0-based BIP39 wordlist index as four
+/// zero-padded digits (48 digits for 12 words, 96 for 24)
+pub fn seedqr_digits(mnemonic: &Mnemonic) -> Result<Zeroizing<String>, KeygenError> {
+    let word_count = mnemonic.word_count();
+    if word_count != 12 && word_count != 24 {
+        return Err(KeygenError::InvalidArgument(format!(
+            "SeedQR supports 12 or 24 words, not {}",
+            word_count
+        )));
+    }
+    let mut digits = Zeroizing::new(String::with_capacity(word_count * 4));
+    for index in mnemonic.word_indices() {
+        digits.push_str(&format!("{:04}", index));
+    }
+    Ok(digits)
+}
+
+/// Render a standard SeedQR PNG. The data is all digits, so the encoder uses
+/// numeric mode; at error correction `L` this gives the 25x25 (12 words) and
+/// 29x29 (24 words) grids that SeedSigner and Coldcard expect
+pub fn render_seedqr_png(mnemonic: &Mnemonic) -> Result<Vec<u8>, KeygenError> {
+    render_qr_png(&seedqr_digits(mnemonic)?, EcLevel::L)
+}
+
+/// Base32 data characters per BBQr frame (a multiple of 8, so every frame but
+/// the last decodes to whole bytes); small enough to scan reliably off a screen
+pub const BBQR_FRAME_CHARS: usize = 400;
+
+/// BBQr allows at most 36^2 - 1 frames (two base36 digits)
+const BBQR_MAX_FRAMES: usize = 1295;
+
+/// RFC 4648 base32 without padding, as used by BBQr encoding `2`
+pub fn base32_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+    let mut encoded = String::with_capacity(data.len().div_ceil(5) * 8);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | u16::from(byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(ALPHABET[usize::from((buffer >> bits) & 0x1f)] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)] as char);
+    }
+    encoded
+}
+
+/// Two-digit uppercase base36, as used in BBQr frame headers
+fn base36_pair(value: usize) -> String {
+    const DIGITS: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+    format!(
+        "{}{}",
+        DIGITS[value / 36] as char,
+        DIGITS[value % 36] as char
+    )
+}
+
+/// Split a text payload into BBQr (Better Bitcoin QR) frames: base32 encoding
+/// (`2`), unicode text file type (`U`), each frame prefixed with
+/// `B$2U<total><index>` in base36. The data is spread evenly so no frame is
+/// much shorter than the others
+pub fn bbqr_frames(payload: &[u8], max_chars: usize) -> Result<Vec<String>, KeygenError> {
+    if max_chars < 8 {
+        return Err(KeygenError::Render(
+            "BBQr frames need room for at least 8 data characters".to_string(),
+        ));
+    }
+    let encoded = base32_encode(payload);
+    let count = encoded.len().div_ceil(max_chars - max_chars % 8).max(1);
+    if count > BBQR_MAX_FRAMES {
+        return Err(KeygenError::Render(format!(
+            "Payload needs {} BBQr frames (maximum {})",
+            count, BBQR_MAX_FRAMES
+        )));
+    }
+    let per_frame = encoded.len().div_ceil(count).div_ceil(8) * 8;
+
+    Ok((0..count)
+        .map(|i| {
+            let start = (i * per_frame).min(encoded.len());
+            let end = ((i + 1) * per_frame).min(encoded.len());
+            format!(
+                "B$2U{}{}{}",
+                base36_pair(count),
+                base36_pair(i),
+                &encoded[start..end]
+            )
+        })
+        .collect())
+}
+
+/// Render the metal-plate layout as an A4 PDF: header with label, fingerprint and
+/// network, then the numbered 4-column word grid in Courier (a monospace core
+/// PDF font, so letter widths line up with stamping stencils). Core fonts only
+/// cover Latin script, so non-Latin wordlists are rejected. With
+/// `passphrase_required` the header states that the words alone do not restore
+/// the wallet
+pub fn create_printable_pdf(
+    mnemonic: &Mnemonic,
+    fingerprint: &str,
+    label: &str,
+    network: Network,
+    generated: &Timestamp,
+    passphrase_required: bool,
+) -> Result<Vec<u8>, KeygenError> {
+    if matches!(
+        mnemonic.language(),
+        Language::Japanese
+            | Language::Korean
+            | Language::SimplifiedChinese
+            | Language::TraditionalChinese
+    ) {
+        return Err(KeygenError::Render(
+            "--pdf supports Latin-script wordlists only (use the text output)".to_string(),
+        ));
+    }
+
+    let (doc, page, layer) = PdfDocument::new("Bitcoin Seed Phrase", Mm(210.0), Mm(297.0), "Seed");
+    let layer = doc.get_page(page).get_layer(layer);
+    let bold = doc.add_builtin_font(BuiltinFont::CourierBold)?;
+    let regular = doc.add_builtin_font(BuiltinFont::Courier)?;
+
+    // Header
+    layer.use_text(
+        "BITCOIN SEED PHRASE - METAL PLATE BACKUP",
+        16.0,
+        Mm(20.0),
+        Mm(275.0),
+        &bold,
+    );
+    let header = [
+        format!("Label: {}", label),
+        format!("Fingerprint: {}", fingerprint),
+        format!("Network: {}", network_display_name(network)),
+        format!(
+            "Word Count: {} words ({} bits entropy)",
+            mnemonic.word_count(),
+            entropy_bits_for_word_count(mnemonic.word_count())
+        ),
+        format!("Generated: {}", format_timestamp(generated)),
+    ];
+    for (i, line) in header.iter().enumerate() {
+        layer.use_text(line, 11.0, Mm(20.0), Mm(262.0 - 7.0 * i as f32), &regular);
+    }
+    if passphrase_required {
+        let notice = [
+            "*** PASSPHRASE REQUIRED *** These words alone do NOT restore this wallet.",
+            "The BIP39 passphrase is not written here; the fingerprint above is WITH it.",
+        ];
+        for (i, line) in notice.iter().enumerate() {
+            layer.use_text(*line, 10.0, Mm(20.0), Mm(226.0 - 5.0 * i as f32), &bold);
+        }
+    }
+
+    // Numbered 4-column word grid, same order as the text printable
+    let grid_top = 215.0;
+    for (i, word) in mnemonic.words().enumerate() {
+        let (row, column) = (i / 4, i % 4);
+        layer.use_text(
+            format!("{:2}. {}", i + 1, word.to_uppercase()),
+            14.0,
+            Mm(20.0 + 45.0 * column as f32),
+            Mm(grid_top - 12.0 * row as f32),
+            &bold,
+        );
+    }
+
+    layer.use_text(
+        "Anyone with these words can spend your bitcoin. Never photograph or share them.",
+        9.0,
+        Mm(20.0),
+        Mm(30.0),
+        &regular,
+    );
+
+    Ok(doc.save_to_bytes()?)
+}
+
+/// Compact one-page summary for a sealed envelope backup: the words in a
+/// 4-column grid, fingerprint, first BIP84 receive address, BIP84 account
+/// xpub and a small QR code of that address for receiving funds
+pub fn create_paper_wallet(
+    mnemonic: &Mnemonic,
+    master: &Xpriv,
+    network: Network,
+) -> Result<String, KeygenError> {
+    let words: Vec<&str> = mnemonic.words().collect();
+    let keys = wallet_keys(master, network)?;
+
+    let mut output = String::new();
+    output.push_str("═══════════════════════════════════════════════════════════════\n");
+    output.push_str("              BITCOIN PAPER WALLET - SEALED BACKUP\n");
+    output.push_str("═══════════════════════════════════════════════════════════════\n");
+    output.push_str(&format!(
+        "Fingerprint: {}    Network: {}\n",
+        keys.fingerprint,
+        network_display_name(network)
+    ));
+    output.push_str("Keep sealed. Anyone who reads the words can spend the funds.\n");
+    output.push_str("─────────────────────────────────────────────────────────────\n");
+    output.push_str(&format_word_grid(&words));
+    output.push_str("─────────────────────────────────────────────────────────────\n");
+    output.push_str(&format!(
+        "First receive address (m/84'/{}'/0'/0/0):\n  {}\n",
+        coin_type(network),
+        keys.first_address
+    ));
+    output.push_str(&format!(
+        "Account xpub (m/84'/{}'/0', watch-only):\n  {}\n\n",
+        coin_type(network),
+        keys.account_xpub
+    ));
+    output.push_str(&render_qr_ascii(&keys.first_address, EcLevel::L)?);
+    output.push_str("\n(QR: first receive address)\n");
+    output.push_str("═══════════════════════════════════════════════════════════════\n");
+    Ok(output)
+}
+
+/// Fold-and-cut seed card, an alternative to the metal-plate layout for paper
+/// storage. Cut along the outer lines, then fold along both fold lines so the
+/// middle panel with the words is hidden inside; the signature box on the
+/// outside spans the closing edge, so opening the card breaks the signature.
+/// With `passphrase_required` the outer panel carries the passphrase notice
+pub fn create_seed_card(
+    mnemonic: &Mnemonic,
+    fingerprint: &str,
+    label: &str,
+    network: Network,
+    generated: &Timestamp,
+    passphrase_required: bool,
+) -> String {
+    let cut = "✂ - - - - - - - - - - - - - CUT HERE - - - - - - - - - - - - - -\n";
+    let fold = "- - - - - - - - - - - - - - FOLD HERE - - - - - - - - - - - - - -\n";
+    let words: Vec<&str> = mnemonic.words().collect();
+    let half = words.len().div_ceil(2);
+
+    let mut output = String::new();
+    output.push_str(cut);
+
+    // Outer panel: visible when folded, nothing secret
+    output.push_str("                  BITCOIN SEED CARD - KEEP FOLDED\n\n");
+    output.push_str(&format!("Label: {}\n", label));
+    output.push_str(&format!("Fingerprint: {}\n", fingerprint));
+    output.push_str(&format!("Network: {}\n", network_display_name(network)));
+    output.push_str(&format!("Generated: {}\n\n", format_timestamp(generated)));
+    if passphrase_required {
+        output.push_str(&passphrase_required_notice(&ENGLISH_TEXT, fingerprint));
+    }
+    output.push_str("Do not unfold except to restore the wallet.\n");
+    output.push_str(fold);
+
+    // Middle panel: the words, hidden inside the fold
+    output.push_str(&format!("SEED WORDS ({} words)\n\n", words.len()));
+    for row in 0..half {
+        let left = format!("{:2}. {}", row + 1, words[row]);
+        match words.get(row + half) {
+            Some(word) => output.push_str(&format!("{:<32}{:2}. {}\n", left, row + half + 1, word)),
+            None => output.push_str(&format!("{}\n", left)),
+        }
+    }
+    output.push('\n');
+    output.push_str(fold);
+
+    // Closing panel: tamper evidence across the edge of the folded card
+    output.push_str("TAMPER EVIDENCE - sign across the edge after folding and sealing\n\n");
+    output.push_str("+-------------------------------------------------------------+\n");
+    output.push_str("| Signature:                                                  |\n");
+    output.push_str("|                                                             |\n");
+    output.push_str("| Sealed on (date):              Witness:                     |\n");
+    output.push_str("+-------------------------------------------------------------+\n");
+    output.push_str("□ Card folded with the words inside\n");
+    output.push_str("□ Sealed with tamper-evident tape over the edge\n");
+    output.push_str("□ Signed across the tape and the card\n");
+    output.push_str("□ Checked unbroken on every inspection (record dates on the back)\n");
+    output.push_str(cut);
+    output
+}
+
+/// Create a simple text file with just the words (for easy copying)
+pub fn create_simple_word_list(mnemonic: &Mnemonic) -> String {
+    let words: Vec<&str> = mnemonic.words().collect();
+    let mut output = String::new();
+
+    // Numbered list
+    for (i, word) in words.iter().enumerate() {
+        output.push_str(&format!("{:2}. {}\n", i + 1, word));
+    }
+
+    output
+}
+
+/// Create the word index file: each word's BIP39 wordlist index (0-2047), one
+/// per line, for transfer schemes that carry the 11-bit values instead of words
+pub fn create_index_list(mnemonic: &Mnemonic) -> String {
+    mnemonic
+        .word_indices()
+        .map(|index| format!("{}\n", index))
+        .collect()
+}
+
+/// Create the hardware wallet import file (one word per line, no numbers)
+/// Words are written NFKD-normalized and never joined with the ideographic
+/// space (U+3000) BIP39 uses for displaying Japanese phrases, so the file
+/// parses identically on every wallet
+pub fn create_coldcard_word_list(mnemonic: &Mnemonic) -> String {
+    mnemonic
+        .words()
+        .map(|word| {
+            let mut word = Cow::Borrowed(word);
+            Mnemonic::normalize_utf8_cow(&mut word);
+            word.into_owned()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -1,479 +1,6865 @@
-use bip39::Mnemonic;
-use bitcoin::bip32::Xpriv;
-use bitcoin::Network;
-use chrono::Local;
+use bitcoin::bip32::{DerivationPath, Xpriv, Xpub};
+use bitcoin::hashes::{sha256, Hash};
 /**
  * Bitcoin Key Generator - Simple Rust Implementation
  *
- * This application generates a BIP39 mnemonic seed phrase for use with
- * Bitcoin hardware wallets (Coldcard, Trezor, Ledger, etc.).
+ * Command-line front end: parses options, prompts for secrets and writes the
+ * output files. Key generation and formatting live in the library crate.
  * Designed to run on an air-gapped computer for maximum security.
- *
- * Outputs a printable file optimized for metal plate punching/storage.
  */
+use bitcoin_keygen::*;
+use rayon::prelude::*;
+use serde::Deserialize;
+use std::cell::RefCell;
 use std::fs;
-use std::io::Write;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use zeroize::Zeroizing;
 
-/// Generate a new BIP39 mnemonic (24 words for maximum security)
-/// Most hardware wallets support 12, 18, or 24 word seeds - we use 24 for maximum entropy
-fn generate_mnemonic() -> Result<Mnemonic, Box<dyn std::error::Error>> {
-    let mut entropy = [0u8; 32]; // 256 bits = 24 words
-    getrandom::fill(&mut entropy)?;
+/// Plain-ASCII console output (`--ascii`), set once at startup
+static ASCII_CONSOLE: AtomicBool = AtomicBool::new(false);
 
-    let mnemonic = Mnemonic::from_entropy(&entropy)?;
-    Ok(mnemonic)
+/// Console text as printed: with `--ascii`, box drawing and symbols are replaced
+fn console_text(text: String) -> String {
+    if ASCII_CONSOLE.load(Ordering::Relaxed) {
+        ascii_decorations(&text)
+    } else {
+        text
+    }
 }
 
-/// Generate seed from mnemonic
-fn generate_seed(mnemonic: &Mnemonic, passphrase: &str) -> [u8; 64] {
-    mnemonic.to_seed(passphrase)
+/// `println!` that honours `--ascii`
+macro_rules! cprintln {
+    () => {
+        println!()
+    };
+    ($($arg:tt)*) => {
+        println!("{}", console_text(format!($($arg)*)))
+    };
 }
 
-/// Derive master private key from seed
-fn derive_master_key(
-    seed: &[u8; 64],
-    network: Network,
-) -> Result<Xpriv, Box<dyn std::error::Error>> {
-    let key = Xpriv::new_master(network, seed)?;
-    Ok(key)
+/// `print!` that honours `--ascii`
+macro_rules! cprint {
+    ($($arg:tt)*) => {
+        print!("{}", console_text(format!($($arg)*)))
+    };
 }
 
-/// Get master key fingerprint in hardware wallet format (8 hex characters)
-fn get_hardware_wallet_fingerprint(key: &Xpriv) -> String {
-    use bitcoin::secp256k1::Secp256k1;
-    let secp = Secp256k1::new();
-    let fingerprint = key.fingerprint(&secp);
-    let fingerprint_bytes = fingerprint.as_bytes();
-    format!(
-        "{:08x}",
-        u32::from_be_bytes([
-            fingerprint_bytes[0],
-            fingerprint_bytes[1],
-            fingerprint_bytes[2],
-            fingerprint_bytes[3]
-        ])
+/// Where the mnemonic entropy comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntropySource {
+    /// Operating system RNG via `getrandom` (default)
+    Random,
+    /// Physical dice rolls read from stdin
+    Dice,
+    /// Coin flips (one `0`/`1` per entropy bit) read from stdin
+    Coins,
+}
+
+/// `--electrum` mode: generate an Electrum segwit seed instead of a BIP39 wallet
+fn run_electrum(options: &Options, output_dir: &Path) -> Result<(), KeygenError> {
+    cprintln!("Generating Electrum segwit seed (not BIP39)...");
+    let seed = generate_electrum_seed()?;
+    cprintln!(
+        "✓ Seed version prefix {} (native segwit)",
+        ELECTRUM_SEGWIT_PREFIX
+    );
+
+    let encryption_password = if options.encrypt {
+        Some(prompt_encryption_password(true)?)
+    } else {
+        None
+    };
+    let output = OutputWriter::new(
+        output_dir,
+        options.no_write,
+        encryption_password.as_deref().map(String::as_str),
     )
+    .with_ascii(options.ascii);
+    output.prepare()?;
+    output.write(
+        "electrum_seed.txt",
+        "Electrum seed file",
+        create_electrum_seed_output(
+            &seed,
+            &options.label,
+            &options.timestamp.unwrap_or_else(now_timestamp),
+        )
+        .as_bytes(),
+    )?;
+    output.write_manifest()?;
+    Ok(())
+}
+
+/// `--from-xprv` mode: fingerprint, addresses, account keys and descriptors of
+/// an existing master key. There is no mnemonic, so nothing is printable as words
+fn run_from_xprv(
+    options: &Options,
+    xprv: &str,
+    output_dir: &Path,
+) -> Result<WalletKeys, KeygenError> {
+    let mut master_key = parse_master_xprv(xprv, options.network)?;
+    cprintln!(
+        "✓ Parsed master private key ({})",
+        network_display_name(options.network)
+    );
+    let fingerprint = get_hardware_wallet_fingerprint(&master_key);
+    let addresses = derive_addresses_for(
+        &master_key,
+        options.network,
+        &options.scripts,
+        ADDRESS_PREVIEW_COUNT,
+    )?;
+    let account_path = account_derivation_path(options.network, 84, 0)?;
+    let account_xpub = derive_account_xpub(&master_key, options.network, 84, 0)?;
+    let descriptors = build_descriptors_for(&master_key, options.network, &options.scripts)?;
+    let mut accounts = account_summary(&master_key, options.network);
+    accounts.retain(|account| options.scripts.contains(&account.purpose));
+    let keys = wallet_keys(&master_key, options.network)?;
+    master_key.private_key.non_secure_erase();
+
+    let output = OutputWriter::new(output_dir, options.no_write, None).with_ascii(options.ascii);
+    output.prepare()?;
+    let bip84 = options.scripts.contains(&84);
+    if bip84 {
+        output.write(
+            "account_xpub.txt",
+            "account xpub file",
+            create_account_xpub_output(&account_xpub, &account_path, &fingerprint, 84).as_bytes(),
+        )?;
+    }
+    output.write(
+        "accounts.txt",
+        "account summary file",
+        create_accounts_output(&accounts, &fingerprint).as_bytes(),
+    )?;
+    output.write(
+        "descriptors.txt",
+        "descriptors file",
+        create_descriptors_output(&descriptors).as_bytes(),
+    )?;
+    output.write_manifest()?;
+
+    cprintln!();
+    cprintln!("Fingerprint: {}", fingerprint);
+    cprintln!("⚠ No mnemonic: back up the xprv itself, it cannot be turned into words");
+    cprintln!();
+    if bip84 {
+        cprintln!("Account xpub (m/{}):", account_path);
+        cprintln!("  {}", account_xpub);
+        cprintln!();
+    }
+    cprintln!("Receive addresses:");
+    for (path, address) in &addresses {
+        cprintln!("  {:20} {}", path, address);
+    }
+    Ok(keys)
+}
+
+/// `--fingerprint-only` mode: the master fingerprint of the `--recover` phrase
+/// (and passphrase), with no address derivation and no files written
+fn recovered_fingerprint(options: &Options) -> Result<String, KeygenError> {
+    let phrase = options.recover.as_ref().ok_or_else(|| {
+        KeygenError::InvalidArgument("--fingerprint-only requires --recover".to_string())
+    })?;
+    let mnemonic = recover_mnemonic(phrase, options.language)?;
+    let passphrase = bip39_passphrase(options)?;
+    mnemonic_fingerprint(&mnemonic, &passphrase, options.network)
+}
+
+/// Master fingerprint of `mnemonic` with `passphrase`, erasing the key after
+fn mnemonic_fingerprint(
+    mnemonic: &Mnemonic,
+    passphrase: &str,
+    network: Network,
+) -> Result<String, KeygenError> {
+    let mut master_key = derive_master_key(&generate_seed(mnemonic, passphrase), network)?;
+    let fingerprint = get_hardware_wallet_fingerprint(&master_key);
+    master_key.private_key.non_secure_erase();
+    Ok(fingerprint)
+}
+
+/// `--compare` mode: parse both phrases and print where they differ and
+/// whether they open the same wallet
+fn run_compare(options: &Options, first: &str, second: &str) -> Result<(), KeygenError> {
+    let first = recover_mnemonic(first, options.language)?;
+    let second = recover_mnemonic(second, options.language)?;
+    let passphrase = bip39_passphrase(options)?;
+    cprint!(
+        "{}",
+        format_mnemonic_comparison(
+            &first,
+            &second,
+            &mnemonic_fingerprint(&first, &passphrase, options.network)?,
+            &mnemonic_fingerprint(&second, &passphrase, options.network)?,
+        )
+    );
+    Ok(())
+}
+
+/// `--explore` mode: load the `--recover` seed and derive keys for paths typed
+/// on stdin until end of input
+fn run_explore(options: &Options) -> Result<(), KeygenError> {
+    let phrase = options
+        .recover
+        .as_ref()
+        .ok_or_else(|| KeygenError::InvalidArgument("--explore requires --recover".to_string()))?;
+    let mnemonic = recover_mnemonic(phrase, options.language)?;
+    let passphrase = bip39_passphrase(options)?;
+    let mut master_key =
+        derive_master_key(&generate_seed(&mnemonic, &passphrase), options.network)?;
+    cprintln!(
+        "Loaded seed with fingerprint {}. Enter derivation paths (e.g. m/84'/0'/0'), \
+         Ctrl-D to quit. xprvs are shown: keep this screen private.",
+        get_hardware_wallet_fingerprint(&master_key)
+    );
+    let result = explore_paths(
+        &master_key,
+        options.network,
+        &mut prompt_input(options, "--explore")?,
+        &mut std::io::stdout(),
+    );
+    master_key.private_key.non_secure_erase();
+    result
+}
+
+/// Derivation path explorer loop: prompt, read one path per line from `input`
+/// and write its fingerprint, xpub, xprv and (for standard paths) address to
+/// `output`. Invalid paths are reported and the loop continues; blank lines
+/// are skipped and `quit`/`exit` or end of input stops it
+fn explore_paths<R, W>(
+    master: &Xpriv,
+    network: Network,
+    input: &mut R,
+    output: &mut W,
+) -> Result<(), KeygenError>
+where
+    R: std::io::BufRead,
+    W: Write,
+{
+    loop {
+        write!(output, "path> ")?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            writeln!(output)?;
+            return Ok(());
+        }
+        let line = line.trim();
+        match line {
+            "" => continue,
+            "quit" | "exit" => return Ok(()),
+            _ => {}
+        }
+
+        match parse_derivation_path(line)
+            .and_then(|path| derive_custom_path(master, network, &path))
+        {
+            Ok(derived) => {
+                writeln!(output, "  path:        m/{}", derived.path)?;
+                writeln!(output, "  fingerprint: {}", derived.xpub.fingerprint())?;
+                writeln!(output, "  xpub:        {}", derived.xpub)?;
+                writeln!(output, "  xprv:        {}", derived.xprv)?;
+                if let Some(address) = &derived.address {
+                    writeln!(output, "  address:     {}", address)?;
+                }
+            }
+            Err(e) => writeln!(output, "  error: {}", e)?,
+        }
+    }
+}
+
+/// `--last-word` mode: list every final word that completes a valid checksum
+fn run_last_word(partial: &str, language: Language) -> Result<(), KeygenError> {
+    let candidates = last_word_candidates(partial, language)?;
+    let word_count = partial.split_whitespace().count() + 1;
+    cprintln!(
+        "{} valid final words for this {}-word phrase:",
+        candidates.len(),
+        word_count
+    );
+    for row in candidates.chunks(8) {
+        cprintln!(
+            "  {}",
+            row.iter()
+                .map(|w| format!("{:10}", w))
+                .collect::<String>()
+                .trim_end()
+        );
+    }
+    cprintln!();
+    cprintln!("Each candidate is a different wallet. Check the fingerprint of each");
+    cprintln!("with --recover --no-write against your hardware wallet or records.");
+    Ok(())
+}
+
+/// Milliseconds with one decimal, for `--bench` output
+fn format_millis(duration: Duration) -> String {
+    format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+}
+
+/// `--bench` mode: time seed and address derivation on a throwaway wallet
+/// (nothing is written or printed) to size batch runs on slow hardware
+fn run_bench(options: &Options) -> Result<(), KeygenError> {
+    let mnemonic = generate_mnemonic(options.word_count, options.language)?;
+    let seed_time = time_seed_derivation(&mnemonic, "");
+
+    let mut master = derive_master_key(&generate_seed(&mnemonic, ""), options.network)?;
+    let address_time = time_address_derivation(&master, options.network, ADDRESS_PREVIEW_COUNT)?;
+    master.private_key.non_secure_erase();
+
+    cprintln!(
+        "Benchmark ({} words, {}):",
+        options.word_count,
+        network_display_name(options.network)
+    );
+    cprintln!(
+        "  Seed derivation (PBKDF2-HMAC-SHA512, 2048 rounds): {}",
+        format_millis(seed_time)
+    );
+    cprintln!(
+        "  Address derivation ({} per script type, 4 types):  {}",
+        ADDRESS_PREVIEW_COUNT,
+        format_millis(address_time)
+    );
+    cprintln!(
+        "  Estimated per wallet:                              {}",
+        format_millis(seed_time + address_time)
+    );
+    Ok(())
+}
+
+/// `--selftest` mode: print one pass/fail line per check, failing if any did.
+/// Runs before the startup wordlist check so a bad list is reported, not fatal
+fn run_selftest_report(checks: &[SelfTestCheck]) -> Result<(), KeygenError> {
+    cprintln!("Self-test:");
+    for check in checks {
+        match &check.result {
+            Ok(()) => cprintln!("  ✓ {}", check.name),
+            Err(e) => cprintln!("  ✗ {}: {}", check.name, e),
+        }
+    }
+    let failed = checks.iter().filter(|check| check.result.is_err()).count();
+    if failed > 0 {
+        return Err(KeygenError::Integrity(format!(
+            "Self-test failed: {} of {} checks did not pass",
+            failed,
+            checks.len()
+        )));
+    }
+    cprintln!("✓ All {} checks passed", checks.len());
+    Ok(())
+}
+
+/// `--fingerprint-stats` mode: derive the master fingerprints of `count`
+/// throwaway RNG wallets in parallel and report distinct values and repeats.
+/// Nothing is written and no words are shown
+fn run_fingerprint_stats(options: &Options, count: usize) -> Result<FingerprintStats, KeygenError> {
+    let fingerprints = (0..count)
+        .into_par_iter()
+        .map(|_| {
+            let mnemonic = generate_mnemonic(options.word_count, options.language)?;
+            mnemonic_fingerprint(&mnemonic, "", options.network)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let stats = fingerprint_stats(&fingerprints);
+
+    cprintln!(
+        "Fingerprint stats ({} wallets, {} words):",
+        stats.total,
+        options.word_count
+    );
+    cprintln!("  Distinct fingerprints: {}", stats.distinct);
+    cprintln!(
+        "  Collisions:            {} (about {:.4} expected for 32-bit fingerprints)",
+        stats.total - stats.distinct,
+        expected_fingerprint_collisions(stats.total)
+    );
+    for (fingerprint, seen) in &stats.collisions {
+        cprintln!("    {} seen {} times", fingerprint, seen);
+    }
+    Ok(stats)
+}
+
+/// Ask the user to type `YES` before revealing sensitive material
+fn confirm_prompt<R: std::io::BufRead>(prompt: &str, input: &mut R) -> Result<bool, KeygenError> {
+    cprint!("{} Type YES to continue: ", prompt);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(answer.trim() == "YES")
+}
+
+/// Ask before writing seed material into `dir`; only `y` proceeds, so an empty
+/// answer or end of input aborts (e.g. the user meant `--no-write`)
+fn confirm_write<R: std::io::BufRead>(dir: &Path, input: &mut R) -> Result<bool, KeygenError> {
+    cprint!(
+        "About to write seed material to {}. Continue? [y/N] ",
+        dir.display()
+    );
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(answer.trim().eq_ignore_ascii_case("y"))
+}
+
+/// Whether stdin carries the dice rolls, coin flips or mnemonic, so it
+/// cannot also answer prompts
+fn stdin_feeds_input(options: &Options) -> bool {
+    options.recover_stdin || options.entropy_source != EntropySource::Random
+}
+
+/// Whether a phrase is being recovered, including one still waiting on stdin
+fn recovering(options: &Options) -> bool {
+    options.recover.is_some() || options.recover_stdin
+}
+
+/// The controlling terminal, which answers prompts when stdin feeds the input
+fn open_tty() -> std::io::Result<std::io::BufReader<fs::File>> {
+    fs::File::open("/dev/tty").map(std::io::BufReader::new)
+}
+
+/// Where prompt answers are read from: `stdin`, or the terminal returned by
+/// `open_tty` when [`stdin_feeds_input`]
+fn prompt_reader<'a, R: std::io::BufRead + 'a, T: std::io::BufRead + 'a>(
+    options: &Options,
+    stdin: R,
+    open_tty: impl FnOnce() -> std::io::Result<T>,
+) -> std::io::Result<Box<dyn std::io::BufRead + 'a>> {
+    if stdin_feeds_input(options) {
+        Ok(Box::new(open_tty()?))
+    } else {
+        Ok(Box::new(stdin))
+    }
+}
+
+/// Error for prompts that have neither stdin nor a terminal to read from
+fn no_terminal(what: &str) -> KeygenError {
+    KeygenError::InvalidArgument(format!(
+        "{} needs a terminal: --dice, --coins and --recover-stdin use standard input \
+         and no terminal is available to answer on",
+        what
+    ))
+}
+
+/// [`prompt_reader`] on this process's stdin and terminal
+fn prompt_input(options: &Options, what: &str) -> Result<Box<dyn std::io::BufRead>, KeygenError> {
+    prompt_reader(options, std::io::stdin().lock(), open_tty).map_err(|_| no_terminal(what))
+}
+
+/// Flags whose prompts run during the selected mode
+fn prompting_flags(options: &Options) -> Vec<&'static str> {
+    let mut flags = Vec::new();
+    if options.show_entropy {
+        flags.push("--show-entropy");
+    }
+    if options.raw_seed || options.raw_seed_hex {
+        flags.push("--raw-seed");
+    }
+    if options.verify_quiz {
+        flags.push("--verify-quiz");
+    }
+    if options.explore {
+        flags.push("--explore");
+    }
+    flags
+}
+
+/// Confirm the write unless `--yes` or `--no-write` is set. When stdin feeds
+/// the input, the answer is read from the terminal returned by `open_tty`
+/// instead; without one, `--yes` is required
+fn confirm_before_writing<R: std::io::BufRead, T: std::io::BufRead>(
+    options: &Options,
+    stdin: &mut R,
+    open_tty: impl FnOnce() -> std::io::Result<T>,
+) -> Result<(), KeygenError> {
+    if options.no_write || options.yes {
+        return Ok(());
+    }
+    let mut input = prompt_reader(options, stdin, open_tty).map_err(|_| {
+        KeygenError::InvalidArgument(
+            "--dice, --coins and --recover-stdin use standard input, so there is no \
+             terminal to confirm the write on; pass --yes (or --no-write)"
+                .to_string(),
+        )
+    })?;
+    if !confirm_write(&options.output_dir, &mut input)? {
+        return Err(KeygenError::InvalidArgument(format!(
+            "Aborted: nothing was written to {} (pass --yes to skip this prompt, \
+             or --no-write to print instead)",
+            options.output_dir.display()
+        )));
+    }
+    Ok(())
+}
+
+/// Write the 64-byte BIP39 seed for HSMs that ingest it instead of words:
+/// raw bytes to `seed.bin` and/or lowercase hex to `seed.hex`
+fn write_raw_seed(
+    output: &OutputWriter,
+    seed: &[u8; 64],
+    binary: bool,
+    hex: bool,
+) -> Result<(), KeygenError> {
+    if binary {
+        output.write("seed.bin", "raw seed file", seed)?;
+    }
+    if hex {
+        let mut contents = seed_hex(seed);
+        contents.push('\n');
+        output.write("seed.hex", "raw seed hex file", contents.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Number of word positions asked by `--verify-quiz`
+const QUIZ_QUESTIONS: usize = 5;
+
+/// Pick `count` distinct random 1-based word positions for the backup quiz
+/// (partial Fisher-Yates shuffle driven by the OS RNG)
+fn quiz_positions(word_count: usize, count: usize) -> Result<Vec<usize>, KeygenError> {
+    let count = count.min(word_count);
+    let mut positions: Vec<usize> = (1..=word_count).collect();
+    for i in 0..count {
+        let mut random = [0u8; 4];
+        getrandom::fill(&mut random)?;
+        let j = i + u32::from_le_bytes(random) as usize % (word_count - i);
+        positions.swap(i, j);
+    }
+    positions.truncate(count);
+    Ok(positions)
 }
 
-/// Create printable output optimized for metal plate punching
-fn create_printable_output(mnemonic: &Mnemonic, fingerprint: &str, label: &str) -> String {
-    let words: Vec<&str> = mnemonic.words().collect();
-    let now = Local::now();
-    let timestamp = now.format("%Y-%m-%d %H:%M:%S").to_string();
-
-    let mut output = String::new();
-
-    // Header
-    output.push_str("═══════════════════════════════════════════════════════════════\n");
-    output.push_str("           BITCOIN SEED PHRASE - METAL PLATE BACKUP\n");
-    output.push_str("═══════════════════════════════════════════════════════════════\n\n");
-
-    // Label and metadata
-    output.push_str(&format!("Label: {}\n", label));
-    output.push_str(&format!("Generated: {}\n", timestamp));
-    output.push_str(&format!("Fingerprint: {}\n", fingerprint));
-    output.push_str("Word Count: 24 words (256 bits entropy)\n");
-    output.push_str("Network: Bitcoin Mainnet\n\n");
-
-    // Warning
-    output.push_str("⚠️  SECURITY WARNING ⚠️\n");
-    output.push_str("─────────────────────────────────────────────────────────────\n");
-    output.push_str("This seed phrase provides full access to your Bitcoin wallet.\n");
-    output.push_str("Store this metal plate in a secure, fireproof location.\n");
-    output.push_str("Never share this seed phrase with anyone.\n");
-    output.push_str("─────────────────────────────────────────────────────────────\n\n");
-
-    // Seed words in large, clear format for punching
-    output.push_str("SEED WORDS (Punch these in order):\n");
-    output.push_str("═══════════════════════════════════════════════════════════════\n\n");
-
-    // Format words in rows of 4 for easy reading and punching
-    for (i, word) in words.iter().enumerate() {
-        let word_num = i + 1;
-        output.push_str(&format!("{:2}. {:12}", word_num, word));
-
-        // New line every 4 words
-        if word_num % 4 == 0 {
-            output.push('\n');
+/// Ask for the word at each 1-based position and return how many answers
+/// were correct. Comparison ignores case and surrounding whitespace, and the
+/// answer is NFKD-normalized like the BIP39 wordlists, so accented words typed
+/// precomposed (NFC) still match
+fn verify_quiz<R: std::io::BufRead>(
+    words: &[&str],
+    positions: &[usize],
+    input: &mut R,
+) -> Result<usize, KeygenError> {
+    let mut correct = 0;
+    for &position in positions {
+        cprint!("Enter word #{}: ", position);
+        std::io::stdout().flush()?;
+        let mut answer = Zeroizing::new(String::new());
+        input.read_line(&mut answer)?;
+        let lowered = Zeroizing::new(answer.trim().to_lowercase());
+        let mut normalized = std::borrow::Cow::Borrowed(lowered.as_str());
+        Mnemonic::normalize_utf8_cow(&mut normalized);
+        let answer = Zeroizing::new(normalized.into_owned());
+        if secure_compare(answer.as_bytes(), words[position - 1].as_bytes()) {
+            cprintln!("  ✓ Correct");
+            correct += 1;
         } else {
-            output.push_str("  ");
+            cprintln!("  ✗ Incorrect");
+        }
+    }
+    Ok(correct)
+}
+
+/// Check the passphrase and its confirmation match
+fn confirm_passphrase(
+    passphrase: Zeroizing<String>,
+    confirmation: Zeroizing<String>,
+) -> Result<Zeroizing<String>, KeygenError> {
+    if passphrase.is_empty() {
+        return Err(KeygenError::Passphrase(
+            "Passphrase is empty (omit --passphrase to use no passphrase)".to_string(),
+        ));
+    }
+    if !secure_compare(passphrase.as_bytes(), confirmation.as_bytes()) {
+        return Err(KeygenError::Passphrase(
+            "Passphrases do not match".to_string(),
+        ));
+    }
+    Ok(passphrase)
+}
+
+/// The BIP39 passphrase selected by `--passphrase` (prompted) or
+/// `--passphrase-file`, empty when neither is given
+fn bip39_passphrase(options: &Options) -> Result<Zeroizing<String>, KeygenError> {
+    if options.passphrase {
+        let passphrase = prompt_passphrase()?;
+        cprintln!("✓ Using BIP39 passphrase (fingerprint and addresses reflect it)");
+        Ok(passphrase)
+    } else if let Some(path) = &options.passphrase_file {
+        let passphrase = read_passphrase_file(path)?;
+        cprintln!(
+            "✓ Using BIP39 passphrase from {} (fingerprint and addresses reflect it)",
+            path.display()
+        );
+        Ok(passphrase)
+    } else {
+        Ok(Zeroizing::new(String::new()))
+    }
+}
+
+/// Prompt for the BIP39 passphrase twice without echoing it to the terminal
+fn prompt_passphrase() -> Result<Zeroizing<String>, KeygenError> {
+    let passphrase = Zeroizing::new(rpassword::prompt_password("Enter BIP39 passphrase: ")?);
+    let confirmation = Zeroizing::new(rpassword::prompt_password("Confirm BIP39 passphrase: ")?);
+    confirm_passphrase(passphrase, confirmation)
+}
+
+/// Warn (without blocking) when a passphrase is easy to brute-force
+fn warn_weak_passphrase(passphrase: &str) {
+    let bits = passphrase_entropy_bits(passphrase);
+    match assess_passphrase_strength(passphrase) {
+        Strength::Weak => cprintln!(
+            "⚠ WARNING: weak passphrase (~{:.0} bits); anyone holding the seed words \
+             could brute-force it. Use a longer passphrase with mixed character types",
+            bits
+        ),
+        Strength::Moderate => cprintln!(
+            "⚠ Passphrase strength is moderate (~{:.0} bits); consider a longer one",
+            bits
+        ),
+        Strength::Strong => {}
+    }
+}
+
+/// Warn when the passphrase changes under NFKD normalization, which other
+/// wallets may not apply identically
+fn warn_passphrase_normalization(passphrase: &str) {
+    if passphrase_needs_normalization(passphrase) {
+        cprintln!(
+            "⚠ WARNING: passphrase contains characters that change under Unicode NFKD \
+             normalization (accents, ligatures, full-width forms). BIP39 wallets should \
+             normalize it the same way, but a wallet that does not will derive a \
+             different seed. Test recovery on your target wallet, or use plain ASCII"
+        );
+    }
+}
+
+/// Read a BIP39 passphrase from a file, stripping a single trailing newline
+/// (all other whitespace is significant). The file buffer is wiped on drop
+fn read_passphrase_file(path: &Path) -> Result<Zeroizing<String>, KeygenError> {
+    let mut bytes = Zeroizing::new(fs::read(path)?);
+    if bytes.last() == Some(&b'\n') {
+        bytes.pop();
+        if bytes.last() == Some(&b'\r') {
+            bytes.pop();
+        }
+    }
+    if bytes.is_empty() {
+        return Err(KeygenError::Passphrase(format!(
+            "Passphrase file is empty: {}",
+            path.display()
+        )));
+    }
+
+    let passphrase = std::str::from_utf8(&bytes).map_err(|_| {
+        KeygenError::Passphrase(format!(
+            "Passphrase file is not valid UTF-8: {}",
+            path.display()
+        ))
+    })?;
+    Ok(Zeroizing::new(passphrase.to_string()))
+}
+
+/// Read a `--recover-stdin` phrase, so it never appears in the process list.
+/// Accepts the same layouts as `--recover-file`
+fn read_recover_phrase<R: Read>(input: &mut R) -> Result<Zeroizing<String>, KeygenError> {
+    let mut contents = Zeroizing::new(String::new());
+    input.read_to_string(&mut contents)?;
+    let phrase = phrase_from_word_list(&contents);
+    if phrase.is_empty() {
+        return Err(KeygenError::MnemonicParse(
+            "No recovery phrase on stdin".to_string(),
+        ));
+    }
+    Ok(phrase)
+}
+
+/// Load the `--recover-stdin` phrase into a copy of `options`, setting the word
+/// count from it as parse_args does for `--recover`
+fn read_stdin_phrase<R: Read>(options: &Options, input: &mut R) -> Result<Options, KeygenError> {
+    let phrase = read_recover_phrase(input)?;
+    let mut options = options.clone();
+    if let Some(count) = infer_word_count(&phrase) {
+        options.word_count = count;
+    }
+    options.recover = Some(phrase);
+    check_seedqr_word_count(&options)?;
+    Ok(options)
+}
+
+/// SeedQR only has 12 and 24 word layouts
+fn check_seedqr_word_count(options: &Options) -> Result<(), KeygenError> {
+    if options.seedqr && options.word_count != 12 && options.word_count != 24 {
+        return Err(KeygenError::InvalidArgument(
+            "--seedqr only supports 12 or 24 words".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Read a `--passphrase-list` file: one passphrase per line, kept verbatim
+/// apart from the line ending. Blank lines are skipped
+fn read_passphrase_list(path: &Path) -> Result<Vec<Zeroizing<String>>, KeygenError> {
+    let contents = Zeroizing::new(fs::read_to_string(path).map_err(|e| {
+        KeygenError::Passphrase(format!(
+            "Cannot read passphrase list {}: {}",
+            path.display(),
+            e
+        ))
+    })?);
+    let passphrases: Vec<Zeroizing<String>> = contents
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .filter(|line| !line.is_empty())
+        .map(|line| Zeroizing::new(line.to_string()))
+        .collect();
+    if passphrases.is_empty() {
+        return Err(KeygenError::Passphrase(format!(
+            "Passphrase list is empty: {}",
+            path.display()
+        )));
+    }
+    Ok(passphrases)
+}
+
+/// Prompt for the output file encryption password without echo
+/// (entered twice when encrypting)
+fn prompt_encryption_password(confirm: bool) -> Result<Zeroizing<String>, KeygenError> {
+    let password = Zeroizing::new(rpassword::prompt_password("Enter encryption password: ")?);
+    if password.is_empty() {
+        return Err(KeygenError::Passphrase(
+            "Encryption password must not be empty".to_string(),
+        ));
+    }
+    if confirm {
+        let confirmation =
+            Zeroizing::new(rpassword::prompt_password("Confirm encryption password: ")?);
+        if !secure_compare(password.as_bytes(), confirmation.as_bytes()) {
+            return Err(KeygenError::Passphrase(
+                "Encryption passwords do not match".to_string(),
+            ));
+        }
+    }
+    Ok(password)
+}
+
+/// `--decrypt` mode: prompt for the password and write the plaintext to stdout
+fn decrypt_file(path: &Path) -> Result<(), KeygenError> {
+    let data = fs::read(path)?;
+    let password = prompt_encryption_password(false)?;
+    let plaintext = decrypt_payload(&data, &password)?;
+    std::io::stdout().write_all(&plaintext)?;
+    Ok(())
+}
+
+/// Parse a `--multisig` value of the form `<m>-of-<n>`
+fn parse_multisig_spec(spec: &str) -> Result<(usize, usize), KeygenError> {
+    let invalid = || {
+        KeygenError::InvalidArgument(format!(
+            "Invalid multisig spec: {} (expected e.g. 2-of-3)",
+            spec
+        ))
+    };
+    let (threshold, total) = spec.split_once("-of-").ok_or_else(invalid)?;
+    let threshold: usize = threshold.parse().map_err(|_| invalid())?;
+    let total: usize = total.parse().map_err(|_| invalid())?;
+
+    // 15 keys is the standard-policy limit for sortedmulti in wsh
+    if threshold == 0 || threshold > total || !(2..=15).contains(&total) {
+        return Err(KeygenError::InvalidArgument(format!(
+            "Invalid multisig spec: {} (need 1 <= m <= n, 2 <= n <= 15)",
+            spec
+        )));
+    }
+    Ok((threshold, total))
+}
+
+/// Parse a `--bip85` value of the form `<words>:<index>`
+fn parse_bip85_spec(spec: &str) -> Result<(u32, u32), KeygenError> {
+    let invalid = || {
+        KeygenError::InvalidArgument(format!("Invalid BIP85 spec: {} (expected e.g. 12:0)", spec))
+    };
+    let (words, index) = spec.split_once(':').ok_or_else(invalid)?;
+    let words: u32 = words.parse().map_err(|_| invalid())?;
+
+    if ![12, 18, 24].contains(&words) {
+        return Err(KeygenError::InvalidArgument(format!(
+            "Unsupported BIP85 word count: {} (expected 12, 18 or 24)",
+            words
+        )));
+    }
+    Ok((words, parse_bip85_index(index)?))
+}
+
+/// Parse a BIP85 child index (a hardened derivation step, so below 2^31)
+fn parse_bip85_index(value: &str) -> Result<u32, KeygenError> {
+    let index: u32 = value
+        .parse()
+        .map_err(|_| KeygenError::InvalidArgument(format!("Invalid BIP85 index: {}", value)))?;
+    if index >= 1 << 31 {
+        return Err(KeygenError::InvalidArgument(format!(
+            "BIP85 index out of range: {}",
+            index
+        )));
+    }
+    Ok(index)
+}
+
+/// Parse a `--bip85-hex` value of the form `<index>:<bytes>`
+fn parse_bip85_hex_spec(spec: &str) -> Result<(u32, usize), KeygenError> {
+    let invalid = || {
+        KeygenError::InvalidArgument(format!(
+            "Invalid BIP85 hex spec: {} (expected <index>:<bytes>, e.g. 0:64)",
+            spec
+        ))
+    };
+    let (index, bytes) = spec.split_once(':').ok_or_else(invalid)?;
+    let bytes: usize = bytes.parse().map_err(|_| invalid())?;
+    if !(16..=64).contains(&bytes) {
+        return Err(KeygenError::InvalidArgument(format!(
+            "Unsupported BIP85 hex length: {} bytes (expected 16-64)",
+            bytes
+        )));
+    }
+    Ok((parse_bip85_index(index)?, bytes))
+}
+
+/// Parse a `--shamir` value of the form `<threshold>-of-<total>`
+fn parse_shamir_spec(spec: &str) -> Result<(u8, u8), KeygenError> {
+    let invalid = || {
+        KeygenError::InvalidArgument(format!(
+            "Invalid Shamir spec: {} (expected e.g. 2-of-3)",
+            spec
+        ))
+    };
+    let (threshold, total) = spec.split_once("-of-").ok_or_else(invalid)?;
+    let threshold: u8 = threshold.parse().map_err(|_| invalid())?;
+    let total: u8 = total.parse().map_err(|_| invalid())?;
+
+    if threshold == 0 || threshold > total || total > 16 {
+        return Err(KeygenError::InvalidArgument(format!(
+            "Invalid Shamir spec: {} (need 1 <= threshold <= total <= 16)",
+            spec
+        )));
+    }
+    Ok((threshold, total))
+}
+
+/// Output file format selected with `--format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputFormat {
+    /// Printable text files (default)
+    Text,
+    /// A single structured `wallet.json`
+    Json,
+}
+
+/// Parse a QR error-correction level (`L`, `M`, `Q` or `H`)
+fn parse_ec_level(level: &str) -> Result<EcLevel, KeygenError> {
+    match level.to_ascii_uppercase().as_str() {
+        "L" => Ok(EcLevel::L),
+        "M" => Ok(EcLevel::M),
+        "Q" => Ok(EcLevel::Q),
+        "H" => Ok(EcLevel::H),
+        _ => Err(KeygenError::InvalidArgument(format!(
+            "Unknown QR error correction level: {} (expected L, M, Q or H)",
+            level
+        ))),
+    }
+}
+
+/// Parse a `--timestamp` value (ISO 8601 / RFC 3339, e.g. `2024-01-01T00:00:00Z`)
+fn parse_timestamp(value: &str) -> Result<Timestamp, KeygenError> {
+    chrono::DateTime::parse_from_rfc3339(value).map_err(|e| {
+        KeygenError::InvalidArgument(format!(
+            "Invalid timestamp '{}': {} (expected e.g. 2024-01-01T00:00:00Z)",
+            value, e
+        ))
+    })
+}
+
+/// Parse an `--instructions-lang` code
+fn parse_instruction_language(code: &str) -> Result<InstructionLanguage, KeygenError> {
+    match code.to_ascii_lowercase().as_str() {
+        "en" => Ok(InstructionLanguage::English),
+        "es" => Ok(InstructionLanguage::Spanish),
+        "fr" => Ok(InstructionLanguage::French),
+        "de" => Ok(InstructionLanguage::German),
+        "ja" => Ok(InstructionLanguage::Japanese),
+        _ => Err(KeygenError::InvalidArgument(format!(
+            "Unknown instructions language: {} (expected en, es, fr, de or ja)",
+            code
+        ))),
+    }
+}
+
+/// Address count for `--preview-addresses` and `--label-addresses` (1-1000);
+/// `what` names the count in the range error
+fn parse_address_count(value: &str, what: &str) -> Result<usize, KeygenError> {
+    let count: usize = value
+        .parse()
+        .map_err(|_| KeygenError::InvalidArgument(format!("Invalid address count: {}", value)))?;
+    if !(1..=1000).contains(&count) {
+        return Err(KeygenError::InvalidArgument(format!(
+            "{} must be 1-1000, got {}",
+            what, count
+        )));
+    }
+    Ok(count)
+}
+
+/// Parse a `--script-type` name into its BIP purpose
+fn parse_script_type(name: &str) -> Result<u32, KeygenError> {
+    match name.to_ascii_lowercase().as_str() {
+        "p2wpkh" | "bip84" => Ok(84),
+        "p2sh-p2wpkh" | "bip49" => Ok(49),
+        "p2pkh" | "bip44" => Ok(44),
+        "p2tr" | "bip86" => Ok(86),
+        _ => Err(KeygenError::InvalidArgument(format!(
+            "Unknown script type: {} (expected p2wpkh, p2sh-p2wpkh, p2pkh or p2tr)",
+            name
+        ))),
+    }
+}
+
+/// Defaults read from `--config <file.toml>`. Keys mirror the flag names;
+/// flags given on the command line override them
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+struct Config {
+    network: Option<String>,
+    words: Option<usize>,
+    language: Option<String>,
+    output_dir: Option<PathBuf>,
+    script_type: Option<String>,
+    scripts: Option<Vec<String>>,
+}
+
+impl Config {
+    /// Read and parse a TOML config file
+    fn load(path: &Path) -> Result<Self, KeygenError> {
+        let contents = fs::read_to_string(path)?;
+        toml::from_str(&contents).map_err(|e| {
+            KeygenError::InvalidArgument(format!("Invalid config file {}: {}", path.display(), e))
+        })
+    }
+
+    /// Apply the configured values, validated like their flags
+    fn apply(&self, options: &mut Options) -> Result<(), KeygenError> {
+        if let Some(network) = &self.network {
+            options.network = parse_network(network)?;
+        }
+        if let Some(words) = self.words {
+            if entropy_bytes_for_word_count(words).is_none() {
+                return Err(KeygenError::InvalidWordCount(words));
+            }
+            options.word_count = words;
+        }
+        if let Some(language) = &self.language {
+            options.language = parse_language(language)?;
         }
+        if let Some(dir) = &self.output_dir {
+            options.output_dir = dir.clone();
+        }
+        if let Some(script_type) = &self.script_type {
+            options.script_type = parse_script_type(script_type)?;
+        }
+        if let Some(scripts) = &self.scripts {
+            options.scripts = parse_scripts(scripts)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a `--scripts` list such as `bip84,bip86` into BIP purposes, in
+/// canonical [`ALL_PURPOSES`] order
+fn parse_scripts<S: AsRef<str>>(names: &[S]) -> Result<Vec<u32>, KeygenError> {
+    let selected = names
+        .iter()
+        .map(|name| parse_script_type(name.as_ref().trim()))
+        .collect::<Result<Vec<_>, _>>()?;
+    if selected.is_empty() {
+        return Err(KeygenError::InvalidArgument(
+            "--scripts needs at least one script type".to_string(),
+        ));
+    }
+    Ok(ALL_PURPOSES
+        .into_iter()
+        .filter(|purpose| selected.contains(purpose))
+        .collect())
+}
+
+/// Command-line options
+#[derive(Clone)]
+struct Options {
+    /// Wallet label printed on the backup
+    label: String,
+    /// Number of mnemonic words to generate
+    word_count: usize,
+    /// BIP39 wordlist language
+    language: Language,
+    /// Network used for key serialization
+    network: Network,
+    /// SLIP39 split as (threshold, total), if requested
+    shamir: Option<(u8, u8)>,
+    /// Number of Seed XOR parts, if requested
+    seed_xor: Option<usize>,
+    /// Format of the main wallet output files
+    format: OutputFormat,
+    /// Prompt for a BIP39 passphrase (the "25th word")
+    passphrase: bool,
+    /// Read the BIP39 passphrase from this file instead of prompting
+    passphrase_file: Option<PathBuf>,
+    /// State on the printable that a (never written) passphrase is required
+    passphrase_required: bool,
+    /// Print fingerprint and first address for every passphrase in this file
+    passphrase_list: Option<PathBuf>,
+    /// Only show words on the console as first and last letter (`a*****n`)
+    redact: bool,
+    /// Print each word's BIP39 index next to it in the single-column list
+    word_indices: bool,
+    /// Add a NATO phonetic spelling of each word to the printable
+    phonetic: bool,
+    /// Write the BIP39 word indices to `indices.txt`
+    indices: bool,
+    /// Copy the mnemonic to the system clipboard, cleared after the timeout
+    clipboard: bool,
+    /// Seconds before `--clipboard` clears the clipboard again
+    clipboard_timeout: u64,
+    /// Source of the mnemonic entropy
+    entropy_source: EntropySource,
+    /// Write QR codes of the mnemonic
+    qr: bool,
+    /// Error-correction level for QR codes
+    qr_ec_level: EcLevel,
+    /// Write a compact numeric SeedQR of the mnemonic
+    seedqr: bool,
+    /// Write the watch-only descriptors as animated BBQr frames
+    bbqr: bool,
+    /// Print this many receive and change addresses as a table
+    preview_addresses: Option<usize>,
+    /// BIP purpose of the script type used by `--preview-addresses`
+    script_type: u32,
+    /// Write this many receive addresses with empty labels to a CSV
+    label_addresses: Option<usize>,
+    /// BIP purposes whose addresses, descriptors and account keys are output
+    scripts: Vec<u32>,
+    /// Quiz the user on random word positions after generation
+    verify_quiz: bool,
+    /// Language of the printable instructions (the words stay unchanged)
+    instructions_lang: InstructionLanguage,
+    /// Fixed generation time for reproducible output instead of the clock
+    timestamp: Option<Timestamp>,
+    /// User entropy XORed into the RNG output
+    mix_entropy: Option<Zeroizing<Vec<u8>>>,
+    /// Regenerate until the master fingerprint starts with this hex prefix
+    vanity_fingerprint: Option<String>,
+    /// Existing mnemonic to recover/verify instead of generating one
+    recover: Option<Zeroizing<String>>,
+    /// The mnemonic was read from stdin (`--recover-stdin`)
+    recover_stdin: bool,
+    /// BIP85 child mnemonic as (words, index), if requested
+    bip85: Option<(u32, u32)>,
+    /// BIP85 WIF private key (application 2) index to derive
+    bip85_wif: Option<u32>,
+    /// BIP85 hex entropy (application 128169) as (index, bytes)
+    bip85_hex: Option<(u32, usize)>,
+    /// Print everything to stdout instead of writing files
+    no_write: bool,
+    /// Skip the confirmation before writing seed material to disk
+    yes: bool,
+    /// Append a non-sensitive line per wallet to `audit.log`
+    audit_log: bool,
+    /// Fixed entropy for deterministic test vectors instead of the RNG
+    from_entropy: Option<Zeroizing<Vec<u8>>>,
+    /// Print (and write) the raw entropy hex after a confirmation prompt
+    show_entropy: bool,
+    /// Write the entropy as a grid of filled/empty cells
+    entropy_grid: bool,
+    /// Multisig cosigner entry as (threshold, total), if requested
+    multisig: Option<(usize, usize)>,
+    /// Extra derivation path to derive and print
+    derive: Option<DerivationPath>,
+    /// Number of independent wallets to generate in one batch
+    count: Option<usize>,
+    /// Generate an Electrum segwit seed instead of a BIP39 wallet
+    electrum: bool,
+    /// Encrypt every output file with a prompted password
+    encrypt: bool,
+    /// Decrypt this `.enc` file to stdout instead of generating
+    decrypt: Option<PathBuf>,
+    /// Verify the integrity manifest of this output directory instead of generating
+    verify_manifest: Option<PathBuf>,
+    /// Overwrite and delete this output directory instead of generating
+    wipe: Option<PathBuf>,
+    /// Also render the metal-plate layout to `seed_phrase.pdf`
+    pdf: bool,
+    /// Also write a fold-and-cut paper card to `seed_card.txt`
+    seed_card: bool,
+    /// Send the printable to the system printer (`lp`, falling back to `lpr`)
+    print: bool,
+    /// Printer name for `--print` instead of the system default
+    printer: Option<String>,
+    /// Refuse to generate if the machine can reach the internet
+    require_offline: bool,
+    /// Emit a machine-readable `KEY=VALUE` summary line on stderr
+    summary_stderr: bool,
+    /// Second BIP39 passphrase for a decoy wallet on the same mnemonic
+    duress_passphrase: Option<Zeroizing<String>>,
+    /// Directory the output files are written to
+    output_dir: PathBuf,
+    /// Message to sign with the first BIP84 key as proof of ownership
+    sign_message: Option<String>,
+    /// Write a Bitcoin Core `importdescriptors` request
+    core_import: bool,
+    /// Write receive and change descriptors to separate files
+    split_descriptors: bool,
+    /// Write a watch-only descriptor for only the first BIP84 address
+    first_address_watchonly: bool,
+    /// Write a Coldcard generic JSON export of the BIP44/49/84 accounts
+    coldcard_export: bool,
+    /// Write a Sparrow Wallet keystore JSON for the BIP84 account
+    sparrow_export: bool,
+    /// Write the BIP84 account as a `ur:crypto-hdkey` string for UR scanners
+    ur_hdkey: bool,
+    /// Write the 64-byte BIP39 seed to `seed.bin` after a confirmation prompt
+    raw_seed: bool,
+    /// Write the 64-byte BIP39 seed as hex to `seed.hex` after a confirmation prompt
+    raw_seed_hex: bool,
+    /// Descriptor whose fingerprint and first address the seed must reproduce
+    match_descriptor: Option<String>,
+    /// Account xpub (e.g. from a hardware wallet screen) the seed must derive
+    verify_xpub: Option<Xpub>,
+    /// Time the derivation steps on a throwaway wallet instead of generating
+    bench: bool,
+    /// Run the known-answer and RNG checks and print a pass/fail report
+    selftest: bool,
+    /// Generate this many throwaway wallets and report fingerprint collisions
+    fingerprint_stats: Option<usize>,
+    /// Cross-check the seed with an independent PBKDF2 computation
+    verify_seed: bool,
+    /// Plain-ASCII console and text files (no box drawing or emoji)
+    ascii: bool,
+    /// Print the LND-style lightning node key summary (m/1017'/coin'/6')
+    lightning_summary: bool,
+    /// Phrase missing its last word, to list the valid final words for
+    last_word: Option<Zeroizing<String>>,
+    /// With `--recover`: print only the master fingerprint, write nothing
+    fingerprint_only: bool,
+    /// With `--recover`: read derivation paths from stdin and print their keys
+    explore: bool,
+    /// Two phrases to compare word by word
+    compare: Option<(Zeroizing<String>, Zeroizing<String>)>,
+    /// Existing master xprv to derive from instead of a mnemonic
+    from_xprv: Option<Zeroizing<String>>,
+    /// Also write printable + words + xpub encrypted to this age recipient
+    age_recipient: Option<age::x25519::Recipient>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            label: "Bitcoin Wallet".to_string(),
+            word_count: DEFAULT_WORD_COUNT,
+            language: Language::English,
+            network: Network::Bitcoin,
+            shamir: None,
+            seed_xor: None,
+            format: OutputFormat::Text,
+            passphrase: false,
+            passphrase_file: None,
+            passphrase_required: false,
+            passphrase_list: None,
+            word_indices: false,
+            phonetic: false,
+            indices: false,
+            clipboard: false,
+            clipboard_timeout: CLIPBOARD_CLEAR_SECONDS,
+            redact: false,
+            entropy_source: EntropySource::Random,
+            qr: false,
+            qr_ec_level: EcLevel::M,
+            seedqr: false,
+            bbqr: false,
+            preview_addresses: None,
+            script_type: 84,
+            label_addresses: None,
+            scripts: ALL_PURPOSES.to_vec(),
+            verify_quiz: false,
+            instructions_lang: InstructionLanguage::English,
+            timestamp: None,
+            mix_entropy: None,
+            vanity_fingerprint: None,
+            recover: None,
+            recover_stdin: false,
+            bip85: None,
+            bip85_wif: None,
+            bip85_hex: None,
+            no_write: false,
+            yes: false,
+            audit_log: false,
+            summary_stderr: false,
+            require_offline: false,
+            pdf: false,
+            seed_card: false,
+            print: false,
+            printer: None,
+            from_entropy: None,
+            show_entropy: false,
+            entropy_grid: false,
+            multisig: None,
+            derive: None,
+            count: None,
+            electrum: false,
+            encrypt: false,
+            decrypt: None,
+            verify_manifest: None,
+            wipe: None,
+            duress_passphrase: None,
+            output_dir: PathBuf::from("output"),
+            sign_message: None,
+            core_import: false,
+            split_descriptors: false,
+            first_address_watchonly: false,
+            coldcard_export: false,
+            sparrow_export: false,
+            ur_hdkey: false,
+            raw_seed: false,
+            raw_seed_hex: false,
+            match_descriptor: None,
+            verify_xpub: None,
+            bench: false,
+            selftest: false,
+            fingerprint_stats: None,
+            verify_seed: false,
+            ascii: false,
+            lightning_summary: false,
+            last_word: None,
+            compare: None,
+            fingerprint_only: false,
+            explore: false,
+            from_xprv: None,
+            age_recipient: None,
+        }
+    }
+}
+
+/// Take the value following a flag, erroring if it is missing
+fn flag_value<I>(args: &mut I, flag: &str) -> Result<String, KeygenError>
+where
+    I: Iterator<Item = String>,
+{
+    args.next()
+        .ok_or_else(|| KeygenError::InvalidArgument(format!("Missing value for {}", flag)))
+}
+
+/// Parse command-line arguments (excluding the program name). A `--config`
+/// file is applied first, wherever it appears, so other flags override it
+///
+/// Usage: bitcoin-keygen [--config <file.toml>] [--output-dir <dir>]
+///                       [--words <12|15|18|21|24>] [--language <name>]
+///                       [--network <mainnet|testnet|testnet4|signet|regtest>]
+///                       [--shamir <threshold>-of-<total>] [--seed-xor <parts>]
+///                       [--format <text|json>] [--passphrase] [--dice | --coins]
+///                       [--qr] [--qr-ec <L|M|Q|H>] [--seedqr] [--bbqr] [--verify-quiz]
+///                       [--core-import] [--split-descriptors] [--first-address-watchonly]
+///                       [--coldcard-export] [--sparrow-export] [--ur-hdkey]
+///                       [--instructions-lang <en|es|fr|de|ja>]
+///                       [--timestamp <iso8601>] [--mix-entropy <hex>]
+///                       [--vanity-fingerprint <1-4 hex digits>]
+///                       [--preview-addresses <n>] [--script-type <type>] [--recover "<words>"]
+///                       [--label-addresses <n>]
+///                       [--scripts <type,type,..>]
+///                       [--recover-file <path> | --recover-stdin]
+///                       [--match-descriptor <descriptor>] [--verify-xpub <xpub>]
+///                       [--bip85 <words>:<index>] [--bip85-wif <index>]
+///                       [--bip85-hex <index>:<bytes>] [--no-write | --stdout-only] [--yes]
+///                       [--audit-log]
+///                       [--duress-passphrase <passphrase>]
+///                       [--passphrase-file <path>] [--passphrase-required]
+///                       [--passphrase-list <path>]
+///                       [--with-word-indices] [--phonetic] [--indices] [--redact]
+///                       [--clipboard] [--clipboard-timeout <seconds>]
+///                       [--summary-stderr]
+///                       [--from-entropy <hex>] [--show-entropy] [--entropy-grid]
+///                       [--encrypt]
+///                       [--age-recipient <age1...>]
+///                       [--raw-seed] [--raw-seed-hex] [--verify-seed]
+///                       [--derive <path>] [--lightning-summary] [--pdf] [--seed-card]
+///                       [--multisig <m>-of-<n>] [--print] [--printer <name>]
+///                       [--sign-message <text>] [--ascii] [--require-offline] [LABEL]
+///        bitcoin-keygen --count <1-99> [generation options] [LABEL]
+///        bitcoin-keygen --electrum [--no-write | --encrypt] [LABEL]
+///        bitcoin-keygen --fingerprint-only --recover "<words>" [--passphrase]
+///        bitcoin-keygen --explore --recover "<words>" [--passphrase] [--network <name>]
+///        bitcoin-keygen --from-xprv <xprv> [--network <name>] [--scripts <type,..>]
+///        bitcoin-keygen --decrypt <file.enc>
+///        bitcoin-keygen --verify-manifest <dir>
+///        bitcoin-keygen --wipe <dir>
+///        bitcoin-keygen --bench [--words <n>] [--network <name>]
+///        bitcoin-keygen --selftest
+///        bitcoin-keygen --fingerprint-stats <n> [--words <n>]
+///        bitcoin-keygen --last-word "<11-23 words>" [--language <name>]
+///        bitcoin-keygen --compare "<words>" "<words>" [--language <name>] [--passphrase]
+fn parse_args<I>(args: I) -> Result<Options, KeygenError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut options = Options::default();
+    let args: Vec<String> = args.into_iter().collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--config") {
+        let path = args.get(index + 1).ok_or_else(|| {
+            KeygenError::InvalidArgument("Missing value for --config".to_string())
+        })?;
+        Config::load(Path::new(path))?.apply(&mut options)?;
+    }
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--config" => {
+                // Already applied before the other flags
+                flag_value(&mut args, &arg)?;
+            }
+            "--output-dir" => {
+                options.output_dir = PathBuf::from(flag_value(&mut args, &arg)?);
+            }
+            "--words" => {
+                let value = flag_value(&mut args, &arg)?;
+                let word_count: usize = value.parse().map_err(|_| {
+                    KeygenError::InvalidArgument(format!("Invalid word count: {}", value))
+                })?;
+                if entropy_bytes_for_word_count(word_count).is_none() {
+                    return Err(KeygenError::InvalidWordCount(word_count));
+                }
+                options.word_count = word_count;
+            }
+            "--language" => {
+                options.language = parse_language(&flag_value(&mut args, &arg)?)?;
+            }
+            "--network" => {
+                options.network = parse_network(&flag_value(&mut args, &arg)?)?;
+            }
+            "--shamir" => {
+                options.shamir = Some(parse_shamir_spec(&flag_value(&mut args, &arg)?)?);
+            }
+            "--seed-xor" => {
+                let value = flag_value(&mut args, &arg)?;
+                let parts: usize = value.parse().map_err(|_| {
+                    KeygenError::InvalidArgument(format!("Invalid Seed XOR part count: {}", value))
+                })?;
+                if parts < 2 {
+                    return Err(KeygenError::InvalidArgument(
+                        "Seed XOR needs at least 2 parts".to_string(),
+                    ));
+                }
+                options.seed_xor = Some(parts);
+            }
+            "--format" => {
+                options.format = match flag_value(&mut args, &arg)?.as_str() {
+                    "text" => OutputFormat::Text,
+                    "json" => OutputFormat::Json,
+                    other => {
+                        return Err(KeygenError::InvalidArgument(format!(
+                            "Unknown format: {} (expected text or json)",
+                            other
+                        )))
+                    }
+                };
+            }
+            "--passphrase" => options.passphrase = true,
+            "--passphrase-required" => options.passphrase_required = true,
+            "--passphrase-list" => {
+                options.passphrase_list = Some(PathBuf::from(flag_value(&mut args, &arg)?));
+            }
+            "--with-word-indices" => options.word_indices = true,
+            "--phonetic" => options.phonetic = true,
+            "--indices" => options.indices = true,
+            "--clipboard" => options.clipboard = true,
+            "--clipboard-timeout" => {
+                let value = flag_value(&mut args, &arg)?;
+                let seconds: u64 = value.parse().map_err(|_| {
+                    KeygenError::InvalidArgument(format!("Invalid clipboard timeout: {}", value))
+                })?;
+                if !(1..=3600).contains(&seconds) {
+                    return Err(KeygenError::InvalidArgument(format!(
+                        "Clipboard timeout must be 1-3600 seconds, got {}",
+                        seconds
+                    )));
+                }
+                options.clipboard_timeout = seconds;
+            }
+            "--redact" => options.redact = true,
+            "--passphrase-file" => {
+                options.passphrase_file = Some(PathBuf::from(flag_value(&mut args, &arg)?));
+            }
+            "--dice" | "--coins" => {
+                if options.entropy_source != EntropySource::Random {
+                    return Err(KeygenError::InvalidArgument(
+                        "--dice and --coins are mutually exclusive".to_string(),
+                    ));
+                }
+                options.entropy_source = if arg == "--dice" {
+                    EntropySource::Dice
+                } else {
+                    EntropySource::Coins
+                };
+            }
+            "--recover" | "--recover-file" | "--recover-stdin" => {
+                if recovering(&options) {
+                    return Err(KeygenError::InvalidArgument(
+                        "--recover, --recover-file and --recover-stdin are mutually exclusive"
+                            .to_string(),
+                    ));
+                }
+                // The --recover-stdin phrase is read at run time (read_stdin_phrase)
+                match arg.as_str() {
+                    "--recover-stdin" => options.recover_stdin = true,
+                    "--recover-file" => {
+                        let path = flag_value(&mut args, &arg)?;
+                        options.recover = Some(phrase_from_word_list(&Zeroizing::new(
+                            fs::read_to_string(path)?,
+                        )));
+                    }
+                    _ => options.recover = Some(Zeroizing::new(flag_value(&mut args, &arg)?)),
+                }
+            }
+            "--match-descriptor" => {
+                let descriptor = flag_value(&mut args, &arg)?;
+                parse_descriptor(&descriptor)?;
+                options.match_descriptor = Some(descriptor);
+            }
+            "--verify-xpub" => {
+                options.verify_xpub = Some(parse_account_xpub(&flag_value(&mut args, &arg)?)?);
+            }
+            "--bip85" => {
+                options.bip85 = Some(parse_bip85_spec(&flag_value(&mut args, &arg)?)?);
+            }
+            "--bip85-wif" => {
+                options.bip85_wif = Some(parse_bip85_index(&flag_value(&mut args, &arg)?)?);
+            }
+            "--bip85-hex" => {
+                options.bip85_hex = Some(parse_bip85_hex_spec(&flag_value(&mut args, &arg)?)?);
+            }
+            "--no-write" | "--stdout-only" => options.no_write = true,
+            "--yes" => options.yes = true,
+            "--audit-log" => options.audit_log = true,
+            "--summary-stderr" => options.summary_stderr = true,
+            "--require-offline" => options.require_offline = true,
+            "--pdf" => options.pdf = true,
+            "--seed-card" => options.seed_card = true,
+            "--print" => options.print = true,
+            "--printer" => {
+                options.printer = Some(flag_value(&mut args, &arg)?);
+                options.print = true;
+            }
+            "--show-entropy" => options.show_entropy = true,
+            "--entropy-grid" => options.entropy_grid = true,
+            "--raw-seed" => options.raw_seed = true,
+            "--raw-seed-hex" => options.raw_seed_hex = true,
+            "--electrum" => options.electrum = true,
+            "--bench" => options.bench = true,
+            "--selftest" => options.selftest = true,
+            "--fingerprint-stats" => {
+                let value = flag_value(&mut args, &arg)?;
+                let count: usize = value.parse().map_err(|_| {
+                    KeygenError::InvalidArgument(format!("Invalid wallet count: {}", value))
+                })?;
+                if !(1..=1_000_000).contains(&count) {
+                    return Err(KeygenError::InvalidArgument(format!(
+                        "--fingerprint-stats count must be 1-1000000, got {}",
+                        count
+                    )));
+                }
+                options.fingerprint_stats = Some(count);
+            }
+            "--verify-seed" => options.verify_seed = true,
+            "--ascii" => options.ascii = true,
+            "--lightning-summary" => options.lightning_summary = true,
+            "--age-recipient" => {
+                options.age_recipient = Some(parse_age_recipient(&flag_value(&mut args, &arg)?)?);
+            }
+            "--fingerprint-only" => options.fingerprint_only = true,
+            "--explore" => options.explore = true,
+            "--from-xprv" => {
+                options.from_xprv = Some(Zeroizing::new(flag_value(&mut args, &arg)?));
+            }
+            "--compare" => {
+                let first = Zeroizing::new(flag_value(&mut args, &arg)?);
+                let second = Zeroizing::new(flag_value(&mut args, &arg)?);
+                options.compare = Some((first, second));
+            }
+            "--last-word" => {
+                options.last_word = Some(Zeroizing::new(flag_value(&mut args, &arg)?));
+            }
+            "--multisig" => {
+                options.multisig = Some(parse_multisig_spec(&flag_value(&mut args, &arg)?)?);
+            }
+            "--derive" => {
+                options.derive = Some(parse_derivation_path(&flag_value(&mut args, &arg)?)?)
+            }
+            "--count" => {
+                let value = flag_value(&mut args, &arg)?;
+                let count: usize = value.parse().map_err(|_| {
+                    KeygenError::InvalidArgument(format!("Invalid wallet count: {}", value))
+                })?;
+                if !(1..=99).contains(&count) {
+                    return Err(KeygenError::InvalidArgument(format!(
+                        "Wallet count must be 1-99, got {}",
+                        count
+                    )));
+                }
+                options.count = Some(count);
+            }
+            "--encrypt" => options.encrypt = true,
+            "--decrypt" => options.decrypt = Some(PathBuf::from(flag_value(&mut args, &arg)?)),
+            "--wipe" => options.wipe = Some(PathBuf::from(flag_value(&mut args, &arg)?)),
+            "--verify-manifest" => {
+                options.verify_manifest = Some(PathBuf::from(flag_value(&mut args, &arg)?));
+            }
+            "--from-entropy" => {
+                let hex = Zeroizing::new(flag_value(&mut args, &arg)?);
+                let entropy = parse_entropy_hex(&hex)?;
+                options.word_count = entropy.len() * 3 / 4;
+                options.from_entropy = Some(entropy);
+            }
+            "--duress-passphrase" => {
+                let duress = Zeroizing::new(flag_value(&mut args, &arg)?);
+                if duress.is_empty() {
+                    return Err(KeygenError::InvalidArgument(
+                        "Duress passphrase must not be empty".to_string(),
+                    ));
+                }
+                options.duress_passphrase = Some(duress);
+            }
+            "--qr" => options.qr = true,
+            "--seedqr" => options.seedqr = true,
+            "--bbqr" => options.bbqr = true,
+            "--core-import" => options.core_import = true,
+            "--split-descriptors" => options.split_descriptors = true,
+            "--first-address-watchonly" => options.first_address_watchonly = true,
+            "--coldcard-export" => options.coldcard_export = true,
+            "--sparrow-export" => options.sparrow_export = true,
+            "--ur-hdkey" => options.ur_hdkey = true,
+            "--verify-quiz" => options.verify_quiz = true,
+            "--sign-message" => options.sign_message = Some(flag_value(&mut args, &arg)?),
+            "--vanity-fingerprint" => {
+                options.vanity_fingerprint =
+                    Some(parse_vanity_prefix(&flag_value(&mut args, &arg)?)?);
+            }
+            "--mix-entropy" => {
+                options.mix_entropy = Some(parse_entropy_hex(&flag_value(&mut args, &arg)?)?);
+            }
+            "--timestamp" => {
+                options.timestamp = Some(parse_timestamp(&flag_value(&mut args, &arg)?)?);
+            }
+            "--instructions-lang" => {
+                options.instructions_lang =
+                    parse_instruction_language(&flag_value(&mut args, &arg)?)?;
+            }
+            "--preview-addresses" => {
+                options.preview_addresses = Some(parse_address_count(
+                    &flag_value(&mut args, &arg)?,
+                    "Address preview count",
+                )?);
+            }
+            "--label-addresses" => {
+                options.label_addresses = Some(parse_address_count(
+                    &flag_value(&mut args, &arg)?,
+                    "Address label count",
+                )?);
+            }
+            "--scripts" => {
+                let value = flag_value(&mut args, &arg)?;
+                let names: Vec<&str> = value.split(',').filter(|name| !name.is_empty()).collect();
+                options.scripts = parse_scripts(&names)?;
+            }
+            "--script-type" => {
+                options.script_type = parse_script_type(&flag_value(&mut args, &arg)?)?;
+            }
+            "--qr-ec" => {
+                options.qr_ec_level = parse_ec_level(&flag_value(&mut args, &arg)?)?;
+            }
+            flag if flag.starts_with("--") => {
+                return Err(KeygenError::InvalidArgument(format!(
+                    "Unknown option: {}",
+                    flag
+                )));
+            }
+            _ => options.label = arg,
+        }
+    }
+
+    // A recovered phrase sets the word count (like --from-entropy), so checks such
+    // as --seedqr's see the real one. Bad counts are reported by recover_mnemonic
+    if let Some(count) = options.recover.as_deref().and_then(|p| infer_word_count(p)) {
+        options.word_count = count;
+    }
+
+    // A passphrase-required backup needs a passphrase: prompt unless a file is given
+    if options.passphrase_required && options.passphrase_file.is_none() {
+        options.passphrase = true;
+    }
+
+    if options.count.is_some()
+        && (recovering(&options)
+            || options.from_entropy.is_some()
+            || options.entropy_source != EntropySource::Random
+            || options.passphrase
+            || options.passphrase_list.is_some()
+            || options.show_entropy
+            || options.raw_seed
+            || options.raw_seed_hex
+            || options.encrypt
+            || options.electrum
+            || options.verify_quiz)
+    {
+        return Err(KeygenError::InvalidArgument(
+            "--count only supports non-interactive RNG generation (no --recover, \
+             --from-entropy, --dice, --coins, --passphrase, --passphrase-list, --show-entropy, \
+             --raw-seed, --encrypt, --electrum or --verify-quiz)"
+                .to_string(),
+        ));
+    }
+    if options.encrypt && options.no_write {
+        return Err(KeygenError::InvalidArgument(
+            "--encrypt cannot be combined with --no-write".to_string(),
+        ));
+    }
+    if options.redact && (options.no_write || options.show_entropy) {
+        return Err(KeygenError::InvalidArgument(
+            "--redact cannot be combined with --no-write or --show-entropy, which print \
+             the full secret"
+                .to_string(),
+        ));
+    }
+    if options.print && (options.format == OutputFormat::Json || options.count.is_some()) {
+        return Err(KeygenError::InvalidArgument(
+            "--print needs the text printable (no --format json or --count)".to_string(),
+        ));
+    }
+    if options.clipboard && (options.count.is_some() || options.redact) {
+        return Err(KeygenError::InvalidArgument(
+            "--clipboard cannot be combined with --count or --redact".to_string(),
+        ));
+    }
+    if options.age_recipient.is_some() && (options.no_write || options.encrypt) {
+        return Err(KeygenError::InvalidArgument(
+            "--age-recipient cannot be combined with --no-write or --encrypt".to_string(),
+        ));
     }
+    let bip84_exports: Vec<&str> = [
+        (options.first_address_watchonly, "--first-address-watchonly"),
+        (options.coldcard_export, "--coldcard-export"),
+        (options.sparrow_export, "--sparrow-export"),
+        (options.ur_hdkey, "--ur-hdkey"),
+    ]
+    .into_iter()
+    .filter_map(|(set, flag)| set.then_some(flag))
+    .collect();
+    if !bip84_exports.is_empty() && !options.scripts.contains(&84) {
+        return Err(KeygenError::InvalidArgument(format!(
+            "{} export the BIP84 account: add bip84 to --scripts",
+            bip84_exports.join(", ")
+        )));
+    }
+    if options.vanity_fingerprint.is_some()
+        && (recovering(&options)
+            || options.from_entropy.is_some()
+            || options.entropy_source != EntropySource::Random
+            || options.mix_entropy.is_some()
+            || options.passphrase
+            || options.passphrase_file.is_some()
+            || options.count.is_some()
+            || options.electrum)
+    {
+        return Err(KeygenError::InvalidArgument(
+            "--vanity-fingerprint only searches RNG mnemonics without a passphrase (no \
+             --recover, --from-entropy, --dice, --coins, --mix-entropy, --passphrase, \
+             --passphrase-file, --count or --electrum)"
+                .to_string(),
+        ));
+    }
+    if let Some(supplied) = &options.mix_entropy {
+        if recovering(&options)
+            || options.from_entropy.is_some()
+            || options.entropy_source != EntropySource::Random
+        {
+            return Err(KeygenError::InvalidArgument(
+                "--mix-entropy only mixes into RNG entropy (no --recover, --from-entropy, \
+                 --dice or --coins)"
+                    .to_string(),
+            ));
+        }
+        if entropy_bytes_for_word_count(options.word_count) != Some(supplied.len()) {
+            return Err(KeygenError::InvalidArgument(format!(
+                "--mix-entropy must be {} hex digits for {} words, got {}",
+                entropy_bytes_for_word_count(options.word_count).unwrap_or_default() * 2,
+                options.word_count,
+                supplied.len() * 2
+            )));
+        }
+    }
+    check_seedqr_word_count(&options)?;
+    if options.passphrase && options.passphrase_file.is_some() {
+        return Err(KeygenError::InvalidArgument(
+            "--passphrase and --passphrase-file are mutually exclusive".to_string(),
+        ));
+    }
+    if options.fingerprint_only && !recovering(&options) {
+        return Err(KeygenError::InvalidArgument(
+            "--fingerprint-only requires --recover or --recover-file".to_string(),
+        ));
+    }
+    if options.explore && !recovering(&options) {
+        return Err(KeygenError::InvalidArgument(
+            "--explore requires --recover or --recover-file".to_string(),
+        ));
+    }
+    if let Some(xprv) = &options.from_xprv {
+        if recovering(&options)
+            || options.from_entropy.is_some()
+            || options.entropy_source != EntropySource::Random
+            || options.passphrase
+            || options.passphrase_file.is_some()
+            || options.count.is_some()
+            || options.electrum
+        {
+            return Err(KeygenError::InvalidArgument(
+                "--from-xprv replaces the mnemonic (no --recover, --from-entropy, --dice, \
+                 --coins, --passphrase, --passphrase-file, --count or --electrum)"
+                    .to_string(),
+            ));
+        }
+        let mut key = parse_master_xprv(xprv, options.network)?;
+        key.private_key.non_secure_erase();
+    }
+    if recovering(&options) && options.entropy_source != EntropySource::Random {
+        return Err(KeygenError::InvalidArgument(
+            "--recover cannot be combined with an entropy input mode".to_string(),
+        ));
+    }
+    if options.from_entropy.is_some()
+        && (recovering(&options) || options.entropy_source != EntropySource::Random)
+    {
+        return Err(KeygenError::InvalidArgument(
+            "--from-entropy cannot be combined with --recover, --dice or --coins".to_string(),
+        ));
+    }
+
+    Ok(options)
+}
+
+/// Well-known public endpoints probed by `--require-offline` (Cloudflare and
+/// Google DNS). Raw IPs, so the check needs no DNS resolution
+const CONNECTIVITY_PROBES: [&str; 2] = ["1.1.1.1:443", "8.8.8.8:53"];
+
+/// Whether any probe endpoint accepts a TCP connection within a short timeout
+fn network_reachable() -> bool {
+    let timeout = std::time::Duration::from_millis(1500);
+    CONNECTIVITY_PROBES.iter().any(|probe| {
+        probe
+            .parse::<std::net::SocketAddr>()
+            .map(|addr| std::net::TcpStream::connect_timeout(&addr, timeout).is_ok())
+            .unwrap_or(false)
+    })
+}
+
+/// Refuse to continue when `is_online` reports a working network connection.
+/// The probe is injected so the check can be tested without touching the network
+fn ensure_offline<F: Fn() -> bool>(is_online: F) -> Result<(), KeygenError> {
+    if is_online() {
+        return Err(KeygenError::Online);
+    }
+    Ok(())
+}
+
+/// Print spooler commands tried in order by `--print`: CUPS `lp`, then BSD `lpr`
+fn printer_commands(printer: Option<&str>) -> Vec<std::process::Command> {
+    let mut lp = std::process::Command::new("lp");
+    let mut lpr = std::process::Command::new("lpr");
+    if let Some(name) = printer {
+        lp.args(["-d", name]);
+        lpr.args(["-P", name]);
+    }
+    vec![lp, lpr]
+}
 
-    // Ensure last line ends properly
-    if !words.len().is_multiple_of(4) {
-        output.push('\n');
+/// Run `command` with `contents` on its stdin and wait for it to succeed
+fn pipe_to_command(command: &mut std::process::Command, contents: &[u8]) -> std::io::Result<()> {
+    let mut child = command
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .spawn()?;
+    // Dropping stdin after writing closes it, so the command sees end-of-input
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(contents)?;
     }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!(
+            "{} exited with {}",
+            command.get_program().to_string_lossy(),
+            status
+        )));
+    }
+    Ok(())
+}
+
+/// Pipe `contents` to the first of `commands` that is installed and return its
+/// name. The commands are injected so tests can stand in for the spooler
+fn send_to_printer(
+    contents: &[u8],
+    commands: Vec<std::process::Command>,
+) -> Result<String, KeygenError> {
+    for mut command in commands {
+        match pipe_to_command(&mut command, contents) {
+            Ok(()) => return Ok(command.get_program().to_string_lossy().into_owned()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(KeygenError::Io(std::io::Error::other(
+        "no print command found (install CUPS for lp, or lpr)",
+    )))
+}
+
+/// Destination for generated files. In dry-run mode nothing touches disk and
+/// text content is printed to stdout instead (e.g. to pipe straight to a printer).
+/// With a password every file is encrypted and written as `<name>.enc`.
+/// In ASCII mode `.txt` files get plain-ASCII decorations.
+/// The SHA-256 of every file written is recorded for `manifest.sha256`
+struct OutputWriter<'a> {
+    dir: &'a Path,
+    dry_run: bool,
+    password: Option<&'a str>,
+    ascii: bool,
+    written: RefCell<Vec<(String, sha256::Hash)>>,
+}
+
+impl<'a> OutputWriter<'a> {
+    fn new(dir: &'a Path, dry_run: bool, password: Option<&'a str>) -> Self {
+        OutputWriter {
+            dir,
+            dry_run,
+            password,
+            ascii: false,
+            written: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Write `.txt` files with [`ascii_decorations`] applied (`--ascii`)
+    fn with_ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    fn prepare(&self) -> Result<(), KeygenError> {
+        if !self.dry_run {
+            fs::create_dir_all(self.dir)?;
+        }
+        Ok(())
+    }
+
+    fn write(&self, name: &str, description: &str, contents: &[u8]) -> Result<(), KeygenError> {
+        let converted;
+        let contents = match std::str::from_utf8(contents) {
+            Ok(text) if self.ascii && name.ends_with(".txt") => {
+                converted = Zeroizing::new(ascii_decorations(text));
+                converted.as_bytes()
+            }
+            _ => contents,
+        };
+
+        if self.dry_run {
+            match std::str::from_utf8(contents) {
+                Ok(text) => {
+                    cprintln!("─── {} ───", name);
+                    cprintln!("{}", text);
+                }
+                Err(_) => cprintln!("(skipped binary file {} in --no-write mode)", name),
+            }
+            return Ok(());
+        }
+
+        if let Some(password) = self.password {
+            let file_name = format!("{}.enc", name);
+            let encrypted = encrypt_payload(contents, password)?;
+            let path = self.dir.join(&file_name);
+            fs::write(&path, &encrypted)?;
+            self.record(file_name, &encrypted);
+            cprintln!("✓ Created encrypted {}: {}", description, path.display());
+            return Ok(());
+        }
+
+        let path = self.dir.join(name);
+        fs::write(&path, contents)?;
+        self.record(name.to_string(), contents);
+        cprintln!("✓ Created {}: {}", description, path.display());
+        Ok(())
+    }
+
+    fn record(&self, name: String, contents: &[u8]) {
+        self.written
+            .borrow_mut()
+            .push((name, sha256::Hash::hash(contents)));
+    }
+
+    /// Write `manifest.sha256` (`sha256sum` format) covering every file written
+    fn write_manifest(&self) -> Result<(), KeygenError> {
+        if self.dry_run {
+            return Ok(());
+        }
+
+        let manifest: String = self
+            .written
+            .borrow()
+            .iter()
+            .map(|(name, hash)| format!("{}  {}\n", hash, name))
+            .collect();
+        let path = self.dir.join(MANIFEST_FILE);
+        fs::write(&path, manifest)?;
+        cprintln!("✓ Created integrity manifest: {}", path.display());
+        Ok(())
+    }
+}
+
+/// Default seconds before `--clipboard` clears the clipboard
+const CLIPBOARD_CLEAR_SECONDS: u64 = 30;
+
+/// Clipboard used by `--clipboard`, behind a trait so tests can substitute one
+trait Clipboard {
+    fn set_text(&mut self, text: &str) -> Result<(), KeygenError>;
+    fn clear(&mut self) -> Result<(), KeygenError>;
+}
+
+impl Clipboard for arboard::Clipboard {
+    fn set_text(&mut self, text: &str) -> Result<(), KeygenError> {
+        arboard::Clipboard::set_text(self, text).map_err(clipboard_error)
+    }
+
+    fn clear(&mut self) -> Result<(), KeygenError> {
+        arboard::Clipboard::clear(self).map_err(clipboard_error)
+    }
+}
+
+fn clipboard_error(e: arboard::Error) -> KeygenError {
+    KeygenError::Io(std::io::Error::other(format!("clipboard: {}", e)))
+}
+
+/// Put `text` on the clipboard, wait `timeout` with `sleep`, then clear it.
+/// The process must stay alive meanwhile: on X11 the owning process serves
+/// the clipboard contents, so they would disappear as soon as it exited
+fn copy_with_auto_clear<C, S>(
+    clipboard: &mut C,
+    text: &str,
+    timeout: Duration,
+    sleep: S,
+) -> Result<(), KeygenError>
+where
+    C: Clipboard,
+    S: FnOnce(Duration),
+{
+    clipboard.set_text(text)?;
+    sleep(timeout);
+    clipboard.clear()
+}
+
+/// Overwrite every file under `dir` with random bytes, flush it to disk, unlink
+/// it, then remove the directory tree. Symlinks are removed, never followed.
+/// (Journaling and SSD wear levelling can still keep old copies - this reduces,
+/// not eliminates, recovery risk.) Returns the number of files wiped
+fn wipe_directory(dir: &Path) -> Result<usize, KeygenError> {
+    let metadata = fs::symlink_metadata(dir)?;
+    if !metadata.is_dir() {
+        return Err(KeygenError::InvalidArgument(format!(
+            "Not a directory: {}",
+            dir.display()
+        )));
+    }
+
+    let mut wiped = 0;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let file_type = fs::symlink_metadata(&path)?.file_type();
+        if file_type.is_dir() {
+            wiped += wipe_directory(&path)?;
+        } else if file_type.is_file() {
+            let len = fs::metadata(&path)?.len() as usize;
+            let mut file = fs::OpenOptions::new().write(true).open(&path)?;
+            let mut noise = Zeroizing::new(vec![0u8; 64 * 1024]);
+            let mut remaining = len;
+            while remaining > 0 {
+                let chunk = remaining.min(noise.len());
+                getrandom::fill(&mut noise[..chunk])?;
+                file.write_all(&noise[..chunk])?;
+                remaining -= chunk;
+            }
+            file.sync_all()?;
+            drop(file);
+            fs::remove_file(&path)?;
+            wiped += 1;
+        } else {
+            fs::remove_file(&path)?;
+        }
+    }
+
+    fs::remove_dir(dir)?;
+    Ok(wiped)
+}
+
+/// Append `lines` to `<dir>/audit.log`, creating it on first use. Never
+/// truncated, encrypted or listed in the manifest: it outlives each generation
+fn append_audit_log(dir: &Path, lines: &str) -> Result<(), KeygenError> {
+    let path = dir.join(AUDIT_LOG_FILE);
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    file.write_all(lines.as_bytes())?;
+    cprintln!("✓ Appended to audit log: {}", path.display());
+    Ok(())
+}
+
+/// Integrity manifest file name inside the output directory
+const MANIFEST_FILE: &str = "manifest.sha256";
+
+/// Recompute the hashes listed in `<dir>/manifest.sha256`, returning the names of
+/// files that are missing or no longer match
+fn verify_manifest(dir: &Path) -> Result<Vec<String>, KeygenError> {
+    let manifest = fs::read_to_string(dir.join(MANIFEST_FILE))?;
+    let mut mismatches = Vec::new();
+
+    for line in manifest.lines().filter(|line| !line.trim().is_empty()) {
+        let (expected, name) = line
+            .split_once("  ")
+            .ok_or_else(|| KeygenError::Integrity(format!("Malformed manifest line: {}", line)))?;
+        let matches = match fs::read(dir.join(name)) {
+            Ok(contents) => sha256::Hash::hash(&contents).to_string() == expected,
+            Err(_) => false,
+        };
+        if !matches {
+            mismatches.push(name.to_string());
+        }
+    }
+    Ok(mismatches)
+}
+
+/// `--verify-manifest` mode: report every file that fails verification
+fn run_verify_manifest(dir: &Path) -> Result<(), KeygenError> {
+    let mismatches = verify_manifest(dir)?;
+    if mismatches.is_empty() {
+        cprintln!("✓ All files in {} match {}", dir.display(), MANIFEST_FILE);
+        return Ok(());
+    }
+    for name in &mismatches {
+        cprintln!("✗ MISMATCH: {}", name);
+    }
+    Err(KeygenError::Integrity(format!(
+        "{} file(s) failed integrity verification",
+        mismatches.len()
+    )))
+}
+
+fn main() {
+    if let Err(e) = run_cli() {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Parse the command line and run the selected mode; errors are reported by
+/// `main` via `Display`
+fn run_cli() -> Result<(), KeygenError> {
+    let options = parse_args(std::env::args().skip(1))?;
+    dispatch(&options, network_reachable)
+}
+
+/// Dispatch to the mode selected in `options`. `is_online` is the
+/// `--require-offline` probe, checked before any mode touches seed material
+fn dispatch<F: Fn() -> bool>(options: &Options, is_online: F) -> Result<(), KeygenError> {
+    ASCII_CONSOLE.store(options.ascii, Ordering::Relaxed);
+    if options.selftest {
+        return run_selftest_report(&run_selftest());
+    }
+    check_english_wordlist()?;
+    if options.require_offline {
+        ensure_offline(is_online)?;
+    }
+    // Fail before any work rather than have the prompts read a drained stdin
+    let prompts = prompting_flags(options);
+    if !prompts.is_empty() && stdin_feeds_input(options) && open_tty().is_err() {
+        return Err(no_terminal(&prompts.join(", ")));
+    }
+    if let Some(path) = &options.decrypt {
+        return decrypt_file(path);
+    }
+    if let Some(dir) = &options.verify_manifest {
+        return run_verify_manifest(dir);
+    }
+    if let Some(dir) = &options.wipe {
+        let wiped = wipe_directory(dir)?;
+        cprintln!(
+            "✓ Overwrote and deleted {} files, removed {}",
+            wiped,
+            dir.display()
+        );
+        return Ok(());
+    }
+    if options.bench {
+        return run_bench(options);
+    }
+    if let Some(count) = options.fingerprint_stats {
+        run_fingerprint_stats(options, count)?;
+        return Ok(());
+    }
+    if let Some(partial) = &options.last_word {
+        return run_last_word(partial, options.language);
+    }
+    let stdin_options;
+    let options = if options.recover_stdin && options.recover.is_none() {
+        stdin_options = read_stdin_phrase(options, &mut std::io::stdin().lock())?;
+        &stdin_options
+    } else {
+        options
+    };
+    if options.fingerprint_only {
+        cprintln!("{}", recovered_fingerprint(options)?);
+        return Ok(());
+    }
+    if let Some((first, second)) = &options.compare {
+        return run_compare(options, first, second);
+    }
+    if options.explore {
+        return run_explore(options);
+    }
+    confirm_before_writing(options, &mut std::io::stdin().lock(), open_tty)?;
+    if options.electrum {
+        return run_electrum(options, &options.output_dir);
+    }
+    if let Some(xprv) = &options.from_xprv {
+        run_from_xprv(options, xprv, &options.output_dir)?;
+        return Ok(());
+    }
+    if let Some(count) = options.count {
+        run_batch(options, &options.output_dir, count)?;
+        return Ok(());
+    }
+    run(options, &options.output_dir)?;
+    Ok(())
+}
+
+/// Run the full generation pipeline, writing files into `output_dir`.
+/// Returns the public identifiers of the generated wallet
+fn run(options: &Options, output_dir: &Path) -> Result<WalletKeys, KeygenError> {
+    cprintln!("═══════════════════════════════════════════════════════════════");
+    cprintln!("        Bitcoin Key Generator - Air-Gapped Edition");
+    cprintln!("═══════════════════════════════════════════════════════════════");
+    cprintln!();
+    if options.recover.is_some() {
+        cprintln!("Verifying existing BIP39 mnemonic seed phrase...");
+    } else {
+        cprintln!("Generating secure BIP39 mnemonic seed phrase...");
+    }
+    cprintln!();
+
+    // Generate mnemonic
+    let mnemonic = if let Some(phrase) = &options.recover {
+        let mnemonic = recover_mnemonic(phrase, options.language)?;
+        cprintln!(
+            "✓ Recovered mnemonic: detected {} words (checksum valid)",
+            mnemonic.word_count()
+        );
+        mnemonic
+    } else if let Some(entropy) = &options.from_entropy {
+        cprintln!("✓ Using fixed entropy from --from-entropy (deterministic, NOT for real funds)");
+        mnemonic_from_entropy(entropy.clone(), options.language)?
+    } else {
+        match options.entropy_source {
+            EntropySource::Random => match &options.mix_entropy {
+                Some(supplied) => {
+                    cprintln!("✓ Mixing --mix-entropy into the system RNG output (XOR)");
+                    generate_mixed_mnemonic(options.word_count, options.language, supplied)?
+                }
+                None => match &options.vanity_fingerprint {
+                    Some(prefix) => {
+                        let (mnemonic, attempts) = find_vanity_mnemonic(
+                            prefix,
+                            options.word_count,
+                            options.language,
+                            options.network,
+                            VANITY_MAX_ATTEMPTS,
+                        )?;
+                        cprintln!(
+                            "✓ Found a fingerprint starting with '{}' after {} attempts",
+                            prefix,
+                            attempts
+                        );
+                        mnemonic
+                    }
+                    None => generate_mnemonic(options.word_count, options.language)?,
+                },
+            },
+            EntropySource::Dice => {
+                cprintln!(
+                    "Enter exactly {} dice rolls (1-6), then press Ctrl-D:",
+                    dice_rolls_required(options.word_count)
+                );
+                let mut rolls = Zeroizing::new(String::new());
+                std::io::stdin().read_to_string(&mut rolls)?;
+                let entropy = dice_entropy(&rolls, options.word_count)?;
+                cprintln!("✓ Derived entropy from dice rolls");
+                mnemonic_from_entropy(entropy, options.language)?
+            }
+            EntropySource::Coins => {
+                cprintln!(
+                    "Enter exactly {} coin flips (0/1), then press Ctrl-D:",
+                    entropy_bits_for_word_count(options.word_count)
+                );
+                let mut flips = Zeroizing::new(String::new());
+                std::io::stdin().read_to_string(&mut flips)?;
+                let entropy = coin_entropy(&flips, options.word_count)?;
+                cprintln!("✓ Packed entropy from coin flips");
+                mnemonic_from_entropy(entropy, options.language)?
+            }
+        }
+    };
+    cprintln!(
+        "✓ {}-word BIP39 mnemonic ({})",
+        mnemonic.word_count(),
+        language_display_name(options.language)
+    );
+
+    // Surface the checksum so the last word can be double-checked by hand
+    let (checksum_len, checksum) = mnemonic_checksum_bits(&Zeroizing::new(mnemonic.to_entropy()));
+    let last_word = mnemonic.words().last().unwrap_or_default();
+    cprintln!(
+        "✓ Checksum: {} bits = {:0width$b} (last word: {})",
+        checksum_len,
+        checksum,
+        if options.redact {
+            redact_word(last_word)
+        } else {
+            last_word.to_string()
+        },
+        width = checksum_len as usize
+    );
+
+    // Raw entropy hex for cross-checking, only after explicit confirmation
+    let shown_entropy = if options.show_entropy {
+        if confirm_prompt(
+            "The raw entropy is as sensitive as the seed words themselves.",
+            &mut prompt_input(options, "--show-entropy")?,
+        )? {
+            let hex = entropy_hex(&mnemonic);
+            cprintln!("✓ Entropy: {}", hex.as_str());
+            Some(hex)
+        } else {
+            cprintln!("✗ Not showing entropy (confirmation declined)");
+            None
+        }
+    } else {
+        None
+    };
+
+    // Optional BIP39 passphrase, read without echo so it never hits shell history
+    let passphrase = bip39_passphrase(options)?;
+    if !passphrase.is_empty() {
+        warn_weak_passphrase(&passphrase);
+        warn_passphrase_normalization(&passphrase);
+    }
+
+    // Generate seed and master key
+    let seed = generate_seed(&mnemonic, &passphrase);
+    if options.verify_seed {
+        if !verify_seed_derivation(&mnemonic, &passphrase) {
+            return Err(KeygenError::Integrity(
+                "Independent PBKDF2 seed derivation does not match the BIP39 seed".to_string(),
+            ));
+        }
+        cprintln!(
+            "✓ Seed cross-checked with independent PBKDF2-HMAC-SHA512 ({} rounds)",
+            BIP39_PBKDF2_ROUNDS
+        );
+    }
+    let master_key = derive_master_key(&seed, options.network)?;
+    cprintln!(
+        "✓ Derived master private key ({})",
+        network_display_name(options.network)
+    );
+
+    // Copy of the raw seed for --raw-seed / --raw-seed-hex, only after explicit confirmation
+    let raw_seed = if options.raw_seed || options.raw_seed_hex {
+        if confirm_prompt(
+            "The raw seed can spend all funds (it already includes any passphrase).",
+            &mut prompt_input(options, "--raw-seed")?,
+        )? {
+            Some(seed.clone())
+        } else {
+            cprintln!("✗ Not exporting the raw seed (confirmation declined)");
+            None
+        }
+    } else {
+        None
+    };
+
+    // Get fingerprint
+    let fingerprint = get_hardware_wallet_fingerprint(&master_key);
+    cprintln!("✓ Calculated fingerprint: {}", fingerprint);
+    if options.redact {
+        cprintln!();
+        cprint!("{}", create_redacted_preview(&mnemonic, &fingerprint));
+        cprintln!();
+    }
+
+    // Derive the first receive addresses for each script type
+    let addresses = derive_addresses_for(
+        &master_key,
+        options.network,
+        &options.scripts,
+        ADDRESS_PREVIEW_COUNT,
+    )?;
+    cprintln!(
+        "✓ Derived first {} receive addresses per script type",
+        ADDRESS_PREVIEW_COUNT
+    );
+
+    // Gap-limit preview of receive and change addresses for the chosen script type
+    if let Some(count) = options.preview_addresses {
+        let purpose = options.script_type;
+        let receive = derive_chain_addresses(&master_key, options.network, purpose, 0, count)?;
+        let change = derive_chain_addresses(&master_key, options.network, purpose, 1, count)?;
+        cprintln!();
+        cprint!("{}", create_address_preview(purpose, &receive, &change));
+        cprintln!();
+    }
+
+    // Derive the BIP84 account xpub for watch-only wallets; its files and
+    // console lines only appear when --scripts selects bip84
+    let bip84 = options.scripts.contains(&84);
+    let account_path = account_derivation_path(options.network, 84, 0)?;
+    let account_xpub = derive_account_xpub(&master_key, options.network, 84, 0)?;
+    let account_fp = account_fingerprint(&master_key, &account_path)?;
+    cprintln!("✓ Derived account xpub at m/{}", account_path);
+
+    // Output descriptors for Bitcoin Core / Sparrow watch-only import
+    let descriptors = build_descriptors_for(&master_key, options.network, &options.scripts)?;
+    cprintln!("✓ Built {} output descriptors", descriptors.len());
+
+    // Account-level keys for every standard script type
+    let mut accounts = account_summary(&master_key, options.network);
+    accounts.retain(|account| options.scripts.contains(&account.purpose));
+    cprintln!("✓ Derived {} account-level key pairs", accounts.len());
+
+    // Multisig cosigner key and template descriptor
+    let multisig = match options.multisig {
+        Some((threshold, total)) => {
+            let (key, descriptor) =
+                build_multisig_cosigner(&master_key, options.network, threshold, total)?;
+            cprintln!("✓ Built {}-of-{} multisig cosigner entry", threshold, total);
+            Some((threshold, total, key, descriptor))
+        }
+        None => None,
+    };
+
+    // Custom --derive path
+    let custom = match &options.derive {
+        Some(path) => Some(derive_custom_path(&master_key, options.network, path)?),
+        None => None,
+    };
+
+    // --match-descriptor: does this seed reproduce the descriptor's keys?
+    if let Some(descriptor) = &options.match_descriptor {
+        let result = match_descriptor(&master_key, options.network, descriptor)?;
+        let mark = |matches: bool| if matches { "✓" } else { "✗" };
+        cprintln!(
+            "{} Descriptor fingerprint {} (seed: {})",
+            mark(result.fingerprint_matches()),
+            result.descriptor_fingerprint,
+            result.seed_fingerprint
+        );
+        cprintln!(
+            "{} Descriptor first address {} (seed: {})",
+            mark(result.address_matches()),
+            result.descriptor_address,
+            result.seed_address
+        );
+        if !(result.fingerprint_matches() && result.address_matches()) {
+            return Err(KeygenError::Integrity(
+                "Seed does not match the descriptor (check the words, passphrase and network)"
+                    .to_string(),
+            ));
+        }
+    }
+
+    // --verify-xpub: which standard account, if any, produces this xpub?
+    if let Some(xpub) = &options.verify_xpub {
+        match find_xpub_purpose(&master_key, options.network, xpub)? {
+            Some(purpose) => cprintln!(
+                "✓ xpub matches this seed at m/{}",
+                account_derivation_path(options.network, purpose, 0)?
+            ),
+            None => {
+                return Err(KeygenError::Integrity(format!(
+                    "xpub does not match this seed at m/44', m/49', m/84' or m/86' \
+                     (coin {}', account 0'): check the words, passphrase and network",
+                    coin_type(options.network)
+                )))
+            }
+        }
+    }
+
+    // Lightning node key summary (LND-style BIP32 path, not aezeed)
+    let lightning = if options.lightning_summary {
+        Some(lightning_node_key(&master_key, options.network)?)
+    } else {
+        None
+    };
+
+    // --sign-message proof of ownership with the first BIP84 key
+    let signed_message = match &options.sign_message {
+        Some(message) => {
+            let (address, signature) = sign_message(&master_key, options.network, message)?;
+            cprintln!("✓ Signed message with {}", address);
+            Some((message, address, signature))
+        }
+        None => None,
+    };
+
+    // JSON identifiers of the primary wallet and optional duress decoy wallet
+    // (same mnemonic, different BIP39 passphrase)
+    let primary_keys = wallet_keys(&master_key, options.network)?;
+    let duress_keys = match &options.duress_passphrase {
+        Some(duress_passphrase) => {
+            if duress_passphrase.as_str() == passphrase.as_str() {
+                return Err(KeygenError::Passphrase(
+                    "Duress passphrase must differ from the primary passphrase".to_string(),
+                ));
+            }
+            warn_passphrase_normalization(duress_passphrase);
+            let duress_seed = generate_seed(&mnemonic, duress_passphrase);
+            let mut duress_key = derive_master_key(&duress_seed, options.network)?;
+            let keys = wallet_keys(&duress_key, options.network)?;
+            duress_key.private_key.non_secure_erase();
+            cprintln!("✓ Derived duress wallet (fingerprint {})", keys.fingerprint);
+            Some(keys)
+        }
+        None => None,
+    };
+
+    // Fingerprint and first address for each listed passphrase (never written)
+    let passphrase_table = match &options.passphrase_list {
+        Some(path) => {
+            let passphrases = read_passphrase_list(path)?;
+            for listed in &passphrases {
+                warn_passphrase_normalization(listed);
+            }
+            let rows = passphrase_matrix(&mnemonic, options.network, &passphrases)?;
+            cprintln!(
+                "✓ Derived {} passphrase wallets from {}",
+                rows.len(),
+                path.display()
+            );
+            Some(rows)
+        }
+        None => None,
+    };
+
+    // BIP85 deterministic child seed (needs the master key, so derive it now)
+    let bip85_child = match options.bip85 {
+        Some((words, index)) => {
+            let child = derive_bip85_mnemonic(&master_key, words, index)?;
+            let child_key = derive_master_key(&generate_seed(&child, ""), options.network)?;
+            cprintln!(
+                "✓ Derived BIP85 child seed ({} words, index {}, fingerprint {})",
+                words,
+                index,
+                get_hardware_wallet_fingerprint(&child_key)
+            );
+            Some((words, index, child))
+        }
+        None => None,
+    };
+    let bip85_wif = match options.bip85_wif {
+        Some(index) => Some((
+            index,
+            derive_bip85_wif(&master_key, options.network, index)?,
+        )),
+        None => None,
+    };
+    let bip85_hex = match options.bip85_hex {
+        Some((index, bytes)) => Some((index, bytes, derive_bip85_hex(&master_key, bytes, index)?)),
+        None => None,
+    };
+
+    // Paper wallet summary of the BIP84 account (needs the master key, so build it now)
+    let paper_wallet = if bip84 {
+        Some(create_paper_wallet(
+            &mnemonic,
+            &master_key,
+            options.network,
+        )?)
+    } else {
+        None
+    };
+    let core_import = if options.core_import {
+        Some(create_core_import_json(&master_key, options.network)?)
+    } else {
+        None
+    };
+    let address_labels = match options.label_addresses {
+        Some(count) => Some(derive_chain_addresses(
+            &master_key,
+            options.network,
+            options.script_type,
+            0,
+            count,
+        )?),
+        None => None,
+    };
+    let split_descriptors = if options.split_descriptors {
+        let chain =
+            |chain| build_chain_descriptors(&master_key, options.network, &options.scripts, chain);
+        Some((chain(0)?, chain(1)?))
+    } else {
+        None
+    };
+    let first_address_watchonly = if options.first_address_watchonly {
+        let descriptor = build_first_address_descriptor(&master_key, options.network)?;
+        let (_, address) = derive_addresses(&master_key, options.network, 1)?.remove(0);
+        Some(create_first_address_watchonly_output(&descriptor, &address))
+    } else {
+        None
+    };
+    let coldcard_export = if options.coldcard_export {
+        Some(create_coldcard_export(&master_key, options.network)?)
+    } else {
+        None
+    };
+    let sparrow_export = if options.sparrow_export {
+        Some(create_sparrow_export(
+            &master_key,
+            options.network,
+            &options.label,
+        )?)
+    } else {
+        None
+    };
+    let ur_hdkey = if options.ur_hdkey {
+        Some(create_ur_hdkey(&master_key, options.network)?)
+    } else {
+        None
+    };
+
+    // The master private key is no longer needed. `Xpriv` is `Copy` and has no
+    // `Drop`, so overwrite it in place rather than letting it linger until exit
+    // (the mnemonic and seed zeroize themselves on drop)
+    let mut master_key = master_key;
+    master_key.private_key.non_secure_erase();
+    drop(seed);
+
+    let label = &options.label;
+
+    // Create output directory
+    let encryption_password = if options.encrypt {
+        Some(prompt_encryption_password(true)?)
+    } else {
+        None
+    };
+    let output = OutputWriter::new(
+        output_dir,
+        options.no_write,
+        encryption_password.as_deref().map(String::as_str),
+    )
+    .with_ascii(options.ascii);
+    output.prepare()?;
+    let generated = options.timestamp.unwrap_or_else(now_timestamp);
+    let printable_options = PrintableOptions {
+        language: options.instructions_lang,
+        passphrase_required: options.passphrase_required,
+        word_indices: options.word_indices,
+        phonetic: options.phonetic,
+    };
+
+    match options.format {
+        OutputFormat::Text => {
+            // Create printable file
+            let printable_content = create_localized_printable_output(
+                &mnemonic,
+                &fingerprint,
+                label,
+                options.network,
+                &addresses,
+                &printable_options,
+                &generated,
+            );
+            if options.passphrase_required {
+                cprintln!(
+                    "✓ Printable states a passphrase is required (the passphrase is not written)"
+                );
+            }
+            output.write(
+                "seed_phrase_printable.txt",
+                "printable file",
+                printable_content.as_bytes(),
+            )?;
+            if options.print {
+                let printed = if options.ascii {
+                    Zeroizing::new(ascii_decorations(&printable_content))
+                } else {
+                    Zeroizing::new(printable_content.clone())
+                };
+                let command = send_to_printer(
+                    printed.as_bytes(),
+                    printer_commands(options.printer.as_deref()),
+                )?;
+                cprintln!("✓ Sent printable to the printer ({})", command);
+            }
+
+            // Optional PDF of the metal-plate layout for printing
+            if options.pdf {
+                output.write(
+                    "seed_phrase.pdf",
+                    "printable PDF",
+                    &create_printable_pdf(
+                        &mnemonic,
+                        &fingerprint,
+                        label,
+                        options.network,
+                        &generated,
+                        options.passphrase_required,
+                    )?,
+                )?;
+            }
+
+            // Optional fold-and-cut paper card
+            if options.seed_card {
+                output.write(
+                    "seed_card.txt",
+                    "fold-and-cut seed card",
+                    create_seed_card(
+                        &mnemonic,
+                        &fingerprint,
+                        label,
+                        options.network,
+                        &generated,
+                        options.passphrase_required,
+                    )
+                    .as_bytes(),
+                )?;
+            }
+
+            // Create simple word list
+            let word_list = create_simple_word_list(&mnemonic);
+            output.write(
+                "seed_words_simple.txt",
+                "simple word list",
+                word_list.as_bytes(),
+            )?;
+
+            // Create seed words for hardware wallet import (just the words, one per line)
+            output.write(
+                "seed_words_for_coldcard.txt",
+                "Coldcard import file",
+                create_coldcard_word_list(&mnemonic).as_bytes(),
+            )?;
+
+            // Compact one-page summary for a sealed envelope backup
+            if let Some(paper_wallet) = &paper_wallet {
+                output.write("paper_wallet.txt", "paper wallet", paper_wallet.as_bytes())?;
+            }
+
+            // Create account xpub file for watch-only wallet setup
+            let xpub_content = bip84.then(|| {
+                create_account_xpub_output(&account_xpub, &account_path, &fingerprint, 84)
+            });
+            if let Some(xpub_content) = &xpub_content {
+                output.write(
+                    "account_xpub.txt",
+                    "account xpub file",
+                    xpub_content.as_bytes(),
+                )?;
+            }
+
+            // Encrypted copy of the essentials that can leave the air-gapped machine
+            if let Some(recipient) = &options.age_recipient {
+                let combined = Zeroizing::new(format!(
+                    "{}\n{}\n{}",
+                    printable_content,
+                    word_list,
+                    xpub_content.as_deref().unwrap_or_default()
+                ));
+                output.write(
+                    "wallet.age",
+                    "age-encrypted backup",
+                    &encrypt_to_age(combined.as_bytes(), recipient)?,
+                )?;
+            }
+
+            // Create account summary file (xprv + xpub per script type)
+            output.write(
+                "accounts.txt",
+                "account summary file",
+                create_accounts_output(&accounts, &fingerprint).as_bytes(),
+            )?;
+
+            // Create descriptors file for watch-only wallet import
+            output.write(
+                "descriptors.txt",
+                "descriptors file",
+                create_descriptors_output(&descriptors).as_bytes(),
+            )?;
+        }
+        OutputFormat::Json => {
+            // Structured export for tooling integration
+            let json = create_wallet_json(
+                &mnemonic,
+                label,
+                options.network,
+                &primary_keys,
+                duress_keys.as_ref(),
+                &generated,
+            )?;
+            output.write("wallet.json", "JSON wallet file", json.as_bytes())?;
+            if let Some(recipient) = &options.age_recipient {
+                output.write(
+                    "wallet.age",
+                    "age-encrypted backup",
+                    &encrypt_to_age(json.as_bytes(), recipient)?,
+                )?;
+            }
+        }
+    }
+
+    // BIP85 child seed word list
+    if let Some((words, index, child)) = &bip85_child {
+        output.write(
+            &format!("bip85_{}w_index_{}.txt", words, index),
+            "BIP85 child seed file",
+            create_simple_word_list(child).as_bytes(),
+        )?;
+    }
+    if let Some((index, wif)) = &bip85_wif {
+        output.write(
+            &format!("bip85_wif_index_{}.txt", index),
+            "BIP85 WIF key file",
+            format!("{}\n", wif.as_str()).as_bytes(),
+        )?;
+    }
+    if let Some((index, bytes, hex)) = &bip85_hex {
+        output.write(
+            &format!("bip85_hex_{}b_index_{}.txt", bytes, index),
+            "BIP85 hex entropy file",
+            format!("{}\n", hex.as_str()).as_bytes(),
+        )?;
+    }
+
+    // Multisig cosigner file
+    if let Some((threshold, total, key, descriptor)) = &multisig {
+        output.write(
+            "multisig_cosigner.txt",
+            "multisig cosigner file",
+            create_multisig_output(key, descriptor, *threshold, *total).as_bytes(),
+        )?;
+    }
+
+    // Raw entropy file (already printed to the console in --no-write mode)
+    if let Some(hex) = &shown_entropy {
+        if !options.no_write {
+            output.write(
+                "entropy.hex",
+                "entropy file",
+                format!("{}\n", hex.as_str()).as_bytes(),
+            )?;
+        }
+    }
+
+    if let Some(seed) = &raw_seed {
+        write_raw_seed(&output, seed, options.raw_seed, options.raw_seed_hex)?;
+    }
+
+    // QR codes of the space-joined mnemonic for quick offline import
+    if options.qr {
+        let phrase = Zeroizing::new(mnemonic.to_string());
+
+        output.write(
+            "seed_qr.txt",
+            "ASCII QR code",
+            render_qr_ascii(&phrase, options.qr_ec_level)?.as_bytes(),
+        )?;
+        output.write(
+            "seed_qr.png",
+            "QR code image",
+            &render_qr_png(&phrase, options.qr_ec_level)?,
+        )?;
+    }
+
+    // Compact numeric SeedQR (4-digit word indices) for SeedSigner/Coldcard
+    if options.seedqr {
+        output.write("seedqr.png", "SeedQR image", &render_seedqr_png(&mnemonic)?)?;
+    }
+
+    if options.indices {
+        output.write(
+            "indices.txt",
+            "word index list",
+            create_index_list(&mnemonic).as_bytes(),
+        )?;
+    }
+
+    if options.entropy_grid {
+        output.write(
+            "entropy_grid.txt",
+            "entropy grid",
+            create_entropy_grid_output(&mnemonic).as_bytes(),
+        )?;
+    }
+
+    if let Some(addresses) = &address_labels {
+        output.write(
+            "address_labels.csv",
+            "address label CSV",
+            create_address_labels_csv(addresses).as_bytes(),
+        )?;
+    }
+
+    if let Some((receive, change)) = &split_descriptors {
+        output.write(
+            "descriptor_receive.txt",
+            "receive descriptors file",
+            create_chain_descriptors_output(receive, 0).as_bytes(),
+        )?;
+        output.write(
+            "descriptor_change.txt",
+            "change descriptors file",
+            create_chain_descriptors_output(change, 1).as_bytes(),
+        )?;
+    }
+
+    if let Some(core_import) = &core_import {
+        output.write(
+            "core_import.json",
+            "Bitcoin Core importdescriptors request",
+            core_import.as_bytes(),
+        )?;
+    }
+
+    if let Some(coldcard_export) = &coldcard_export {
+        output.write(
+            "coldcard-export.json",
+            "Coldcard generic JSON export",
+            coldcard_export.as_bytes(),
+        )?;
+    }
+
+    if let Some(sparrow_export) = &sparrow_export {
+        output.write(
+            "sparrow.json",
+            "Sparrow Wallet keystore export",
+            sparrow_export.as_bytes(),
+        )?;
+    }
+
+    if let Some(first_address_watchonly) = &first_address_watchonly {
+        output.write(
+            "first_address_watchonly.txt",
+            "first-address watch-only descriptor",
+            first_address_watchonly.as_bytes(),
+        )?;
+    }
+
+    if let Some(ur_hdkey) = &ur_hdkey {
+        output.write(
+            "ur_hdkey.txt",
+            "UR crypto-hdkey",
+            format!("{}\n", ur_hdkey).as_bytes(),
+        )?;
+    }
+
+    // Descriptors are too long for one QR code: export them as BBQr frames to
+    // be shown in sequence (animated) to a scanning wallet
+    if options.bbqr {
+        let payload = create_descriptors_output(&descriptors);
+        let frames = bbqr_frames(payload.as_bytes(), BBQR_FRAME_CHARS)?;
+        for (i, frame) in frames.iter().enumerate() {
+            output.write(
+                &format!("bbqr_frame_{:02}.png", i + 1),
+                &format!("BBQr frame {}/{}", i + 1, frames.len()),
+                &render_qr_png(frame, options.qr_ec_level)?,
+            )?;
+        }
+    }
+
+    // Split the entropy into SLIP39 shares, one metal plate per share
+    if let Some((threshold, total)) = options.shamir {
+        let entropy = Zeroizing::new(mnemonic.to_entropy());
+        let shares = shamir_split(&entropy, threshold, total)?;
+        for (i, share) in shares.iter().enumerate() {
+            output.write(
+                &format!("shamir_share_{:02}.txt", i + 1),
+                "Shamir share file",
+                create_shamir_share_output(
+                    share,
+                    ShamirShareNumber {
+                        number: i + 1,
+                        threshold,
+                        total,
+                    },
+                    &fingerprint,
+                    label,
+                    options.network,
+                    &printable_options,
+                    &generated,
+                )
+                .as_bytes(),
+            )?;
+        }
+    }
+
+    // Split into Seed XOR parts, each a full metal-plate printable of its own
+    if let Some(parts) = options.seed_xor {
+        let entropy = Zeroizing::new(mnemonic.to_entropy());
+        let xor_parts = seed_xor_split(&entropy, parts)?;
+        for (i, part) in xor_parts.iter().enumerate() {
+            let part_seed = generate_seed(part, "");
+            let part_key = derive_master_key(&part_seed, options.network)?;
+            let part_fingerprint = get_hardware_wallet_fingerprint(&part_key);
+            let part_label = format!("{} - Seed XOR part {} of {}", label, i + 1, parts);
+            let part_printable = create_localized_printable_output(
+                part,
+                &part_fingerprint,
+                &part_label,
+                options.network,
+                &[],
+                &PrintableOptions {
+                    language: options.instructions_lang,
+                    word_indices: options.word_indices,
+                    phonetic: options.phonetic,
+                    ..PrintableOptions::default()
+                },
+                &generated,
+            );
+
+            output.write(
+                &format!("seed_xor_part_{}.txt", i + 1),
+                "Seed XOR part file",
+                part_printable.as_bytes(),
+            )?;
+
+            // QR of the part's words for scanning straight into a Coldcard,
+            // captioned so the printed codes can be told apart
+            let part_phrase = Zeroizing::new(part.to_string());
+            output.write(
+                &format!("seed_xor_part_{}.png", i + 1),
+                &format!(
+                    "Seed XOR part {} of {} QR code (fingerprint {})",
+                    i + 1,
+                    parts,
+                    part_fingerprint
+                ),
+                &render_captioned_qr_png(
+                    &part_phrase,
+                    options.qr_ec_level,
+                    &seed_xor_caption(i + 1, parts, &part_fingerprint),
+                )?,
+            )?;
+        }
+    }
+
+    output.write_manifest()?;
+
+    if options.audit_log && !options.no_write {
+        append_audit_log(
+            output_dir,
+            &format_audit_log_line(
+                &now_timestamp(),
+                label,
+                options.network,
+                mnemonic.word_count(),
+                &fingerprint,
+            ),
+        )?;
+    }
+
+    cprintln!();
+    cprintln!("═══════════════════════════════════════════════════════════════");
+    cprintln!("                    GENERATION COMPLETE");
+    cprintln!("═══════════════════════════════════════════════════════════════");
+    cprintln!();
+    if options.no_write {
+        cprintln!("Dry run: no files were written (--no-write)");
+    } else {
+        cprintln!("Files created in: {}", output_dir.display());
+    }
+    cprintln!();
+    cprintln!("IMPORTANT SECURITY NOTES:");
+    cprintln!("─────────────────────────────────────────────────────────────");
+    cprintln!("1. Print the 'seed_phrase_printable.txt' file for metal plate");
+    cprintln!("2. Verify all words are correct before punching");
+    cprintln!("3. Store metal plate in secure, fireproof location");
+    cprintln!("4. Create backup copy in separate location");
+    cprintln!("5. Delete all files from this computer after printing");
+    cprintln!("   (bitcoin-keygen --wipe {})", output_dir.display());
+    cprintln!("6. Never store seed phrases on internet-connected devices");
+    cprintln!("7. Test import on hardware wallet with small amount first");
+    cprintln!("─────────────────────────────────────────────────────────────");
+    cprintln!();
+    cprintln!("Fingerprint: {}", fingerprint);
+    cprintln!("(Verify this matches your hardware wallet after import)");
+    cprintln!();
+    if bip84 {
+        cprintln!("Account fingerprint (m/{}): {}", account_path, account_fp);
+        cprintln!(
+            "Account parent fingerprint: {}",
+            account_xpub.parent_fingerprint
+        );
+        cprintln!("(Some wallets show these account-level values instead)");
+        cprintln!();
+        cprintln!("Account xpub (m/{}):", account_path);
+        cprintln!("  {}", account_xpub);
+        cprintln!("  {}", slip132_encode(&account_xpub, 84));
+        cprintln!();
+    }
+    cprintln!("Accounts:");
+    for account in &accounts {
+        cprintln!(
+            "  BIP{} {:14} {}",
+            account.purpose,
+            account.path,
+            account.xpub
+        );
+    }
+    cprintln!();
+    cprintln!("Receive addresses:");
+    for (path, address) in &addresses {
+        cprintln!("  {:20} {}", path, address);
+    }
+    cprintln!();
+    if let Some(duress) = &duress_keys {
+        cprintln!("Duress (decoy) wallet - fund it with a small amount:");
+        cprintln!("  Fingerprint:   {}", duress.fingerprint);
+        cprintln!("  First address: {}", duress.first_address);
+        cprintln!();
+    }
+
+    if let Some(custom) = &custom {
+        cprintln!("Custom path (m/{}):", custom.path);
+        cprintln!("  xprv: {}", custom.xprv);
+        cprintln!("  xpub: {}", custom.xpub);
+        if let Some(address) = &custom.address {
+            cprintln!("  address: {}", address);
+        }
+        cprintln!();
+    }
+
+    if let Some(rows) = &passphrase_table {
+        cprintln!("Passphrase wallets (line number in the list file):");
+        cprint!("{}", format_passphrase_matrix(rows));
+        cprintln!();
+    }
+
+    if let Some(lightning) = &lightning {
+        cprintln!("Lightning node key (m/{}):", lightning.path);
+        cprintln!("  Fingerprint:  {}", lightning.fingerprint);
+        cprintln!("  Node pubkey:  {}", lightning.node_pubkey);
+        cprintln!("  ⚠ This is NOT an LND aezeed. LND's own seeds cannot be restored from");
+        cprintln!("    BIP39 words; use this only with setups that import a BIP32 root key.");
+        cprintln!();
+    }
+
+    if let Some((message, address, signature)) = &signed_message {
+        cprintln!(
+            "Signed message (BIP137, m/84'/{}'/0'/0/0):",
+            coin_type(options.network)
+        );
+        cprintln!("  Message:   {}", message);
+        cprintln!("  Address:   {}", address);
+        cprintln!("  Signature: {}", signature);
+        cprintln!();
+    }
+
+    // Backup quiz: confirm the words were recorded correctly before relying on them
+    if options.verify_quiz {
+        cprint!("Write down / punch your backup, then press Enter to start the quiz...");
+        std::io::stdout().flush()?;
+        let mut input = prompt_input(options, "--verify-quiz")?;
+        input.read_line(&mut String::new())?;
+
+        let words: Vec<&str> = mnemonic.words().collect();
+        let positions = quiz_positions(words.len(), QUIZ_QUESTIONS)?;
+        let correct = verify_quiz(&words, &positions, &mut input)?;
+        if correct < positions.len() {
+            return Err(KeygenError::QuizFailed {
+                wrong: positions.len() - correct,
+                total: positions.len(),
+            });
+        }
+        cprintln!(
+            "✓ Backup verified: {}/{} words correct",
+            correct,
+            positions.len()
+        );
+        cprintln!();
+    }
+
+    // Last, so the words are on the clipboard only while nothing else happens
+    if options.clipboard {
+        cprintln!("⚠ WARNING: --clipboard puts your seed words on the system clipboard.");
+        cprintln!("  Any program on this machine (and clipboard history or sync tools) can");
+        cprintln!("  read them, which defeats the purpose of an air-gapped setup.");
+        cprintln!(
+            "✓ Mnemonic copied to the clipboard, clearing in {} seconds (keep this running)...",
+            options.clipboard_timeout
+        );
+        let mut clipboard = arboard::Clipboard::new().map_err(clipboard_error)?;
+        let phrase = Zeroizing::new(mnemonic.to_string());
+        copy_with_auto_clear(
+            &mut clipboard,
+            &phrase,
+            Duration::from_secs(options.clipboard_timeout),
+            std::thread::sleep,
+        )?;
+        cprintln!("✓ Clipboard cleared");
+        cprintln!();
+    }
+
+    // Machine-readable summary on stderr so `2>summary` captures just this line
+    if options.summary_stderr {
+        eprintln!(
+            "{}",
+            create_summary_line(&primary_keys, options.network, mnemonic.word_count())
+        );
+    }
+
+    Ok(primary_keys)
+}
+
+/// `--count` mode: generate independent wallets in parallel, one per
+/// `wallet_NN/` subdirectory. `getrandom` is safe to call from any thread, so
+/// every wallet draws its own entropy straight from the OS RNG
+fn run_batch(
+    options: &Options,
+    output_dir: &Path,
+    count: usize,
+) -> Result<Vec<WalletKeys>, KeygenError> {
+    let wallets = (1..=count)
+        .into_par_iter()
+        .map(|i| {
+            let wallet_options = Options {
+                label: format!("{} #{}", options.label, i),
+                count: None,
+                audit_log: false,
+                ..options.clone()
+            };
+            run(
+                &wallet_options,
+                &output_dir.join(format!("wallet_{:02}", i)),
+            )
+            .map_err(|e| KeygenError::Batch {
+                index: i,
+                source: Box::new(e),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // One line per wallet in the batch directory, in wallet order
+    if options.audit_log && !options.no_write {
+        let timestamp = now_timestamp();
+        let lines: String = wallets
+            .iter()
+            .enumerate()
+            .map(|(i, wallet)| {
+                format_audit_log_line(
+                    &timestamp,
+                    &format!("{} #{}", options.label, i + 1),
+                    options.network,
+                    options.word_count,
+                    &wallet.fingerprint,
+                )
+            })
+            .collect();
+        append_audit_log(output_dir, &lines)?;
+    }
+
+    cprintln!();
+    cprintln!("Batch complete: {} wallets", wallets.len());
+    for (i, wallet) in wallets.iter().enumerate() {
+        cprintln!(
+            "  wallet_{:02}  {}  {}",
+            i + 1,
+            wallet.fingerprint,
+            wallet.first_address
+        );
+    }
+    Ok(wallets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::bip32::{Xpriv, Xpub};
+    use bitcoin::secp256k1::Secp256k1;
+    use std::fs;
+    use tempfile::TempDir;
+    use zeroize::Zeroize;
+
+    #[test]
+    fn test_generate_mnemonic() {
+        for (word_count, entropy_len) in [(12, 16), (15, 20), (18, 24), (21, 28), (24, 32)] {
+            let mnemonic = generate_mnemonic(word_count, Language::English).unwrap();
+            let words: Vec<&str> = mnemonic.words().collect();
+            assert_eq!(
+                words.len(),
+                word_count,
+                "Mnemonic should have {} words",
+                word_count
+            );
+            assert_eq!(
+                mnemonic.to_entropy().len(),
+                entropy_len,
+                "{} words should encode {} bytes of entropy",
+                word_count,
+                entropy_len
+            );
+
+            // Verify all words are from BIP39 wordlist
+            for word in words {
+                assert!(!word.is_empty(), "Word should not be empty");
+                assert!(
+                    word.chars().all(|c| c.is_alphabetic()),
+                    "Word should contain only letters"
+                );
+            }
+        }
+
+        // Unsupported word counts are rejected
+        assert!(generate_mnemonic(13, Language::English).is_err());
+        assert!(generate_mnemonic(0, Language::English).is_err());
+    }
+
+    #[test]
+    fn test_coin_entropy() {
+        // 256 flips of the repeating pattern 00001111 = 0x0f * 32
+        let flips = "00001111".repeat(32);
+        let entropy = coin_entropy(&flips, 24).unwrap();
+        assert_eq!(entropy.as_slice(), &[0x0f; 32]);
+        let mnemonic = mnemonic_from_entropy(entropy, Language::English).unwrap();
+        assert_eq!(
+            mnemonic.to_string(),
+            "audit journey sense bulk valley maple destroy tiger audit journey sense bulk \
+             valley maple destroy tiger audit journey sense bulk valley maple destroy vibrant"
+        );
+
+        // Whitespace is ignored, 12 words need exactly 128 flips
+        let spaced = "1111 1111\n".repeat(16);
+        let entropy = coin_entropy(&spaced, 12).unwrap();
+        let mnemonic = mnemonic_from_entropy(entropy, Language::English).unwrap();
+        assert_eq!(
+            mnemonic.to_string(),
+            "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo wrong"
+        );
+
+        assert!(coin_entropy(&flips[..255], 24).is_err());
+        assert!(coin_entropy(&format!("{}0", flips), 24).is_err());
+        assert!(coin_entropy(&format!("{}2", &flips[..255]), 24).is_err());
+    }
+
+    #[test]
+    fn test_parse_entropy_hex() {
+        let entropy = parse_entropy_hex(&"00".repeat(32)).unwrap();
+        let mnemonic = mnemonic_from_entropy(entropy, Language::English).unwrap();
+        assert_eq!(
+            mnemonic.to_string(),
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+             abandon abandon abandon art"
+        );
+
+        // Mixed case and every supported length
+        assert_eq!(
+            parse_entropy_hex("FFff".repeat(8).as_str()).unwrap().len(),
+            16
+        );
+        for len in [16, 20, 24, 28, 32] {
+            assert!(parse_entropy_hex(&"7f".repeat(len)).is_ok());
+        }
+
+        assert!(parse_entropy_hex(&"00".repeat(17)).is_err());
+        assert!(parse_entropy_hex(&"0".repeat(33)).is_err());
+        assert!(parse_entropy_hex(&"zz".repeat(16)).is_err());
+    }
+
+    #[test]
+    fn test_entropy_hex() {
+        let hex = "0c1e24e5917779d297e14d45f14e1a1a0c1e24e5917779d297e14d45f14e1a1a";
+        let options = parse_args(vec![
+            "--from-entropy".to_string(),
+            hex.to_uppercase(),
+            "--show-entropy".to_string(),
+        ])
+        .unwrap();
+        let mnemonic =
+            mnemonic_from_entropy(options.from_entropy.unwrap(), Language::English).unwrap();
+        assert_eq!(entropy_hex(&mnemonic).as_str(), hex);
+
+        let mut yes = std::io::Cursor::new("YES\n");
+        assert!(confirm_prompt("Show?", &mut yes).unwrap());
+        let mut no = std::io::Cursor::new("yes please\n");
+        assert!(!confirm_prompt("Show?", &mut no).unwrap());
+        let mut eof = std::io::Cursor::new("");
+        assert!(!confirm_prompt("Show?", &mut eof).unwrap());
+    }
+
+    #[test]
+    fn test_confirm_write() {
+        let dir = Path::new("output");
+        let mut yes = "y\n".as_bytes();
+        assert!(confirm_write(dir, &mut yes).unwrap());
+        let mut upper = "Y\n".as_bytes();
+        assert!(confirm_write(dir, &mut upper).unwrap());
+        let mut no = "n\n".as_bytes();
+        assert!(!confirm_write(dir, &mut no).unwrap());
+        let mut yes_word = "yes\n".as_bytes();
+        assert!(!confirm_write(dir, &mut yes_word).unwrap());
+        let mut empty = "\n".as_bytes();
+        assert!(!confirm_write(dir, &mut empty).unwrap());
+        let mut eof = "".as_bytes();
+        assert!(!confirm_write(dir, &mut eof).unwrap());
+
+        assert!(parse_args(["--yes".to_string()]).unwrap().yes);
+        assert!(!parse_args(Vec::<String>::new()).unwrap().yes);
+    }
+
+    #[test]
+    fn test_confirm_before_writing_piped_dice() {
+        let no_tty = || Err::<&[u8], _>(std::io::Error::from(std::io::ErrorKind::NotFound));
+        let rolls = "123456".repeat(17)[..99].to_string();
+        let dice = Options {
+            entropy_source: EntropySource::Dice,
+            ..Options::default()
+        };
+
+        // The rolls on stdin are never taken as the answer
+        let mut stdin = std::io::Cursor::new(rolls.as_bytes());
+        let error = confirm_before_writing(&dice, &mut stdin, no_tty).unwrap_err();
+        assert!(error.to_string().contains("--yes"));
+        assert_eq!(stdin.position(), 0);
+
+        // The terminal answers instead
+        let mut stdin = std::io::Cursor::new(rolls.as_bytes());
+        assert!(confirm_before_writing(&dice, &mut stdin, || Ok("y\n".as_bytes())).is_ok());
+        assert_eq!(stdin.position(), 0);
+        let error = confirm_before_writing(&dice, &mut stdin, || Ok("n\n".as_bytes())).unwrap_err();
+        assert!(error.to_string().contains("Aborted"));
+
+        let yes = Options {
+            yes: true,
+            ..dice.clone()
+        };
+        assert!(confirm_before_writing(&yes, &mut stdin, no_tty).is_ok());
+        assert_eq!(stdin.position(), 0);
+
+        // Without piped input the answer still comes from stdin
+        let mut stdin = "y\n".as_bytes();
+        assert!(confirm_before_writing(&Options::default(), &mut stdin, no_tty).is_ok());
+    }
+
+    #[test]
+    fn test_prompts_with_piped_input() {
+        let read = |mut reader: Box<dyn std::io::BufRead + '_>| {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            line
+        };
+
+        // Quiz answers and confirmations come from the terminal while stdin
+        // carries the mnemonic, and from stdin otherwise
+        let piped = Options {
+            recover_stdin: true,
+            verify_quiz: true,
+            show_entropy: true,
+            ..Options::default()
+        };
+        let reader = prompt_reader(&piped, "abandon about\n".as_bytes(), || {
+            Ok("YES\n".as_bytes())
+        });
+        assert_eq!(read(reader.unwrap()), "YES\n");
+        let reader = prompt_reader(&Options::default(), "YES\n".as_bytes(), || {
+            Err::<&[u8], _>(std::io::Error::from(std::io::ErrorKind::NotFound))
+        });
+        assert_eq!(read(reader.unwrap()), "YES\n");
+        let no_tty = prompt_reader(&piped, "".as_bytes(), || {
+            Err::<&[u8], _>(std::io::Error::from(std::io::ErrorKind::NotFound))
+        });
+        assert!(no_tty.is_err());
+
+        assert_eq!(prompting_flags(&piped), ["--show-entropy", "--verify-quiz"]);
+        assert!(prompting_flags(&Options::default()).is_empty());
+        assert!(no_terminal("--verify-quiz")
+            .to_string()
+            .contains("--verify-quiz needs a terminal"));
+    }
+
+    #[test]
+    fn test_mnemonic_from_entropy_zeroizes() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        /// Entropy buffer that counts how often it is wiped
+        struct CountingBuffer {
+            bytes: Vec<u8>,
+            wipes: Rc<Cell<usize>>,
+        }
+
+        impl Zeroize for CountingBuffer {
+            fn zeroize(&mut self) {
+                self.bytes.zeroize();
+                self.wipes.set(self.wipes.get() + 1);
+            }
+        }
+
+        impl AsRef<[u8]> for CountingBuffer {
+            fn as_ref(&self) -> &[u8] {
+                &self.bytes
+            }
+        }
+
+        let wipes = Rc::new(Cell::new(0));
+        let buffer = CountingBuffer {
+            bytes: vec![0u8; 32],
+            wipes: Rc::clone(&wipes),
+        };
+
+        let mnemonic = mnemonic_from_entropy(Zeroizing::new(buffer), Language::English).unwrap();
+        assert_eq!(mnemonic.word_count(), 24);
+        assert_eq!(wipes.get(), 1, "Entropy buffer should be zeroized on drop");
+    }
+
+    #[test]
+    fn test_dice_entropy() {
+        // 99 rolls cycling 1-6; expected phrase is SHA-256 of the ASCII digits
+        let rolls = "123456".repeat(17)[..99].to_string();
+        let entropy = dice_entropy(&rolls, 24).unwrap();
+        let mnemonic = mnemonic_from_entropy(entropy, Language::English).unwrap();
+        assert_eq!(
+            mnemonic.to_string(),
+            "few educate sugar bless boring random strategy waste mutual cargo type hawk prefer denial scan abstract filter extend dignity balcony dust unusual correct bubble"
+        );
+
+        // Whitespace between rolls is ignored
+        let spaced: String = rolls.chars().flat_map(|c| [c, ' ']).collect();
+        assert_eq!(
+            dice_entropy(&spaced, 24).unwrap(),
+            dice_entropy(&rolls, 24).unwrap()
+        );
+
+        // 12 words truncate the hash to 128 bits
+        let entropy = dice_entropy(&"61".repeat(25), 12).unwrap();
+        let mnemonic = mnemonic_from_entropy(entropy, Language::English).unwrap();
+        assert_eq!(
+            mnemonic.to_string(),
+            "tool panther stuff husband faith sauce rigid category pull wreck lake worth"
+        );
+
+        // Too few or too many rolls, or digits outside 1-6, are rejected
+        assert!(dice_entropy(&rolls[..98], 24).is_err());
+        assert!(dice_entropy(&format!("{}1", rolls), 24).is_err());
+        assert!(dice_entropy(&"61".repeat(26), 12).is_err());
+        assert!(dice_entropy(&format!("{}0", &rolls[..98]), 24).is_err());
+        assert!(dice_entropy(&format!("{}7", rolls), 24).is_err());
+    }
+
+    #[test]
+    fn test_entropy_sanity_check() {
+        assert!(entropy_sanity_check(&[0x00; 32]).is_err());
+        assert!(entropy_sanity_check(&[0xFF; 16]).is_err());
+        assert!(entropy_sanity_check(&[]).is_err());
+
+        // 32 bits set out of 256 is far outside the monobit bound
+        assert!(entropy_sanity_check(&[0x01; 32]).is_err());
+
+        // Perfectly balanced and real RNG output pass
+        assert!(entropy_sanity_check(&[0x5A; 32]).is_ok());
+        let mut random = [0u8; 32];
+        getrandom::fill(&mut random).unwrap();
+        assert!(entropy_sanity_check(&random).is_ok());
+    }
+
+    #[test]
+    fn test_mnemonic_checksum_bits() {
+        // 128 zero bits: the last word carries 7 zero entropy bits + 4 checksum bits
+        let (bits, value) = mnemonic_checksum_bits(&[0u8; 16]);
+        assert_eq!(bits, 4);
+        assert_eq!(value, 0b0011);
+        assert_eq!(Language::English.word_list()[value as usize], "about");
+
+        // 256 zero bits: the last word is entirely checksum
+        let (bits, value) = mnemonic_checksum_bits(&[0u8; 32]);
+        assert_eq!(bits, 8);
+        assert_eq!(Language::English.word_list()[value as usize], "art");
+
+        // Agrees with the last word of a generated mnemonic
+        for &word_count in SUPPORTED_WORD_COUNTS.iter() {
+            let mnemonic = generate_mnemonic(word_count, Language::English).unwrap();
+            let entropy = mnemonic.to_entropy();
+            let (bits, value) = mnemonic_checksum_bits(&entropy);
+            let last_index = Language::English
+                .find_word(mnemonic.words().last().unwrap())
+                .unwrap();
+            assert_eq!(last_index & ((1 << bits) - 1), value as u16);
+        }
+    }
+
+    #[test]
+    fn test_recover_mnemonic() {
+        let test_phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let mnemonic = recover_mnemonic(test_phrase, Language::English).unwrap();
+        let seed = generate_seed(&mnemonic, "");
+        let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
+        assert_eq!(get_hardware_wallet_fingerprint(&master_key), "73c5da0a");
+
+        // Surrounding whitespace and capitalisation are tolerated
+        let messy = format!("  {}\n", test_phrase.to_uppercase());
+        assert_eq!(
+            recover_mnemonic(&messy, Language::English).unwrap(),
+            mnemonic
+        );
+
+        // Checksum failures are rejected with a clear error
+        let bad_checksum = test_phrase.replace("about", "abandon");
+        let err = recover_mnemonic(&bad_checksum, Language::English).unwrap_err();
+        assert!(err.to_string().contains("checksum"));
+
+        let unknown = test_phrase.replace("about", "bitcoin1");
+        let err = recover_mnemonic(&unknown, Language::English).unwrap_err();
+        assert!(err.to_string().contains("word 12 'bitcoin1'"));
+
+        assert!(recover_mnemonic("abandon about", Language::English).is_err());
+
+        // A typo gets the closest wordlist entries suggested
+        let typo = test_phrase.replacen("abandon", "abandom", 1);
+        let err = recover_mnemonic(&typo, Language::English).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("word 1 'abandom' is not in the English wordlist; did you mean: abandon"));
+    }
+
+    #[test]
+    fn test_suggest_words() {
+        assert_eq!(levenshtein("abandom", "abandon"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "zoo"), 3);
+
+        let suggestions = suggest_words("abandom", Language::English);
+        assert_eq!(suggestions[0], "abandon");
+        assert!(suggestions.len() <= 3);
+        assert_eq!(suggest_words("zooo", Language::English)[0], "zoo");
+        assert!(suggest_words("qqqqqqqq", Language::English).is_empty());
+        assert_eq!(suggest_words("abejx", Language::Spanish)[0], "abeja");
+    }
+
+    #[test]
+    fn test_generate_mnemonic_spanish() {
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::Spanish).unwrap();
+        assert_eq!(mnemonic.language(), Language::Spanish);
+
+        // Round-trip the words back through the parser
+        let phrase = mnemonic.to_string();
+        let parsed = Mnemonic::parse_in_normalized(Language::Spanish, &phrase).unwrap();
+        assert_eq!(parsed, mnemonic);
+        assert_eq!(parsed.to_entropy(), mnemonic.to_entropy());
+    }
+
+    #[test]
+    fn test_create_coldcard_word_list_japanese() {
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::Japanese).unwrap();
+        let output = create_coldcard_word_list(&mnemonic);
+        assert!(
+            !output.contains('\u{3000}'),
+            "Import file should not contain ideographic spaces"
+        );
+        assert_eq!(output.lines().count(), 24);
+
+        // The file contents parse back to the same mnemonic
+        let parsed = Mnemonic::parse_in_normalized(Language::Japanese, &output).unwrap();
+        assert_eq!(parsed, mnemonic);
+    }
+
+    #[test]
+    fn test_generate_seed() {
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+        let seed = generate_seed(&mnemonic, "");
+        assert_eq!(seed.len(), 64, "Seed should be 64 bytes");
+
+        // Test with passphrase
+        let seed_with_passphrase = generate_seed(&mnemonic, "test_passphrase");
+        assert_ne!(
+            seed, seed_with_passphrase,
+            "Seed with passphrase should be different"
+        );
+    }
+
+    #[test]
+    fn test_passphrase_changes_fingerprint() {
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+        let plain = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        let protected =
+            derive_master_key(&generate_seed(&mnemonic, "hunter2"), Network::Bitcoin).unwrap();
+
+        assert_ne!(
+            get_hardware_wallet_fingerprint(&plain),
+            get_hardware_wallet_fingerprint(&protected),
+            "Passphrase should change the fingerprint"
+        );
+    }
+
+    #[test]
+    fn test_read_passphrase_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+        let inline_key =
+            derive_master_key(&generate_seed(&mnemonic, " my secret "), Network::Bitcoin).unwrap();
+
+        // Only the trailing newline is trimmed, surrounding spaces are kept
+        for contents in [" my secret \n", " my secret \r\n", " my secret "] {
+            let path = temp_dir.path().join("passphrase.txt");
+            fs::write(&path, contents).unwrap();
+
+            let passphrase = read_passphrase_file(&path).unwrap();
+            let file_key =
+                derive_master_key(&generate_seed(&mnemonic, &passphrase), Network::Bitcoin)
+                    .unwrap();
+            assert_eq!(
+                get_hardware_wallet_fingerprint(&file_key),
+                get_hardware_wallet_fingerprint(&inline_key)
+            );
+        }
+
+        let empty = temp_dir.path().join("empty.txt");
+        fs::write(&empty, "\n").unwrap();
+        assert!(read_passphrase_file(&empty).is_err());
+        assert!(read_passphrase_file(&temp_dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_generate_electrum_seed() {
+        // Electrum's own segwit seed test vector
+        assert!(is_electrum_segwit_seed(
+            "wild father tree among universe such mobile favorite target dynamic credit identify"
+        ));
+        assert!(!is_electrum_segwit_seed(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"
+        ));
+
+        for _ in 0..3 {
+            let seed = generate_electrum_seed().unwrap();
+            assert_eq!(seed.split(' ').count(), 12);
+            assert!(electrum_seed_version(&seed).starts_with("100"));
+            assert!(Mnemonic::parse_in_normalized(Language::English, &seed).is_err());
+        }
+
+        let output = create_electrum_seed_output("wild father tree", "Test", &now_timestamp());
+        assert!(output.contains("NOT A BIP39 MNEMONIC"));
+        assert!(output.contains(" 3. tree"));
+    }
+
+    #[test]
+    fn test_encrypt_payload_round_trip() {
+        let payload = b"abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+        let encrypted = encrypt_payload(payload, "correct horse").unwrap();
+        assert!(encrypted.starts_with(ENCRYPTION_MAGIC));
+        assert_eq!(encrypted.len(), ENCRYPTION_HEADER_LEN + payload.len() + 16);
+        assert!(!encrypted.windows(7).any(|window| window == b"abandon"));
+
+        let decrypted = decrypt_payload(&encrypted, "correct horse").unwrap();
+        assert_eq!(decrypted.as_slice(), payload);
+
+        // Fresh salt and nonce every time
+        assert_ne!(
+            encrypted,
+            encrypt_payload(payload, "correct horse").unwrap()
+        );
+
+        assert!(decrypt_payload(&encrypted, "wrong horse").is_err());
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(decrypt_payload(&tampered, "correct horse").is_err());
+        let mut tampered_header = encrypted;
+        tampered_header[ENCRYPTION_MAGIC.len()] ^= 1;
+        assert!(decrypt_payload(&tampered_header, "correct horse").is_err());
+        assert!(decrypt_payload(b"not encrypted", "correct horse").is_err());
+    }
+
+    #[test]
+    fn test_confirm_passphrase() {
+        let secret = |s: &str| Zeroizing::new(s.to_string());
+
+        let confirmed = confirm_passphrase(secret("hunter2"), secret("hunter2")).unwrap();
+        assert_eq!(confirmed.as_str(), "hunter2");
+
+        assert!(confirm_passphrase(secret("hunter2"), secret("hunter3")).is_err());
+        assert!(confirm_passphrase(secret(""), secret("")).is_err());
+    }
+
+    #[test]
+    fn test_derive_master_key() {
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+        let seed = generate_seed(&mnemonic, "");
+        let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
+
+        // Verify master key is valid
+        assert!(!master_key.to_string().is_empty());
+        assert!(master_key.to_string().starts_with("xprv"));
+    }
+
+    #[test]
+    fn test_derive_master_key_testnet() {
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+        let seed = generate_seed(&mnemonic, "");
+        let mainnet_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
+        let testnet_key = derive_master_key(&seed, Network::Testnet).unwrap();
+
+        // Version bytes differ per network
+        assert!(testnet_key.to_string().starts_with("tprv"));
+
+        // Fingerprint is network-independent
+        assert_eq!(
+            get_hardware_wallet_fingerprint(&mainnet_key),
+            get_hardware_wallet_fingerprint(&testnet_key)
+        );
+    }
+
+    #[test]
+    fn test_derive_addresses() {
+        // BIP84/BIP49/BIP44/BIP86 test vectors for the all-abandon mnemonic
+        let test_phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let mnemonic = Mnemonic::parse_in_normalized(Language::English, test_phrase).unwrap();
+        let seed = generate_seed(&mnemonic, "");
+        let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
+
+        let addresses = derive_addresses(&master_key, Network::Bitcoin, 3).unwrap();
+        assert_eq!(addresses.len(), 12, "Should derive 3 addresses per type");
+
+        assert_eq!(addresses[0].0, "m/84'/0'/0'/0/0");
+        assert_eq!(addresses[0].1, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+        assert_eq!(addresses[1].1, "bc1qnjg0jd8228aq7egyzacy8cys3knf9xvrerkf9g");
+        assert_eq!(addresses[3].0, "m/49'/0'/0'/0/0");
+        assert_eq!(addresses[3].1, "37VucYSaXLCAsxYyAPfbSi9eh4iEcbShgf");
+        assert_eq!(addresses[6].0, "m/44'/0'/0'/0/0");
+        assert_eq!(addresses[6].1, "1LqBGSKuX5yYUonjxT5qGfpUsXKYYWeabA");
+
+        // BIP86 reference vector: tweaked x-only output key
+        assert_eq!(addresses[9].0, "m/86'/0'/0'/0/0");
+        assert_eq!(
+            addresses[9].1,
+            "bc1p5cyxnuxmeuwuvkwfem96lqzszd02n6xdcjrs20cac6yqjjwudpxqkedrcr"
+        );
+        assert_eq!(
+            addresses[10].1,
+            "bc1p4qhjn9zdvkux4e44uhx8tc55attvtyu358kutcqkudyccelu0was9fqzwh"
+        );
+
+        // Test networks use coin type 1'
+        let testnet_key = derive_master_key(&seed, Network::Testnet).unwrap();
+        let testnet_addresses = derive_addresses(&testnet_key, Network::Testnet, 1).unwrap();
+        assert_eq!(testnet_addresses[0].0, "m/84'/1'/0'/0/0");
+        assert!(testnet_addresses[0].1.starts_with("tb1q"));
+        assert!(testnet_addresses[3].1.starts_with("tb1p"));
+    }
+
+    #[test]
+    fn test_derive_account_xpub() {
+        // BIP84 test vector account 0 for the all-abandon mnemonic
+        let test_phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let mnemonic = Mnemonic::parse_in_normalized(Language::English, test_phrase).unwrap();
+        let seed = generate_seed(&mnemonic, "");
+        let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
+
+        let xpub = derive_account_xpub(&master_key, Network::Bitcoin, 84, 0).unwrap();
+        assert_eq!(
+            slip132_encode(&xpub, 84),
+            "zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs"
+        );
+        assert_eq!(
+            xpub.to_string(),
+            "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V"
+        );
+
+        let path = account_derivation_path(Network::Bitcoin, 84, 0).unwrap();
+        let output = create_account_xpub_output(&xpub, &path, "73c5da0a", 84);
+        assert!(output.contains("m/84'/0'/0'"));
+        assert!(output.contains("zpub6rFR7y4Q2AijBEqTUquhVz398htDFrtymD9xYYfG1m4wAcvPhXNfE3EfH1r1ADqtfSdVCToUG868RvUUkgDKf31mGDtKsAYz2oz2AGutZYs"));
+    }
+
+    #[test]
+    fn test_shamir_split() {
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+        let entropy = mnemonic.to_entropy();
+
+        let shares = shamir_split(&entropy, 2, 3).unwrap();
+        assert_eq!(shares.len(), 3, "Should produce 3 shares");
+
+        // Any threshold subset reconstructs the original entropy
+        for subset in [[0, 1], [0, 2], [1, 2]] {
+            let chosen: Vec<Vec<String>> = subset.iter().map(|&i| shares[i].clone()).collect();
+            let recovered = sssmc39::combine_mnemonics(&chosen, "").unwrap();
+            assert_eq!(recovered, entropy, "Shares should recover the entropy");
+        }
+
+        // Each share gets its own punchable layout
+        let share = ShamirShareNumber {
+            number: 1,
+            threshold: 2,
+            total: 3,
+        };
+        let output = create_shamir_share_output(
+            &shares[0],
+            share,
+            "73c5da0a",
+            "Test",
+            Network::Bitcoin,
+            &PrintableOptions::default(),
+            &now_timestamp(),
+        );
+        assert!(output.contains("SLIP39 SHARE 1 OF 3"));
+        assert!(output.contains("any 2 of 3 shares"));
+        assert!(output.contains("SHARE WORDS (Punch these in order):"));
+        assert!(output.contains("VERIFICATION CHECKLIST:"));
+        assert!(!output.contains("HARDWARE WALLET IMPORT INSTRUCTIONS"));
+        for word in &shares[0] {
+            assert!(output.contains(word.as_str()), "Share should contain word");
+        }
+
+        // The same layout options as the wallet printable apply
+        let output = create_shamir_share_output(
+            &shares[0],
+            share,
+            "73c5da0a",
+            "Test",
+            Network::Bitcoin,
+            &PrintableOptions {
+                language: InstructionLanguage::German,
+                phonetic: true,
+                word_indices: true,
+                ..PrintableOptions::default()
+            },
+            &now_timestamp(),
+        );
+        assert!(output.contains("ANTEILSWÖRTER"));
+        assert!(!output.contains("SECURITY WARNING"));
+        assert!(output.contains("PHONETISCHE BUCHSTABIERUNG"));
+        let words: Vec<&str> = shares[0].iter().map(String::as_str).collect();
+        assert!(output.contains(&format_word_column(&words)));
+    }
+
+    #[test]
+    fn test_seed_xor_split() {
+        for word_count in [12, 24] {
+            let mnemonic = generate_mnemonic(word_count, Language::English).unwrap();
+            let entropy = mnemonic.to_entropy();
+
+            let parts = seed_xor_split(&entropy, 3).unwrap();
+            assert_eq!(parts.len(), 3, "Should produce 3 parts");
+
+            // XOR of all part entropies reproduces the input exactly
+            let mut combined = vec![0u8; entropy.len()];
+            for part in &parts {
+                assert_eq!(part.word_count(), word_count);
+                for (acc, byte) in combined.iter_mut().zip(part.to_entropy()) {
+                    *acc ^= byte;
+                }
+            }
+            assert_eq!(combined, entropy, "Parts should XOR to the original");
+        }
+
+        assert!(seed_xor_split(&[0u8; 32], 1).is_err());
+    }
+
+    #[test]
+    fn test_create_wallet_json() {
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+        let seed = generate_seed(&mnemonic, "");
+        let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
+        let fingerprint = get_hardware_wallet_fingerprint(&master_key);
+        let xpub = derive_account_xpub(&master_key, Network::Bitcoin, 84, 0).unwrap();
+
+        let primary = wallet_keys(&master_key, Network::Bitcoin).unwrap();
+
+        let json = create_wallet_json(
+            &mnemonic,
+            "Test",
+            Network::Bitcoin,
+            &primary,
+            None,
+            &now_timestamp(),
+        )
+        .unwrap();
+        let wallet: WalletJson = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(wallet.label, "Test");
+        assert_eq!(wallet.network, "mainnet");
+        assert_eq!(wallet.word_count, 24);
+        assert_eq!(wallet.words.len(), 24, "Should contain all words");
+        assert_eq!(wallet.primary.fingerprint, fingerprint);
+        assert!(wallet.primary.first_address.starts_with("bc1q"));
+        assert_eq!(wallet.primary.account_xpub, xpub.to_string());
+        assert!(wallet.duress.is_none());
+        assert!(!json.contains("duress"));
+    }
+
+    #[test]
+    fn test_create_summary_line() {
+        let mnemonic = Mnemonic::parse_in(
+            Language::English,
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let master_key =
+            derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        let keys = wallet_keys(&master_key, Network::Bitcoin).unwrap();
+
+        let line = create_summary_line(&keys, Network::Bitcoin, mnemonic.word_count());
+        assert!(!line.contains('\n'), "Summary should be a single line");
+
+        let pairs: std::collections::HashMap<&str, &str> = line
+            .split_whitespace()
+            .map(|pair| pair.split_once('=').expect("KEY=VALUE pair"))
+            .collect();
+        assert_eq!(pairs.len(), 5);
+        assert_eq!(pairs["FINGERPRINT"], "73c5da0a");
+        assert_eq!(pairs["NETWORK"], "mainnet");
+        assert_eq!(pairs["WORDS"], "12");
+        assert_eq!(pairs["XPUB"], keys.account_xpub);
+        assert_eq!(
+            pairs["FIRST_ADDRESS"],
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+    }
+
+    #[test]
+    fn test_duress_wallet() {
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+        let primary_key =
+            derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        let duress_key =
+            derive_master_key(&generate_seed(&mnemonic, "decoy"), Network::Bitcoin).unwrap();
+
+        let primary = wallet_keys(&primary_key, Network::Bitcoin).unwrap();
+        let duress = wallet_keys(&duress_key, Network::Bitcoin).unwrap();
+        assert_ne!(primary.fingerprint, duress.fingerprint);
+        assert_ne!(primary.first_address, duress.first_address);
+
+        let json = create_wallet_json(
+            &mnemonic,
+            "Test",
+            Network::Bitcoin,
+            &primary,
+            Some(&duress),
+            &now_timestamp(),
+        )
+        .unwrap();
+        let wallet: WalletJson = serde_json::from_str(&json).unwrap();
+        assert_eq!(wallet.primary, primary);
+        assert_eq!(wallet.duress, Some(duress));
+    }
+
+    #[test]
+    fn test_render_qr() {
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+        let phrase = mnemonic.to_string();
+
+        // PNG decodes back to the original mnemonic string
+        let png = render_qr_png(&phrase, EcLevel::M).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_luma8();
+        let mut prepared = rqrr::PreparedImage::prepare(image);
+        let grids = prepared.detect_grids();
+        assert_eq!(grids.len(), 1, "Should detect one QR code");
+        let (_, content) = grids[0].decode().unwrap();
+        assert_eq!(content, phrase);
+
+        // ASCII rendering is pure ASCII
+        let ascii = render_qr_ascii(&phrase, EcLevel::H).unwrap();
+        assert!(ascii.contains('#'));
+        assert!(ascii.is_ascii());
+
+        assert_eq!(parse_ec_level("q").unwrap(), EcLevel::Q);
+        assert!(parse_ec_level("X").is_err());
+    }
+
+    #[test]
+    fn test_seed_xor_qr() {
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+        let entropy = mnemonic.to_entropy();
+        let parts = seed_xor_split(&entropy, 2).unwrap();
+
+        let mut combined = vec![0u8; entropy.len()];
+        let mut captions = Vec::new();
+        for (i, part) in parts.iter().enumerate() {
+            let fingerprint = get_hardware_wallet_fingerprint(
+                &derive_master_key(&generate_seed(part, ""), Network::Bitcoin).unwrap(),
+            );
+            let caption = seed_xor_caption(i + 1, parts.len(), &fingerprint);
+            assert_eq!(
+                caption,
+                format!("Seed XOR part {} of 2 - fp {}", i + 1, fingerprint)
+            );
+            let png = render_captioned_qr_png(&part.to_string(), EcLevel::M, &caption).unwrap();
+            let image = image::load_from_memory(&png).unwrap().to_luma8();
+
+            // The caption strip below the code is drawn and differs per part
+            let plain =
+                image::load_from_memory(&render_qr_png(&part.to_string(), EcLevel::M).unwrap())
+                    .unwrap()
+                    .to_luma8();
+            assert!(image.height() > plain.height());
+            let strip: Vec<u8> = image
+                .rows()
+                .skip(plain.height() as usize)
+                .flatten()
+                .map(|pixel| pixel.0[0])
+                .collect();
+            assert!(strip.contains(&0), "Caption should be drawn");
+            captions.push(strip);
+
+            let mut prepared = rqrr::PreparedImage::prepare(image);
+            let grids = prepared.detect_grids();
+            assert_eq!(grids.len(), 1, "Should detect one QR code per part");
+            let (_, content) = grids[0].decode().unwrap();
+
+            // Each QR decodes to a valid BIP39 mnemonic equal to its part
+            let decoded = Mnemonic::parse_in(Language::English, &content).unwrap();
+            assert_eq!(&decoded, part);
+            for (c, b) in combined.iter_mut().zip(decoded.to_entropy()) {
+                *c ^= b;
+            }
+        }
+
+        // And the decoded parts XOR back to the original seed
+        assert_eq!(combined, entropy);
+        assert_ne!(captions[0], captions[1]);
+
+        let temp_dir = TempDir::new().unwrap();
+        let options = parse_args(["--seed-xor", "2", "--phonetic"].map(String::from)).unwrap();
+        let output_dir = temp_dir.path().join("output");
+        run(&options, &output_dir).unwrap();
+        let png = fs::read(output_dir.join("seed_xor_part_1.png")).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_luma8();
+        assert!(image.height() > image.width());
+        // The parts follow --phonetic like the wallet printable
+        let part = fs::read_to_string(output_dir.join("seed_xor_part_1.txt")).unwrap();
+        assert!(part.contains("PHONETIC SPELLING (for reading aloud)"));
+    }
+
+    #[test]
+    fn test_parse_shamir_spec() {
+        assert_eq!(parse_shamir_spec("2-of-3").unwrap(), (2, 3));
+        assert_eq!(parse_shamir_spec("3-of-5").unwrap(), (3, 5));
+        assert!(parse_shamir_spec("4-of-3").is_err());
+        assert!(parse_shamir_spec("0-of-3").is_err());
+        assert!(parse_shamir_spec("2of3").is_err());
+    }
+
+    #[test]
+    fn test_account_summary() {
+        let mnemonic = Mnemonic::parse_in(
+            Language::English,
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let master_key =
+            derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+
+        let accounts = account_summary(&master_key, Network::Bitcoin);
+        assert_eq!(accounts.len(), 4);
+        assert_eq!(
+            accounts.iter().map(|a| a.purpose).collect::<Vec<_>>(),
+            vec![44, 49, 84, 86]
+        );
+
+        // BIP84 / BIP86 reference vectors
+        assert_eq!(accounts[2].path, "m/84'/0'/0'");
+        assert_eq!(
+            accounts[2].xpub.to_string(),
+            "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V"
+        );
+        assert_eq!(
+            accounts[3].xpub.to_string(),
+            "xpub6BgBgsespWvERF3LHQu6CnqdvfEvtMcQjYrcRzx53QJjSxarj2afYWcLteoGVky7D3UKDP9QyrLprQ3VCECoY49yfdDEHGCtMMj92pReUsQ"
+        );
+        assert!(accounts
+            .iter()
+            .all(|a| a.xprv.to_string().starts_with("xprv")));
+
+        let output = create_accounts_output(&accounts, "73c5da0a");
+        assert!(output.contains("BIP86 m/86'/0'/0'"));
+        assert!(output.contains(&accounts[0].xprv.to_string()));
+
+        let testnet = account_summary(&master_key, Network::Testnet);
+        assert_eq!(testnet[0].path, "m/44'/1'/0'");
+    }
+
+    #[test]
+    fn test_derive_custom_path() {
+        let mnemonic = Mnemonic::parse_in(
+            Language::English,
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let master_key =
+            derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+
+        // BIP48 multisig account: no single-sig address at this level
+        let path = parse_derivation_path("m/48'/0'/0'/2'").unwrap();
+        let derived = derive_custom_path(&master_key, Network::Bitcoin, &path).unwrap();
+        assert_eq!(derived.xpub.depth, 4);
+        assert_eq!(derived.xpub.fingerprint().to_string(), "abc63537");
+        assert_eq!(
+            derive_custom_path(&master_key, Network::Bitcoin, &path)
+                .unwrap()
+                .xpub,
+            derived.xpub
+        );
+        assert!(derived.address.is_none());
+        assert_eq!(
+            Xpub::from_priv(&Secp256k1::new(), &derived.xprv),
+            derived.xpub
+        );
+
+        // Standard address-level paths also yield the address
+        let path = parse_derivation_path("m/84'/0'/0'/0/0").unwrap();
+        let derived = derive_custom_path(&master_key, Network::Bitcoin, &path).unwrap();
+        assert_eq!(
+            derived.address.as_deref(),
+            Some("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu")
+        );
+
+        let err = parse_derivation_path("m/48'/zero'").unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Invalid derivation path 'm/48'/zero''"));
+        assert!(parse_derivation_path("48h/0h/0h/2h").is_ok());
+    }
+
+    #[test]
+    fn test_build_multisig_cosigner() {
+        let mnemonic = Mnemonic::parse_in(
+            Language::English,
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let master_key =
+            derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+
+        let (key, descriptor) =
+            build_multisig_cosigner(&master_key, Network::Bitcoin, 2, 3).unwrap();
+        assert!(key.starts_with("[73c5da0a/48h/0h/0h/2h]xpub"));
+        assert!(descriptor.starts_with("wsh(sortedmulti(2,[73c5da0a/48h/0h/0h/2h]xpub"));
+        assert!(descriptor.contains(&format!("{}/0/*", key)));
+        assert!(descriptor.contains("<cosigner2-xpub>"));
+        assert!(descriptor.contains("<cosigner3-xpub>"));
+        assert!(!descriptor.contains("<cosigner4"));
+        assert!(descriptor.ends_with("))"));
+
+        let output = create_multisig_output(&key, &descriptor, 2, 3);
+        assert!(output.contains("2-of-3"));
+        assert!(output.contains(&descriptor));
+
+        assert_eq!(parse_multisig_spec("3-of-5").unwrap(), (3, 5));
+        assert!(parse_multisig_spec("3-of-2").is_err());
+        assert!(parse_multisig_spec("1-of-1").is_err());
+        assert!(parse_multisig_spec("2-of-16").is_err());
+        assert!(parse_multisig_spec("2of3").is_err());
+    }
+
+    #[test]
+    fn test_build_descriptors() {
+        let test_phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let mnemonic = Mnemonic::parse_in_normalized(Language::English, test_phrase).unwrap();
+        let seed = generate_seed(&mnemonic, "");
+        let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
+
+        let descriptors = build_descriptors(&master_key, Network::Bitcoin).unwrap();
+        assert_eq!(descriptors.len(), 4);
+        assert_eq!(
+            descriptors[0],
+            "wpkh([73c5da0a/84h/0h/0h]xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/0/*)#afwvtk2s"
+        );
+        assert!(descriptors[1].starts_with("sh(wpkh([73c5da0a/49h/0h/0h]xpub"));
+        assert!(descriptors[2].starts_with("pkh([73c5da0a/44h/0h/0h]xpub"));
+        assert!(descriptors[3].starts_with("tr([73c5da0a/86h/0h/0h]xpub"));
+        for descriptor in &descriptors {
+            assert!(
+                descriptor.contains("/0/*"),
+                "Should range over receive chain"
+            );
+            let (body, checksum) = descriptor.split_once('#').unwrap();
+            assert_eq!(checksum, descriptor_checksum(body).unwrap());
+        }
+
+        // Test networks use coin type 1h and tpub keys
+        let testnet_key = derive_master_key(&seed, Network::Testnet).unwrap();
+        let testnet = build_descriptors(&testnet_key, Network::Testnet).unwrap();
+        assert!(testnet[0].starts_with("wpkh([73c5da0a/84h/1h/0h]tpub"));
+
+        let output = create_descriptors_output(&descriptors);
+        assert_eq!(output.lines().filter(|l| !l.starts_with('#')).count(), 4);
+    }
+
+    #[test]
+    fn test_derive_bip85_mnemonic() {
+        use std::str::FromStr;
+
+        // BIP85 reference vectors (application 39, English)
+        let master = Xpriv::from_str("xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb").unwrap();
+
+        let child = derive_bip85_mnemonic(&master, 12, 0).unwrap();
+        assert_eq!(
+            child.to_string(),
+            "girl mad pet galaxy egg matter matrix prison refuse sense ordinary nose"
+        );
+
+        let child = derive_bip85_mnemonic(&master, 18, 0).unwrap();
+        assert_eq!(
+            child.to_string(),
+            "near account window bike charge season chef number sketch tomorrow excuse sniff circle vital hockey outdoor supply token"
+        );
+
+        let child = derive_bip85_mnemonic(&master, 24, 0).unwrap();
+        assert_eq!(
+            child.to_string(),
+            "puppy ocean match cereal symbol another shed magic wrap hammer bulb intact gadget divorce twin tonight reason outdoor destroy simple truth cigar social volcano"
+        );
+
+        // Different indexes give independent children
+        let other = derive_bip85_mnemonic(&master, 12, 1).unwrap();
+        assert_ne!(other, derive_bip85_mnemonic(&master, 12, 0).unwrap());
+
+        assert!(derive_bip85_mnemonic(&master, 15, 0).is_err());
+    }
+
+    #[test]
+    fn test_derive_bip85_wif_and_hex() {
+        use std::str::FromStr;
+
+        // BIP85 reference vectors (application 2 WIF, application 128169 HEX)
+        let master = Xpriv::from_str("xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb").unwrap();
+
+        let wif = derive_bip85_wif(&master, Network::Bitcoin, 0).unwrap();
+        assert_eq!(
+            wif.as_str(),
+            "Kzyv4uF39d4Jrw2W7UryTHwZr1zQVNk4dAFyqE6BuMrMh1Za7uhp"
+        );
+        assert_ne!(derive_bip85_wif(&master, Network::Bitcoin, 1).unwrap(), wif);
+
+        let hex = derive_bip85_hex(&master, 64, 0).unwrap();
+        assert_eq!(
+            hex.as_str(),
+            "492db4698cf3b73a5a24998aa3e9d7fa96275d85724a91e71aa2d645442f8785\
+             55d078fd1f1f67e368976f04137b1f7a0d19232136ca50c44614af72b5582a5c"
+        );
+        assert_eq!(derive_bip85_hex(&master, 16, 0).unwrap().len(), 32);
+        assert!(derive_bip85_hex(&master, 15, 0).is_err());
+        assert!(derive_bip85_hex(&master, 65, 0).is_err());
+
+        assert_eq!(parse_bip85_hex_spec("3:32").unwrap(), (3, 32));
+        assert!(parse_bip85_hex_spec("0:8").is_err());
+        assert!(parse_bip85_hex_spec("64").is_err());
+        assert!(parse_bip85_index("2147483648").is_err());
+    }
+
+    #[test]
+    fn test_parse_bip85_spec() {
+        assert_eq!(parse_bip85_spec("12:0").unwrap(), (12, 0));
+        assert_eq!(parse_bip85_spec("24:7").unwrap(), (24, 7));
+        assert!(parse_bip85_spec("15:0").is_err());
+        assert!(parse_bip85_spec("12").is_err());
+        assert!(parse_bip85_spec("12:2147483648").is_err());
+    }
+
+    #[test]
+    fn test_parse_network() {
+        assert_eq!(parse_network("mainnet").unwrap(), Network::Bitcoin);
+        assert_eq!(parse_network("testnet").unwrap(), Network::Testnet);
+        assert_eq!(parse_network("signet").unwrap(), Network::Signet);
+        assert_eq!(parse_network("regtest").unwrap(), Network::Regtest);
+        assert_eq!(parse_network("testnet4").unwrap(), Network::Testnet4);
+        let error = parse_network("litecoin").unwrap_err();
+        assert!(error.to_string().contains("testnet4"));
+    }
+
+    #[test]
+    fn test_get_hardware_wallet_fingerprint() {
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+        let seed = generate_seed(&mnemonic, "");
+        let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
+        let fingerprint = get_hardware_wallet_fingerprint(&master_key);
+
+        // Fingerprint should be 8 hex characters
+        assert_eq!(
+            fingerprint.len(),
+            8,
+            "Fingerprint should be 8 hex characters"
+        );
+        assert!(
+            fingerprint.chars().all(|c| c.is_ascii_hexdigit()),
+            "Fingerprint should contain only hex characters"
+        );
+    }
+
+    #[test]
+    fn test_create_printable_output() {
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+        let seed = generate_seed(&mnemonic, "");
+        let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
+        let fingerprint = get_hardware_wallet_fingerprint(&master_key);
+
+        let addresses = derive_addresses(&master_key, Network::Bitcoin, 1).unwrap();
+        let output = create_printable_output(
+            &mnemonic,
+            &fingerprint,
+            "Test Wallet",
+            Network::Bitcoin,
+            &addresses,
+        );
+
+        // Verify output contains expected sections
+        assert!(
+            output.contains("BITCOIN SEED PHRASE"),
+            "Should contain header"
+        );
+        assert!(output.contains("Test Wallet"), "Should contain label");
+        assert!(
+            output.contains("Network: Bitcoin Mainnet"),
+            "Should contain network"
+        );
+        assert!(output.contains(&fingerprint), "Should contain fingerprint");
+        assert!(
+            output.contains("SECURITY WARNING"),
+            "Should contain security warning"
+        );
+        assert!(
+            output.contains("SEED WORDS"),
+            "Should contain seed words section"
+        );
+        assert!(
+            output.contains("VERIFICATION CHECKLIST"),
+            "Should contain checklist"
+        );
+        assert!(
+            output.contains("HARDWARE WALLET IMPORT INSTRUCTIONS"),
+            "Should contain instructions"
+        );
+        assert!(
+            output.contains("RECEIVE ADDRESSES"),
+            "Should contain address section"
+        );
+        for (path, address) in &addresses {
+            assert!(output.contains(path.as_str()), "Should contain path");
+            assert!(output.contains(address.as_str()), "Should contain address");
+        }
+
+        // Verify all 24 words are present
+        let words: Vec<&str> = mnemonic.words().collect();
+        for word in &words {
+            assert!(
+                output.contains(word),
+                "Output should contain word: {}",
+                word
+            );
+        }
+
+        // Verify word count
+        let word_count = output.matches("words").count();
+        assert!(word_count > 0, "Should mention word count");
+    }
+
+    #[test]
+    fn test_create_printable_pdf() {
+        let temp_dir = TempDir::new().unwrap();
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+
+        let pdf = create_printable_pdf(
+            &mnemonic,
+            "73c5da0a",
+            "Test",
+            Network::Bitcoin,
+            &now_timestamp(),
+            false,
+        )
+        .unwrap();
+        let pdf_file = temp_dir.path().join("seed_phrase.pdf");
+        fs::write(&pdf_file, &pdf).unwrap();
+
+        let contents = fs::read(&pdf_file).unwrap();
+        assert!(!contents.is_empty(), "PDF should not be empty");
+        assert!(
+            contents.starts_with(b"%PDF"),
+            "Should start with the PDF magic"
+        );
+
+        let japanese = generate_mnemonic(12, Language::Japanese).unwrap();
+        assert!(create_printable_pdf(
+            &japanese,
+            "73c5da0a",
+            "Test",
+            Network::Bitcoin,
+            &now_timestamp(),
+            false
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_create_simple_word_list() {
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+        let output = create_simple_word_list(&mnemonic);
+
+        let words: Vec<&str> = mnemonic.words().collect();
+        assert_eq!(words.len(), 24);
+
+        // Verify all words are in output
+        for (i, word) in words.iter().enumerate() {
+            assert!(
+                output.contains(word),
+                "Output should contain word: {}",
+                word
+            );
+            // Check numbering
+            let expected_line = format!("{:2}. {}", i + 1, word);
+            assert!(
+                output.contains(&expected_line),
+                "Should contain numbered line"
+            );
+        }
+    }
+
+    #[test]
+    fn test_mnemonic_consistency() {
+        // Test that the same mnemonic produces the same seed
+        let test_phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+        let mnemonic =
+            Mnemonic::parse_in_normalized(bip39::Language::English, test_phrase).unwrap();
+
+        let seed1 = generate_seed(&mnemonic, "");
+        let seed2 = generate_seed(&mnemonic, "");
+        assert_eq!(seed1, seed2, "Same mnemonic should produce same seed");
+
+        let master_key1 = derive_master_key(&seed1, Network::Bitcoin).unwrap();
+        let master_key2 = derive_master_key(&seed2, Network::Bitcoin).unwrap();
+        assert_eq!(
+            master_key1.to_string(),
+            master_key2.to_string(),
+            "Same seed should produce same master key"
+        );
+    }
+
+    #[test]
+    fn test_file_generation() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path();
+
+        // Generate mnemonic and files
+        let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+        let seed = generate_seed(&mnemonic, "");
+        let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
+        let fingerprint = get_hardware_wallet_fingerprint(&master_key);
+
+        // Create files
+        let printable_content =
+            create_printable_output(&mnemonic, &fingerprint, "Test", Network::Bitcoin, &[]);
+        let printable_file = output_dir.join("seed_phrase_printable.txt");
+        fs::write(&printable_file, printable_content).unwrap();
+
+        let word_list = create_simple_word_list(&mnemonic);
+        let word_list_file = output_dir.join("seed_words_simple.txt");
+        fs::write(&word_list_file, word_list).unwrap();
+
+        let seed_words_file = output_dir.join("seed_words_for_coldcard.txt");
+        fs::write(&seed_words_file, create_coldcard_word_list(&mnemonic)).unwrap();
+
+        // Verify files exist and have content
+        assert!(printable_file.exists(), "Printable file should exist");
+        assert!(word_list_file.exists(), "Word list file should exist");
+        assert!(seed_words_file.exists(), "Seed words file should exist");
+
+        let printable_content = fs::read_to_string(&printable_file).unwrap();
+        assert!(
+            !printable_content.is_empty(),
+            "Printable file should not be empty"
+        );
+
+        let word_list_content = fs::read_to_string(&word_list_file).unwrap();
+        assert!(
+            !word_list_content.is_empty(),
+            "Word list file should not be empty"
+        );
+
+        let seed_words_content = fs::read_to_string(&seed_words_file).unwrap();
+        assert!(
+            !seed_words_content.is_empty(),
+            "Seed words file should not be empty"
+        );
+
+        // Verify seed words file has 24 lines
+        let lines: Vec<&str> = seed_words_content.lines().collect();
+        assert_eq!(lines.len(), 24, "Seed words file should have 24 lines");
+    }
+
+    #[test]
+    fn test_ensure_offline() {
+        // Mocked probes: only an online machine is blocked
+        let err = ensure_offline(|| true).unwrap_err();
+        assert!(matches!(err, KeygenError::Online));
+        assert!(err.to_string().contains("Network connection detected"));
+        assert!(ensure_offline(|| false).is_ok());
+
+        // The probe is not consulted more than needed and its answer is honoured
+        let calls = std::cell::Cell::new(0);
+        let probe = || {
+            calls.set(calls.get() + 1);
+            false
+        };
+        assert!(ensure_offline(probe).is_ok());
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn test_require_offline_before_any_mode() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path().join("output");
+        let xprv = "xprv9s21ZrQH143K2LBWUUQRFXhucrQqBpKdRRxNVq2zBqsx8HVqFk2uYo8kmbaLLHRdqtQpUm98uKfu3vca1LqdGhUtyoFnCNkfmXRyPXLjbKb";
+        for options in [
+            Options {
+                electrum: true,
+                ..Options::default()
+            },
+            Options {
+                from_xprv: Some(Zeroizing::new(xprv.to_string())),
+                ..Options::default()
+            },
+        ] {
+            let options = Options {
+                require_offline: true,
+                output_dir: output_dir.clone(),
+                ..options
+            };
+            // Online: refused before the write prompt, so nothing reads stdin
+            let err = dispatch(&options, || true).unwrap_err();
+            assert!(matches!(err, KeygenError::Online));
+            assert!(!output_dir.exists());
+        }
+
+        // Offline with --yes, --electrum goes ahead
+        let options = Options {
+            electrum: true,
+            require_offline: true,
+            yes: true,
+            output_dir: output_dir.clone(),
+            ..Options::default()
+        };
+        dispatch(&options, || false).unwrap();
+        assert!(output_dir.exists());
+    }
+
+    #[test]
+    fn test_no_write_creates_no_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path().join("output");
+
+        let options = Options {
+            no_write: true,
+            qr: true,
+            shamir: Some((2, 3)),
+            seed_xor: Some(2),
+            bip85: Some((12, 0)),
+            ..Options::default()
+        };
+        run(&options, &output_dir).unwrap();
+
+        assert!(!output_dir.exists(), "Dry run should not create any files");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+
+        // Same pipeline without the flag does write
+        let options = Options {
+            no_write: false,
+            summary_stderr: false,
+            require_offline: false,
+            pdf: false,
+            from_entropy: None,
+            show_entropy: false,
+            multisig: None,
+            derive: None,
+            count: None,
+            electrum: false,
+            encrypt: false,
+            decrypt: None,
+            verify_manifest: None,
+            wipe: None,
+            ..options
+        };
+        run(&options, &output_dir).unwrap();
+        assert!(output_dir.join("seed_phrase_printable.txt").exists());
+    }
+
+    #[test]
+    fn test_run_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path().join("output");
+        let options = Options {
+            word_count: 12,
+            count: Some(5),
+            ..Options::default()
+        };
+
+        let wallets = run_batch(&options, &output_dir, 5).unwrap();
+        let fingerprints: std::collections::HashSet<_> =
+            wallets.iter().map(|w| w.fingerprint.clone()).collect();
+        assert_eq!(fingerprints.len(), 5, "Each wallet should be independent");
+
+        let mut subdirs: Vec<_> = fs::read_dir(&output_dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        subdirs.sort();
+        assert_eq!(
+            subdirs,
+            vec![
+                "wallet_01",
+                "wallet_02",
+                "wallet_03",
+                "wallet_04",
+                "wallet_05"
+            ]
+        );
+        assert!(output_dir
+            .join("wallet_03")
+            .join("seed_phrase_printable.txt")
+            .exists());
+    }
+
+    #[test]
+    fn test_verify_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = OutputWriter::new(temp_dir.path(), false, None);
+        output.write("a.txt", "file a", b"alpha").unwrap();
+        output.write("b.txt", "file b", b"bravo").unwrap();
+        output
+            .write("c.png", "file c", &[0x89, b'P', b'N', b'G'])
+            .unwrap();
+        output.write_manifest().unwrap();
+
+        let manifest = fs::read_to_string(temp_dir.path().join(MANIFEST_FILE)).unwrap();
+        assert_eq!(manifest.lines().count(), 3);
+        assert!(manifest.contains(&format!("{}  a.txt", sha256::Hash::hash(b"alpha"))));
+        assert!(verify_manifest(temp_dir.path()).unwrap().is_empty());
+
+        // Corrupt exactly one file
+        fs::write(temp_dir.path().join("b.txt"), "brav0").unwrap();
+        assert_eq!(verify_manifest(temp_dir.path()).unwrap(), vec!["b.txt"]);
+        assert!(run_verify_manifest(temp_dir.path()).is_err());
+
+        // A missing file also fails verification
+        fs::remove_file(temp_dir.path().join("a.txt")).unwrap();
+        assert_eq!(
+            verify_manifest(temp_dir.path()).unwrap(),
+            vec!["a.txt", "b.txt"]
+        );
+    }
+
+    #[test]
+    fn test_wipe_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path().join("output");
+        fs::create_dir_all(output_dir.join("wallet_01")).unwrap();
+        fs::write(output_dir.join("seed_words_simple.txt"), "abandon about").unwrap();
+        fs::write(output_dir.join("empty.txt"), "").unwrap();
+        fs::write(
+            output_dir.join("wallet_01").join("seed_qr.png"),
+            vec![7u8; 100_000],
+        )
+        .unwrap();
+
+        // Files outside the directory are untouched even when linked from inside
+        let outside = temp_dir.path().join("keep.txt");
+        fs::write(&outside, "keep").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&outside, output_dir.join("link")).unwrap();
+
+        assert_eq!(wipe_directory(&output_dir).unwrap(), 3);
+        assert!(!output_dir.exists(), "Directory should be removed");
+        assert_eq!(fs::read_to_string(&outside).unwrap(), "keep");
+
+        assert!(wipe_directory(&output_dir).is_err());
+        assert!(wipe_directory(&outside).is_err(), "Refuses to wipe a file");
+    }
+
+    #[test]
+    fn test_fingerprint_format() {
+        // Generate multiple mnemonics and verify fingerprints are unique
+        let mut fingerprints = std::collections::HashSet::new();
+
+        for _ in 0..10 {
+            let mnemonic = generate_mnemonic(DEFAULT_WORD_COUNT, Language::English).unwrap();
+            let seed = generate_seed(&mnemonic, "");
+            let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
+            let fingerprint = get_hardware_wallet_fingerprint(&master_key);
+
+            // Verify format
+            assert_eq!(fingerprint.len(), 8);
+            assert!(fingerprint.chars().all(|c| c.is_ascii_hexdigit()));
+
+            fingerprints.insert(fingerprint);
+        }
+
+        // With high probability, all fingerprints should be unique
+        // (though collisions are possible, they're extremely rare)
+        assert!(
+            !fingerprints.is_empty(),
+            "Should generate at least one fingerprint"
+        );
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        let options = parse_args(args(&[])).unwrap();
+        assert_eq!(options.label, "Bitcoin Wallet");
+        assert_eq!(options.word_count, 24);
+        assert_eq!(options.network, Network::Bitcoin);
+
+        let options = parse_args(args(&["--words", "12", "My Wallet"])).unwrap();
+        assert_eq!(options.label, "My Wallet");
+        assert_eq!(options.word_count, 12);
+
+        let options = parse_args(args(&["--language", "chinese-simplified"])).unwrap();
+        assert_eq!(options.language, Language::SimplifiedChinese);
+        assert!(parse_args(args(&["--language", "klingon"])).is_err());
+
+        let options = parse_args(args(&["--network", "signet"])).unwrap();
+        assert_eq!(options.network, Network::Signet);
+
+        let options = parse_args(args(&["--shamir", "2-of-3"])).unwrap();
+        assert_eq!(options.shamir, Some((2, 3)));
+
+        let options = parse_args(args(&["--seed-xor", "3"])).unwrap();
+        assert_eq!(options.seed_xor, Some(3));
+        assert!(parse_args(args(&["--seed-xor", "1"])).is_err());
+
+        let options = parse_args(args(&["--passphrase"])).unwrap();
+        assert!(options.passphrase);
+
+        let options = parse_args(args(&["--passphrase-file", "/mnt/usb/pass"])).unwrap();
+        assert_eq!(
+            options.passphrase_file,
+            Some(PathBuf::from("/mnt/usb/pass"))
+        );
+        assert!(parse_args(args(&["--passphrase", "--passphrase-file", "p"])).is_err());
+
+        let options = parse_args(args(&["--dice"])).unwrap();
+        assert_eq!(options.entropy_source, EntropySource::Dice);
+
+        let options = parse_args(args(&["--coins"])).unwrap();
+        assert_eq!(options.entropy_source, EntropySource::Coins);
+        assert!(parse_args(args(&["--recover", "abandon about", "--coins"])).is_err());
+        assert!(parse_args(args(&["--dice", "--coins"])).is_err());
+
+        let options = parse_args(args(&["--qr", "--qr-ec", "H"])).unwrap();
+        assert!(options.qr);
+        assert_eq!(options.qr_ec_level, EcLevel::H);
+        assert!(
+            parse_args(args(&["--seedqr", "--words", "12"]))
+                .unwrap()
+                .seedqr
+        );
+        assert!(parse_args(args(&["--seedqr", "--words", "18"])).is_err());
+        assert!(parse_args(args(&["--bbqr"])).unwrap().bbqr);
+        assert!(parse_args(args(&["--verify-quiz"])).unwrap().verify_quiz);
+        assert_eq!(
+            parse_args(args(&["--instructions-lang", "ES"]))
+                .unwrap()
+                .instructions_lang,
+            InstructionLanguage::Spanish
+        );
+        assert!(parse_args(args(&["--instructions-lang", "pt"])).is_err());
+        let options = parse_args(args(&["--timestamp", "2024-01-01T12:30:00+02:00"])).unwrap();
+        assert_eq!(
+            options.timestamp.map(|t| format_timestamp(&t)).as_deref(),
+            Some("2024-01-01 12:30:00")
+        );
+        assert!(parse_args(args(&["--timestamp", "yesterday"])).is_err());
+
+        let hex32 = "11".repeat(32);
+        let options = parse_args(args(&["--mix-entropy", &hex32])).unwrap();
+        assert_eq!(options.mix_entropy.as_deref().map(Vec::len), Some(32));
+        assert!(parse_args(args(&["--mix-entropy", &hex32, "--words", "12"])).is_err());
+        assert!(parse_args(args(&["--mix-entropy", &"11".repeat(16), "--words", "12"])).is_ok());
+        assert!(parse_args(args(&["--mix-entropy", &hex32, "--dice"])).is_err());
+        assert!(parse_args(args(&["--verify-quiz", "--count", "2"])).is_err());
+
+        let options = parse_args(args(&[
+            "--preview-addresses",
+            "20",
+            "--script-type",
+            "p2tr",
+        ]));
+        let options = options.unwrap();
+        assert_eq!(options.preview_addresses, Some(20));
+        assert_eq!(options.script_type, 86);
+        assert_eq!(parse_args(args(&[])).unwrap().script_type, 84);
+        let error = parse_args(args(&["--preview-addresses", "0"]))
+            .err()
+            .unwrap();
+        assert!(error
+            .to_string()
+            .contains("Address preview count must be 1-1000, got 0"));
+        assert!(parse_args(args(&["--script-type", "p2wsh"])).is_err());
+
+        let options = parse_args(args(&["--recover", "abandon about"])).unwrap();
+        assert_eq!(
+            options.recover.as_deref().map(String::as_str),
+            Some("abandon about")
+        );
+        assert!(parse_args(args(&["--recover", "abandon about", "--dice"])).is_err());
+
+        let options = parse_args(args(&["--bip85", "18:2"])).unwrap();
+        assert_eq!(options.bip85, Some((18, 2)));
+
+        let options = parse_args(args(&["--duress-passphrase", "decoy"])).unwrap();
+        assert_eq!(
+            options.duress_passphrase.as_deref().map(String::as_str),
+            Some("decoy")
+        );
+        assert!(parse_args(args(&["--duress-passphrase", ""])).is_err());
+
+        assert!(
+            parse_args(args(&["--summary-stderr"]))
+                .unwrap()
+                .summary_stderr
+        );
+        assert!(parse_args(args(&["--no-write"])).unwrap().no_write);
+        assert!(parse_args(args(&["--stdout-only"])).unwrap().no_write);
+
+        let options = parse_args(args(&["--format", "json"])).unwrap();
+        assert_eq!(options.format, OutputFormat::Json);
+        assert!(parse_args(args(&["--format", "xml"])).is_err());
+
+        assert!(parse_args(args(&["--words", "13"])).is_err());
+        assert!(parse_args(args(&["--words"])).is_err());
+        assert!(parse_args(args(&["--bogus"])).is_err());
+    }
+
+    #[test]
+    fn test_error_variants() {
+        assert!(matches!(
+            generate_mnemonic(13, Language::English),
+            Err(KeygenError::InvalidWordCount(13))
+        ));
+        assert!(matches!(
+            parse_shamir_spec("3-of-2"),
+            Err(KeygenError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            parse_entropy_hex("abc"),
+            Err(KeygenError::Entropy(_))
+        ));
+        assert!(matches!(
+            recover_mnemonic("abandon abandon", Language::English),
+            Err(KeygenError::MnemonicParse(_))
+        ));
+        assert!(matches!(
+            parse_derivation_path("m/x"),
+            Err(KeygenError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_assess_passphrase_strength() {
+        assert_eq!(assess_passphrase_strength("hunter"), Strength::Weak);
+        assert_eq!(assess_passphrase_strength("password"), Strength::Weak);
+        assert_eq!(
+            assess_passphrase_strength("Correct-Horse-Battery-Staple-42"),
+            Strength::Strong
+        );
+        assert_eq!(
+            assess_passphrase_strength("Tr0ub4dor&3x"),
+            Strength::Moderate
+        );
+        assert_eq!(passphrase_entropy_bits(""), 0.0);
+    }
+
+    #[test]
+    fn test_seedqr_digits() {
+        // All-zero entropy: every word is "abandon" (0000) except the checksum word
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let digits = seedqr_digits(&mnemonic).unwrap();
+        assert_eq!(digits.len(), 48);
+        assert_eq!(digits.as_str(), format!("{}0003", "0000".repeat(11)));
+
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 32]).unwrap();
+        let digits = seedqr_digits(&mnemonic).unwrap();
+        assert_eq!(digits.len(), 96);
+        assert_eq!(digits.as_str(), format!("{}0102", "0000".repeat(23)));
+        let code = qrcode::QrCode::with_error_correction_level(digits.as_bytes(), EcLevel::L);
+        assert_eq!(code.unwrap().width(), 29);
+
+        // The PNG decodes back to the digit string
+        let png = render_seedqr_png(&mnemonic).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_luma8();
+        let mut prepared = rqrr::PreparedImage::prepare(image);
+        let grids = prepared.detect_grids();
+        assert_eq!(grids.len(), 1);
+        let (_, content) = grids[0].decode().unwrap();
+        assert_eq!(content, digits.as_str());
+
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 20]).unwrap();
+        assert!(seedqr_digits(&mnemonic).is_err());
+    }
+
+    #[test]
+    fn test_bbqr_frames() {
+        // RFC 4648 vectors, unpadded
+        assert_eq!(base32_encode(b""), "");
+        assert_eq!(base32_encode(b"f"), "MY");
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+
+        // 1000 bytes -> 1600 base32 chars -> 4 frames of 400
+        let payload = vec![0xa5u8; 1000];
+        let frames = bbqr_frames(&payload, BBQR_FRAME_CHARS).unwrap();
+        assert_eq!(frames.len(), 4);
+        for (i, frame) in frames.iter().enumerate() {
+            assert_eq!(&frame[..8], format!("B$2U040{}", i));
+            assert_eq!(frame.len(), 8 + 400);
+        }
+        let data: String = frames.iter().map(|f| &f[8..]).collect();
+        assert_eq!(data, base32_encode(&payload));
+
+        // Every frame fits in a scannable QR code
+        let png = render_qr_png(&frames[0], EcLevel::M).unwrap();
+        let image = image::load_from_memory(&png).unwrap().to_luma8();
+        let mut prepared = rqrr::PreparedImage::prepare(image);
+        let grids = prepared.detect_grids();
+        assert_eq!(grids[0].decode().unwrap().1, frames[0]);
+
+        // Uneven lengths are spread out in multiples of 8 characters
+        let frames = bbqr_frames(&[0u8; 265], BBQR_FRAME_CHARS).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].len() - 8, 216);
+        assert_eq!(frames[1].len() - 8, 208);
+
+        // A small payload is a single frame
+        assert_eq!(
+            bbqr_frames(b"xpub", BBQR_FRAME_CHARS).unwrap(),
+            ["B$2U0100PBYHKYQ"]
+        );
+    }
+
+    #[test]
+    fn test_preview_addresses() {
+        let mnemonic = Mnemonic::parse_in(
+            Language::English,
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+
+        // BIP84 test vectors for the first receive and change addresses
+        let receive = derive_chain_addresses(&master, Network::Bitcoin, 84, 0, 2).unwrap();
+        let change = derive_chain_addresses(&master, Network::Bitcoin, 84, 1, 2).unwrap();
+        assert_eq!(receive[0].0, "m/84'/0'/0'/0/0");
+        assert_eq!(receive[0].1, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+        assert_eq!(change[0].0, "m/84'/0'/0'/1/0");
+        assert_eq!(change[0].1, "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el");
+        assert_ne!(receive[0].1, change[0].1);
+
+        // The script-based addresses match the direct constructors
+        let addresses = derive_addresses(&master, Network::Bitcoin, 1).unwrap();
+        for (purpose, expected) in [84, 49, 44, 86].iter().zip(&addresses) {
+            let preview =
+                derive_chain_addresses(&master, Network::Bitcoin, *purpose, 0, 1).unwrap();
+            assert_eq!(&preview[0], expected);
+        }
+
+        let table = create_address_preview(84, &receive, &change);
+        assert!(table.contains("BIP84 p2wpkh"));
+        assert!(table.contains("  m/84'/0'/0'/1/1  bc1q"));
+        assert!(table.contains("Receive:") && table.contains("Change:"));
+    }
+
+    #[test]
+    fn test_verify_quiz() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let words: Vec<&str> = mnemonic.words().collect();
+
+        // Correct answers, ignoring case and whitespace
+        let mut input = "abandon\n  ABOUT \n".as_bytes();
+        assert_eq!(verify_quiz(&words, &[3, 12], &mut input).unwrap(), 2);
+
+        // One wrong word, one missing answer (EOF)
+        let mut input = "about\n".as_bytes();
+        assert_eq!(verify_quiz(&words, &[1, 12], &mut input).unwrap(), 0);
+        let mut input = "abandon\nabandon\n".as_bytes();
+        assert_eq!(verify_quiz(&words, &[1, 12], &mut input).unwrap(), 1);
+
+        // Wordlists are NFKD: a precomposed (NFC) accented answer still matches
+        let academie = "acade\u{301}mie";
+        assert!(Language::French.word_list().contains(&academie));
+        let mut input = "Acad\u{e9}mie\n".as_bytes();
+        assert_eq!(verify_quiz(&[academie], &[1], &mut input).unwrap(), 1);
+        let mut input = "academie\n".as_bytes();
+        assert_eq!(verify_quiz(&[academie], &[1], &mut input).unwrap(), 0);
+
+        let positions = quiz_positions(24, QUIZ_QUESTIONS).unwrap();
+        assert_eq!(positions.len(), QUIZ_QUESTIONS);
+        assert!(positions.iter().all(|p| (1..=24).contains(p)));
+        let mut unique = positions.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), positions.len());
+        assert_eq!(quiz_positions(3, 5).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_localized_printable_output() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let output = create_localized_printable_output(
+            &mnemonic,
+            "73c5da0a",
+            "Cartera",
+            Network::Bitcoin,
+            &[],
+            &PrintableOptions {
+                language: InstructionLanguage::Spanish,
+                ..PrintableOptions::default()
+            },
+            &now_timestamp(),
+        );
+        assert!(output.contains("ADVERTENCIA DE SEGURIDAD"));
+        assert!(output.contains("□ Las 12 palabras son claramente legibles"));
+        assert!(output.contains("5. Compruebe que la huella coincide: 73c5da0a"));
+        assert!(!output.contains("SECURITY WARNING"));
+        // The seed words are not translated
+        assert!(output.contains(&format_word_grid(&mnemonic.words().collect::<Vec<_>>())));
+
+        for language in [
+            InstructionLanguage::Spanish,
+            InstructionLanguage::French,
+            InstructionLanguage::German,
+            InstructionLanguage::Japanese,
+        ] {
+            let output = create_localized_printable_output(
+                &mnemonic,
+                "73c5da0a",
+                "Wallet",
+                Network::Bitcoin,
+                &[],
+                &PrintableOptions {
+                    language,
+                    phonetic: true,
+                    ..PrintableOptions::default()
+                },
+                &now_timestamp(),
+            );
+            assert!(!output.contains("SECURITY WARNING"));
+            assert!(!output.contains("PHONETIC SPELLING"));
+            assert!(output.contains("Alpha Bravo Alpha November Delta Oscar November"));
+            assert!(!output.contains("{n}") && !output.contains("{fp}"));
+            assert!(output.contains("73c5da0a") && output.contains("12"));
+        }
+    }
+
+    #[test]
+    fn test_printable_output_fixture() {
+        // Fixed entropy + fixed timestamp = byte-for-byte reproducible output
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        let fingerprint = get_hardware_wallet_fingerprint(&master);
+        let addresses = derive_addresses(&master, Network::Bitcoin, ADDRESS_PREVIEW_COUNT).unwrap();
+        let generated = parse_timestamp("2024-01-01T00:00:00Z").unwrap();
+
+        let output = create_localized_printable_output(
+            &mnemonic,
+            &fingerprint,
+            "Fixture Wallet",
+            Network::Bitcoin,
+            &addresses,
+            &PrintableOptions::default(),
+            &generated,
+        );
+        assert_eq!(
+            output,
+            include_str!("../tests/fixtures/printable_abandon_12.txt")
+        );
+    }
+
+    #[test]
+    fn test_mix_entropy() {
+        let mut random = [0u8; 32];
+        getrandom::fill(&mut random).unwrap();
+
+        // All-zero user entropy leaves the RNG output unchanged
+        let mixed = mix_entropy(&random, &[0u8; 32]).unwrap();
+        assert_eq!(mixed.as_slice(), random);
+        assert_eq!(
+            mnemonic_from_entropy(mixed, Language::English).unwrap(),
+            Mnemonic::from_entropy(&random).unwrap()
+        );
+
+        // Anything else changes it, and mixing twice undoes it
+        let supplied = [0x5au8; 32];
+        let mixed = mix_entropy(&random, &supplied).unwrap();
+        assert_ne!(mixed.as_slice(), random);
+        assert_eq!(mix_entropy(&mixed, &supplied).unwrap().as_slice(), random);
+
+        assert!(mix_entropy(&random, &[0u8; 16]).is_err());
+        let mnemonic = generate_mixed_mnemonic(12, Language::English, &[0xffu8; 16]).unwrap();
+        assert_eq!(mnemonic.word_count(), 12);
+    }
+
+    #[test]
+    fn test_account_fingerprint() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        let path = account_derivation_path(Network::Bitcoin, 84, 0).unwrap();
+
+        let account = account_fingerprint(&master, &path).unwrap();
+        assert_eq!(account, "fd13aac9");
+        assert_ne!(account, get_hardware_wallet_fingerprint(&master));
+
+        // The account xpub's parent fingerprint is that of m/84'/0'
+        let xpub = derive_account_xpub(&master, Network::Bitcoin, 84, 0).unwrap();
+        assert_eq!(xpub.parent_fingerprint.to_string(), "7ef32bdb");
+        let parent = parse_derivation_path("m/84'/0'").unwrap();
+        assert_eq!(account_fingerprint(&master, &parent).unwrap(), "7ef32bdb");
+    }
+
+    #[test]
+    fn test_passphrase_normalization() {
+        let composed = "caf\u{e9}";
+        let decomposed = "cafe\u{301}";
+        assert!(passphrase_needs_normalization(composed));
+        assert!(!passphrase_needs_normalization(decomposed));
+        assert!(!passphrase_needs_normalization("plain ascii passphrase"));
+        // Compatibility forms (full-width letters, ligatures) also differ under NFKD
+        assert!(passphrase_needs_normalization("\u{ff21}\u{fb01}"));
+
+        // bip39 normalizes before hashing, so both spellings give the same seed
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        assert_eq!(
+            *generate_seed(&mnemonic, composed),
+            *generate_seed(&mnemonic, decomposed)
+        );
+    }
+
+    #[test]
+    fn test_create_paper_wallet() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        let xpub = derive_account_xpub(&master, Network::Bitcoin, 84, 0).unwrap();
+
+        let output = create_paper_wallet(&mnemonic, &master, Network::Bitcoin).unwrap();
+        assert!(output.contains("Fingerprint: 73c5da0a"));
+        assert!(output.contains("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"));
+        assert!(output.contains(&xpub.to_string()));
+        assert!(output.contains("12. about"));
+        assert!(output.contains('#'), "Should contain the ASCII QR code");
+
+        // Written by a full run (built before the master key is erased)
+        let temp_dir = TempDir::new().unwrap();
+        let options = parse_args([
+            "--recover".to_string(),
+            format!("{}about", "abandon ".repeat(11)),
+        ])
+        .unwrap();
+        let output_dir = temp_dir.path().join("output");
+        run(&options, &output_dir).unwrap();
+        let paper_wallet = fs::read_to_string(output_dir.join("paper_wallet.txt")).unwrap();
+        assert!(paper_wallet.contains("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"));
+        assert!(paper_wallet.contains(&xpub.to_string()));
+    }
+
+    #[test]
+    fn test_config_file_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path().join("from-config");
+        let config_path = temp_dir.path().join("keygen.toml");
+        fs::write(
+            &config_path,
+            format!(
+                "network = \"testnet\"\nwords = 12\nscript-type = \"p2tr\"\noutput-dir = {:?}\n",
+                output_dir.to_str().unwrap()
+            ),
+        )
+        .unwrap();
+        let config_arg = config_path.to_str().unwrap().to_string();
+
+        let options = parse_args(vec!["--config".to_string(), config_arg.clone()]).unwrap();
+        assert_eq!(options.network, Network::Testnet);
+        assert_eq!(options.word_count, 12);
+        assert_eq!(options.script_type, 86);
+        assert_eq!(options.output_dir, output_dir);
+
+        let keys = run(&options, &options.output_dir).unwrap();
+        assert!(keys.account_xpub.starts_with("tpub"));
+        assert!(keys.first_address.starts_with("tb1q"));
+        let words = fs::read_to_string(output_dir.join("seed_words_for_coldcard.txt")).unwrap();
+        assert_eq!(words.split_whitespace().count(), 12);
+
+        // Flags win over the config regardless of their position
+        let args = [
+            "--words",
+            "24",
+            "--config",
+            &config_arg,
+            "--network",
+            "signet",
+        ];
+        let options = parse_args(args.iter().map(|s| s.to_string())).unwrap();
+        assert_eq!(options.word_count, 24);
+        assert_eq!(options.network, Network::Signet);
+
+        // Unknown keys and invalid values are rejected
+        fs::write(&config_path, "wrds = 12\n").unwrap();
+        assert!(parse_args(vec!["--config".to_string(), config_arg.clone()]).is_err());
+        fs::write(&config_path, "words = 13\n").unwrap();
+        assert!(parse_args(vec!["--config".to_string(), config_arg]).is_err());
+    }
+
+    #[test]
+    fn test_sign_message() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        let message = "I control this address";
+
+        let (address, signature) = sign_message(&master, Network::Bitcoin, message).unwrap();
+        assert_eq!(address, "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu");
+        let header =
+            bitcoin::base64::Engine::decode(&bitcoin::base64::prelude::BASE64_STANDARD, &signature)
+                .unwrap()[0];
+        assert!((39..=42).contains(&header), "BIP137 P2WPKH header");
+
+        assert!(verify_message(&address, message, &signature).unwrap());
+        assert!(!verify_message(&address, "I control that address", &signature).unwrap());
+        assert!(!verify_message(
+            "bc1q8c6fshw2dlwun7ekn9qwf37cu2rn755upcp6el",
+            message,
+            &signature
+        )
+        .unwrap());
+        assert!(verify_message(&address, message, "not base64!").is_err());
+
+        // RFC 6979 nonces make the signature deterministic
+        assert_eq!(
+            sign_message(&master, Network::Bitcoin, message).unwrap().1,
+            signature
+        );
+    }
+
+    #[test]
+    fn test_descriptor_checksum() {
+        // BIP380 test vector
+        assert_eq!(descriptor_checksum("raw(deadbeef)").unwrap(), "89f8spxm");
+        assert!(descriptor_checksum("raw(deadbeef)\u{e9}").is_err());
+
+        // Bitcoin Core descriptor_tests.cpp: private and public forms of the
+        // same sh(multi()) have their own checksums
+        assert_eq!(
+            descriptor_checksum(
+                "sh(multi(2,[00000000/111'/222]xprvA1RpRA33e1JQ7ifknakTFpgNXPmW2YvmhqLQYMmrj4xJXXWYpDPS3xz7iAxn8L39njGVyuoseXzU6rcxFLJ8HFsTjSyQbLYnMpCqE2VbFWc,xprv9uPDJpEQgRQfDcW7BkF7eTya6RPxXeJCqCJGHuCJ4GiRVLzkTXBAJMu2qaMWPrS7AANYqdq6vcBcBUdJCVVFceUvJFjaPdGZ2y9WACViL4L/0))"
+            )
+            .unwrap(),
+            "ggrsrxfy"
+        );
+        assert_eq!(
+            descriptor_checksum(
+                "sh(multi(2,[00000000/111'/222]xpub6ERApfZwUNrhLCkDtcHTcxd75RbzS1ed54G1LkBUHQVHQKqhMkhgbmJbZRkrgZw4koxb5JaHWkY4ALHY2grBGRjaDMzQLcgJvLJuZZvRcEL,xpub68NZiKmJWnxxS6aaHmn81bvJeTESw724CRDs6HbuccFQN9Ku14VQrADWgqbhhTHBaohPX4CjNLf9fq9MYo6oDaPPLPxSb7gwQN3ih19Zm4Y/0))"
+            )
+            .unwrap(),
+            "tjg09x5t"
+        );
+    }
+
+    #[test]
+    fn test_create_core_import_json() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        let xpub = derive_account_xpub(&master, Network::Bitcoin, 84, 0).unwrap();
+
+        let json = create_core_import_json(&master, Network::Bitcoin).unwrap();
+        let requests: Vec<CoreImportDescriptor> = serde_json::from_str(&json).unwrap();
+        assert_eq!(requests.len(), 2);
+
+        let (receive, change) = (&requests[0], &requests[1]);
+        assert!(!receive.internal);
+        assert!(change.internal);
+        for (request, chain) in [(receive, 0), (change, 1)] {
+            let (descriptor, checksum) = request.desc.split_once('#').unwrap();
+            assert_eq!(
+                descriptor,
+                format!("wpkh([73c5da0a/84h/0h/0h]{}/{}/*)", xpub, chain)
+            );
+            assert_eq!(checksum, descriptor_checksum(descriptor).unwrap());
+            assert!(request.active);
+            assert_eq!(request.range, CORE_IMPORT_RANGE);
+            assert_eq!(request.timestamp, "now");
+        }
+    }
+
+    #[test]
+    fn test_write_raw_seed() {
+        let options = parse_args([
+            "--from-entropy".to_string(),
+            "00".repeat(16),
+            "--raw-seed".to_string(),
+            "--raw-seed-hex".to_string(),
+        ])
+        .unwrap();
+        assert!(options.raw_seed && options.raw_seed_hex);
+        let mnemonic =
+            mnemonic_from_entropy(options.from_entropy.unwrap(), Language::English).unwrap();
+        let seed = generate_seed(&mnemonic, "TREZOR");
+
+        let temp_dir = TempDir::new().unwrap();
+        let output = OutputWriter::new(temp_dir.path(), false, None);
+        write_raw_seed(&output, &seed, options.raw_seed, options.raw_seed_hex).unwrap();
+
+        // BIP39 reference vector: all-zero entropy with passphrase "TREZOR"
+        let expected = "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e5349553\
+                        1f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04";
+        let binary = fs::read(temp_dir.path().join("seed.bin")).unwrap();
+        assert_eq!(binary.len(), 64);
+        assert_eq!(binary, seed.to_vec());
+        let hex = fs::read_to_string(temp_dir.path().join("seed.hex")).unwrap();
+        assert_eq!(hex.trim_end(), expected);
+    }
+
+    #[test]
+    fn test_match_descriptor() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        let xpub = derive_account_xpub(&master, Network::Bitcoin, 84, 0).unwrap();
+        let descriptor = format!("wpkh([73c5da0a/84'/0'/0']{}/0/*)", xpub);
+
+        let parsed = parse_descriptor(&descriptor).unwrap();
+        assert_eq!(parsed.purpose, 84);
+        assert_eq!(parsed.origin.to_string(), "84'/0'/0'");
+        assert_eq!(parsed.chain, 0);
+
+        let result = match_descriptor(&master, Network::Bitcoin, &descriptor).unwrap();
+        assert!(result.fingerprint_matches());
+        assert!(result.address_matches());
+        assert_eq!(
+            result.seed_address,
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+
+        // Checksummed descriptors from build_descriptors / --core-import work too
+        for descriptor in build_descriptors(&master, Network::Bitcoin).unwrap() {
+            let result = match_descriptor(&master, Network::Bitcoin, &descriptor).unwrap();
+            assert!(result.fingerprint_matches() && result.address_matches());
+        }
+        assert!(parse_descriptor(&format!("{}#qqqqqqqq", descriptor)).is_err());
+
+        // A different passphrase is a different wallet
+        let other = derive_master_key(&generate_seed(&mnemonic, "x"), Network::Bitcoin).unwrap();
+        let result = match_descriptor(&other, Network::Bitcoin, &descriptor).unwrap();
+        assert!(!result.fingerprint_matches());
+        assert!(!result.address_matches());
+
+        assert!(parse_descriptor(&format!("wpkh({}/0/*)", xpub)).is_err());
+        assert!(parse_descriptor("wsh(multi(1,xpub))").is_err());
+    }
+
+    #[test]
+    fn test_time_derivation() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        assert!(time_seed_derivation(&mnemonic, "") > Duration::ZERO);
+
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        assert!(time_address_derivation(&master, Network::Bitcoin, 1).unwrap() > Duration::ZERO);
+        assert_eq!(format_millis(Duration::from_micros(12_345)), "12.3 ms");
+    }
+
+    #[test]
+    fn test_verify_seed_derivation() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        assert!(verify_seed_derivation(&mnemonic, ""));
+        assert!(verify_seed_derivation(&mnemonic, "TREZOR"));
+
+        // Non-ASCII words and passphrase exercise the NFKD step
+        let japanese = Mnemonic::from_entropy_in(Language::Japanese, &[0x7f; 16]).unwrap();
+        assert!(verify_seed_derivation(
+            &japanese,
+            "㍍ガバヴァぱばぐゞちぢ十人十色"
+        ));
+    }
+
+    #[test]
+    fn test_create_coldcard_export() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+
+        let json = create_coldcard_export(&master, Network::Bitcoin).unwrap();
+        let export: ColdcardExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(export.xfp, "73C5DA0A");
+        assert_eq!(export.chain, "BTC");
+        for (section, purpose) in [
+            (&export.bip44, 44),
+            (&export.bip49, 49),
+            (&export.bip84, 84),
+        ] {
+            assert_eq!(section.deriv, format!("m/{}'/0'/0'", purpose));
+            assert_eq!(
+                section.xpub,
+                derive_account_xpub(&master, Network::Bitcoin, purpose, 0)
+                    .unwrap()
+                    .to_string()
+            );
+        }
+        assert_eq!(
+            export.bip84.first,
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        assert!(export.bip84.slip132.as_deref().unwrap().starts_with("zpub"));
+        assert!(export.bip49.slip132.as_deref().unwrap().starts_with("ypub"));
+        assert!(export.bip44.slip132.is_none());
+        assert_eq!(export.bip49.name, "p2wpkh-p2sh");
+        assert!(!json.contains("bip86"));
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert!(value["bip84"]["_pub"].is_string());
+        let testnet = derive_master_key(&generate_seed(&mnemonic, ""), Network::Testnet).unwrap();
+        let export: ColdcardExport =
+            serde_json::from_str(&create_coldcard_export(&testnet, Network::Testnet).unwrap())
+                .unwrap();
+        assert_eq!(export.chain, "XTN");
+        assert_eq!(export.bip84.deriv, "m/84'/1'/0'");
+    }
+
+    #[test]
+    fn test_create_sparrow_export() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+
+        let json = create_sparrow_export(&master, Network::Bitcoin, "Cold Storage").unwrap();
+        assert!(json.contains("\"masterFingerprint\": \"73C5DA0A\""));
+        assert!(json.contains("\"derivationPath\": \"m/84'/0'/0'\""));
+        let export: SparrowExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(export.label, "Cold Storage");
+        assert_eq!(export.script_type, "P2WPKH");
+        assert_eq!(export.extended_public_key, "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V");
+
+        let testnet = derive_master_key(&generate_seed(&mnemonic, ""), Network::Testnet).unwrap();
+        let export: SparrowExport =
+            serde_json::from_str(&create_sparrow_export(&testnet, Network::Testnet, "T").unwrap())
+                .unwrap();
+        assert_eq!(export.derivation_path, "m/84'/1'/0'");
+        assert!(export.extended_public_key.starts_with("tpub"));
+    }
+
+    #[test]
+    fn test_scripts_selection() {
+        let temp_dir = TempDir::new().unwrap();
+        let options = parse_args(
+            ["--scripts", "bip84", "--from-entropy", &"00".repeat(16)]
+                .iter()
+                .map(|s| s.to_string()),
+        )
+        .unwrap();
+        assert_eq!(options.scripts, vec![84]);
+        run(&options, temp_dir.path()).unwrap();
+
+        let printable =
+            fs::read_to_string(temp_dir.path().join("seed_phrase_printable.txt")).unwrap();
+        assert!(printable.contains("bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"));
+        assert!(
+            !printable
+                .split_whitespace()
+                .any(|token| token.starts_with('1') && token.len() >= 26),
+            "No legacy P2PKH address expected"
+        );
+        assert!(!printable.contains("bc1p"));
+
+        let descriptors = fs::read_to_string(temp_dir.path().join("descriptors.txt")).unwrap();
+        assert_eq!(
+            descriptors.lines().filter(|l| !l.starts_with('#')).count(),
+            1
+        );
+        assert!(descriptors.contains("wpkh(["));
+        let accounts = fs::read_to_string(temp_dir.path().join("accounts.txt")).unwrap();
+        assert!(accounts.contains("m/84'/0'/0'"));
+        assert!(!accounts.contains("m/44'/0'/0'"));
+        assert!(temp_dir.path().join("account_xpub.txt").exists());
+        assert!(temp_dir.path().join("paper_wallet.txt").exists());
+
+        // Without bip84 the BIP84 account xpub and paper wallet are not written
+        let taproot_dir = temp_dir.path().join("taproot");
+        let options = parse_args(
+            ["--scripts", "bip86", "--from-entropy", &"00".repeat(16)].map(String::from),
+        )
+        .unwrap();
+        run(&options, &taproot_dir).unwrap();
+        assert!(!taproot_dir.join("account_xpub.txt").exists());
+        assert!(!taproot_dir.join("paper_wallet.txt").exists());
+        let accounts = fs::read_to_string(taproot_dir.join("accounts.txt")).unwrap();
+        assert!(accounts.contains("m/86'/0'/0'"));
+        assert!(!accounts.contains("zpub"));
+        // and the BIP84-only exports are rejected
+        for flag in [
+            "--sparrow-export",
+            "--coldcard-export",
+            "--first-address-watchonly",
+            "--ur-hdkey",
+        ] {
+            let err = parse_args(["--scripts", "bip86", flag].map(String::from))
+                .err()
+                .unwrap();
+            assert!(
+                err.to_string().contains("add bip84 to --scripts"),
+                "{}",
+                flag
+            );
+        }
+        assert!(
+            parse_args(["--scripts", "bip84,bip86", "--sparrow-export"].map(String::from)).is_ok()
+        );
+
+        // Canonical order, duplicates dropped, names validated
+        let parsed = parse_args(["--scripts".to_string(), "p2tr,bip84,p2wpkh".to_string()]);
+        assert_eq!(parsed.unwrap().scripts, vec![84, 86]);
+        assert!(parse_args(["--scripts".to_string(), "bip99".to_string()]).is_err());
+        assert!(parse_args(["--scripts".to_string(), ",".to_string()]).is_err());
+        assert_eq!(Options::default().scripts, ALL_PURPOSES.to_vec());
+    }
+
+    #[test]
+    fn test_rng_health_check() {
+        // A stuck RNG returns the same buffer every time
+        let stuck = rng_health_check(|buf| {
+            buf.fill(0x42);
+            Ok(())
+        });
+        assert!(matches!(stuck, Err(KeygenError::Entropy(_))));
+
+        let mut counter = 0u8;
+        let working = rng_health_check(|buf| {
+            counter += 1;
+            buf.fill(counter);
+            Ok(())
+        });
+        assert!(working.is_ok());
+        assert_eq!(counter, 2);
+
+        assert!(rng_health_check(|buf| Ok(getrandom::fill(buf)?)).is_ok());
+        let failing = rng_health_check(|_| Err(KeygenError::Entropy("no RNG".to_string())));
+        assert!(failing.is_err());
+    }
+
+    #[test]
+    fn test_recover_file_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mnemonic = generate_mnemonic(24, Language::English).unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        let fingerprint = get_hardware_wallet_fingerprint(&master);
+
+        for (name, contents) in [
+            ("seed_words_simple.txt", create_simple_word_list(&mnemonic)),
+            (
+                "seed_words_for_coldcard.txt",
+                create_coldcard_word_list(&mnemonic),
+            ),
+        ] {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, contents).unwrap();
+            let options = parse_args([
+                "--recover-file".to_string(),
+                path.to_str().unwrap().to_string(),
+                "--no-write".to_string(),
+            ])
+            .unwrap();
+            assert_eq!(
+                options.recover.as_deref().map(String::as_str),
+                Some(mnemonic.to_string().as_str())
+            );
+            let keys = run(&options, &temp_dir.path().join("output")).unwrap();
+            assert_eq!(keys.fingerprint, fingerprint);
+        }
+
+        assert_eq!(
+            phrase_from_word_list("# backup\n 1. abandon\n2) ability 3:able\n\nabout").as_str(),
+            "abandon ability able about"
+        );
+        let both = ["--recover", "abandon", "--recover-file", "words.txt"];
+        assert!(parse_args(both.iter().map(|s| s.to_string())).is_err());
+    }
+
+    #[test]
+    fn test_ascii_output() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 32]).unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        let addresses = derive_addresses(&master, Network::Bitcoin, ADDRESS_PREVIEW_COUNT).unwrap();
+        let fingerprint = get_hardware_wallet_fingerprint(&master);
+
+        let printable = create_printable_output(
+            &mnemonic,
+            &fingerprint,
+            "Wallet",
+            Network::Bitcoin,
+            &addresses,
+        );
+        assert!(!printable.is_ascii(), "Default output uses box drawing");
+        let ascii = ascii_decorations(&printable);
+        assert!(
+            ascii.is_ascii(),
+            "ASCII output must contain only ASCII bytes"
+        );
+        assert!(ascii.contains("=========="));
+        assert!(ascii.contains("[ ] "));
+        assert!(ascii.contains("!  SECURITY WARNING !"));
+        assert!(ascii.contains(&fingerprint));
+
+        let paper = create_paper_wallet(&mnemonic, &master, Network::Bitcoin).unwrap();
+        assert!(ascii_decorations(&paper).is_ascii());
+        assert_eq!(ascii_decorations("✓ Created ─ ✗"), "OK Created - X");
+
+        // The writer converts text files only when ASCII mode is on
+        let temp_dir = TempDir::new().unwrap();
+        let output = OutputWriter::new(temp_dir.path(), false, None).with_ascii(true);
+        output
+            .write("printable.txt", "printable", printable.as_bytes())
+            .unwrap();
+        output
+            .write("wallet.json", "json", "\"═\"".as_bytes())
+            .unwrap();
+        assert!(fs::read(temp_dir.path().join("printable.txt"))
+            .unwrap()
+            .is_ascii());
+        assert!(!fs::read(temp_dir.path().join("wallet.json"))
+            .unwrap()
+            .is_ascii());
+
+        let options = parse_args(["--ascii".to_string()]).unwrap();
+        assert!(options.ascii);
+    }
+
+    #[test]
+    fn test_passphrase_required_printable() {
+        let temp_dir = TempDir::new().unwrap();
+        let passphrase = "Tr0ub4dor&3-xyzzy";
+        let passphrase_path = temp_dir.path().join("passphrase.txt");
+        fs::write(&passphrase_path, format!("{}\n", passphrase)).unwrap();
+
+        let options = parse_args([
+            "--from-entropy".to_string(),
+            "00".repeat(16),
+            "--passphrase-file".to_string(),
+            passphrase_path.to_str().unwrap().to_string(),
+            "--passphrase-required".to_string(),
+        ])
+        .unwrap();
+        assert!(!options.passphrase, "A passphrase file replaces the prompt");
+        let output_dir = temp_dir.path().join("output");
+        let keys = run(&options, &output_dir).unwrap();
+        assert_ne!(keys.fingerprint, "73c5da0a");
+
+        let printable = fs::read_to_string(output_dir.join("seed_phrase_printable.txt")).unwrap();
+        assert!(printable.contains("*** PASSPHRASE REQUIRED ***"));
+        assert!(printable.contains(&format!(
+            "Fingerprint WITH passphrase: {}",
+            keys.fingerprint
+        )));
+        assert!(!printable.contains(passphrase));
+        assert!(!printable.contains("Tr0ub4dor"));
+        // The notice follows the metadata block, before the security warning
+        assert!(
+            printable.find("Network: ").unwrap() < printable.find("PASSPHRASE REQUIRED").unwrap()
+        );
+        assert!(
+            printable.find("PASSPHRASE REQUIRED").unwrap()
+                < printable.find("SECURITY WARNING").unwrap()
+        );
+
+        // The notice follows --instructions-lang like the rest of the sheet
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let japanese = create_localized_printable_output(
+            &mnemonic,
+            &keys.fingerprint,
+            "Test",
+            Network::Bitcoin,
+            &[],
+            &PrintableOptions {
+                language: InstructionLanguage::Japanese,
+                passphrase_required: true,
+                ..PrintableOptions::default()
+            },
+            &now_timestamp(),
+        );
+        assert!(japanese.contains("*** パスフレーズが必要です ***"));
+        assert!(japanese.contains(&format!(
+            "パスフレーズ込みのフィンガープリント: {}",
+            keys.fingerprint
+        )));
+        assert!(!japanese.contains("PASSPHRASE REQUIRED"));
+
+        // The seed card and the PDF carry the notice too
+        let card = create_seed_card(
+            &mnemonic,
+            &keys.fingerprint,
+            "Card",
+            Network::Bitcoin,
+            &now_timestamp(),
+            true,
+        );
+        assert!(card.contains("*** PASSPHRASE REQUIRED ***"));
+        assert!(card.find("PASSPHRASE REQUIRED").unwrap() < card.find("FOLD HERE").unwrap());
+        let plain = create_printable_pdf(
+            &mnemonic,
+            &keys.fingerprint,
+            "Test",
+            Network::Bitcoin,
+            &now_timestamp(),
+            false,
+        )
+        .unwrap();
+        let pdf = create_printable_pdf(
+            &mnemonic,
+            &keys.fingerprint,
+            "Test",
+            Network::Bitcoin,
+            &now_timestamp(),
+            true,
+        )
+        .unwrap();
+        assert!(pdf.len() > plain.len());
+
+        // Without a file the flag prompts for the passphrase
+        let options = parse_args(["--passphrase-required".to_string()]).unwrap();
+        assert!(options.passphrase && options.passphrase_required);
+    }
+
+    #[test]
+    fn test_lightning_node_key() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+
+        let lightning = lightning_node_key(&master, Network::Bitcoin).unwrap();
+        assert_eq!(lightning.path.to_string(), "1017'/0'/6'");
+        let bip84 = account_fingerprint(&master, &"m/84'/0'/0'".parse().unwrap()).unwrap();
+        assert_eq!(bip84, "fd13aac9");
+        assert_ne!(lightning.fingerprint, bip84);
+        assert_ne!(
+            lightning.fingerprint,
+            get_hardware_wallet_fingerprint(&master)
+        );
+        assert_eq!(
+            lightning.fingerprint,
+            account_fingerprint(&master, &lightning.path).unwrap()
+        );
+        assert_eq!(lightning.node_pubkey.len(), 66);
+
+        let testnet = derive_master_key(&generate_seed(&mnemonic, ""), Network::Testnet).unwrap();
+        let testnet = lightning_node_key(&testnet, Network::Testnet).unwrap();
+        assert_eq!(testnet.path.to_string(), "1017'/1'/6'");
+    }
+
+    #[test]
+    fn test_last_word_candidates() {
+        let partial = ["abandon"; 23].join(" ");
+        let candidates = last_word_candidates(&partial, Language::English).unwrap();
+        assert_eq!(candidates.len(), 8);
+        assert!(candidates.contains(&"art"));
+        for word in &candidates {
+            let phrase = format!("{} {}", partial, word);
+            assert!(
+                recover_mnemonic(&phrase, Language::English).is_ok(),
+                "{}",
+                word
+            );
+        }
+
+        // 12 words: 7 free entropy bits in the last word
+        let partial = ["abandon"; 11].join(" ");
+        let candidates = last_word_candidates(&partial, Language::English).unwrap();
+        assert_eq!(candidates.len(), 128);
+        assert!(candidates.contains(&"about"));
+
+        assert!(last_word_candidates("abandon abandon", Language::English).is_err());
+        let typo = format!("{} abandom", ["abandon"; 22].join(" "));
+        let error = last_word_candidates(&typo, Language::English).unwrap_err();
+        assert!(error.to_string().contains("did you mean: abandon"));
+    }
+
+    #[test]
+    fn test_age_recipient_round_trip() {
+        let identity = age::x25519::Identity::generate();
+        let recipient = identity.to_public();
+
+        let payload = b"abandon abandon about";
+        let encrypted = encrypt_to_age(payload, &recipient).unwrap();
+        assert!(encrypted.starts_with(b"age-encryption.org/v1"));
+        assert!(!encrypted.windows(7).any(|window| window == b"abandon"));
+        assert_eq!(&decrypt_age(&encrypted, &identity).unwrap()[..], payload);
+        let other = age::x25519::Identity::generate();
+        assert!(decrypt_age(&encrypted, &other).is_err());
+
+        let temp_dir = TempDir::new().unwrap();
+        let options = parse_args([
+            "--from-entropy".to_string(),
+            "00".repeat(16),
+            "--age-recipient".to_string(),
+            recipient.to_string(),
+        ])
+        .unwrap();
+        let output_dir = temp_dir.path().join("output");
+        run(&options, &output_dir).unwrap();
+
+        let decrypted =
+            decrypt_age(&fs::read(output_dir.join("wallet.age")).unwrap(), &identity).unwrap();
+        let decrypted = std::str::from_utf8(&decrypted).unwrap();
+        let printable = fs::read_to_string(output_dir.join("seed_phrase_printable.txt")).unwrap();
+        let words = fs::read_to_string(output_dir.join("seed_words_simple.txt")).unwrap();
+        let xpub = fs::read_to_string(output_dir.join("account_xpub.txt")).unwrap();
+        assert_eq!(decrypted, format!("{}\n{}\n{}", printable, words, xpub));
+
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(parse_args(args(&["--age-recipient", "age1notakey"])).is_err());
+        assert!(parse_args(args(&[
+            "--age-recipient",
+            &recipient.to_string(),
+            "--no-write"
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn test_create_ur_hdkey() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        let ur_string = create_ur_hdkey(&master, Network::Bitcoin).unwrap();
+        assert!(ur_string.starts_with("ur:crypto-hdkey/"));
+
+        let (kind, cbor) = ur::decode(&ur_string).unwrap();
+        assert_eq!(kind, ur::ur::Kind::SinglePart);
+        let mut decoder = minicbor::Decoder::new(&cbor);
+        assert_eq!(decoder.map().unwrap(), Some(5));
+
+        assert_eq!(decoder.u8().unwrap(), 3);
+        let key_data = decoder.bytes().unwrap().to_vec();
+        assert_eq!(decoder.u8().unwrap(), 4);
+        let chain_code = decoder.bytes().unwrap().to_vec();
+        let xpub: Xpub = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V"
+            .parse()
+            .unwrap();
+        assert_eq!(key_data, xpub.public_key.serialize());
+        assert_eq!(chain_code, xpub.chain_code[..]);
+
+        assert_eq!(decoder.u8().unwrap(), 5);
+        assert_eq!(decoder.tag().unwrap().as_u64(), CRYPTO_COININFO_TAG);
+        assert_eq!(decoder.map().unwrap(), Some(2));
+        assert_eq!((decoder.u8().unwrap(), decoder.u8().unwrap()), (1, 0));
+        assert_eq!((decoder.u8().unwrap(), decoder.u8().unwrap()), (2, 0));
+
+        // Origin: m/84'/0'/0' from master fingerprint 73c5da0a
+        assert_eq!(decoder.u8().unwrap(), 6);
+        assert_eq!(decoder.tag().unwrap().as_u64(), CRYPTO_KEYPATH_TAG);
+        assert_eq!(decoder.map().unwrap(), Some(3));
+        assert_eq!(decoder.u8().unwrap(), 1);
+        assert_eq!(decoder.array().unwrap(), Some(6));
+        let mut path = Vec::new();
+        for _ in 0..3 {
+            path.push((decoder.u32().unwrap(), decoder.bool().unwrap()));
+        }
+        assert_eq!(path, [(84, true), (0, true), (0, true)]);
+        assert_eq!(decoder.u8().unwrap(), 2);
+        assert_eq!(format!("{:08x}", decoder.u32().unwrap()), "73c5da0a");
+        assert_eq!((decoder.u8().unwrap(), decoder.u8().unwrap()), (3, 3));
+
+        assert_eq!(decoder.u8().unwrap(), 8);
+        assert_eq!(format!("{:08x}", decoder.u32().unwrap()), "7ef32bdb");
+        assert_eq!(decoder.position(), cbor.len());
+
+        // Test networks use coin type 1 and the testnet network flag
+        let testnet = derive_master_key(&generate_seed(&mnemonic, ""), Network::Testnet).unwrap();
+        let (_, cbor) = ur::decode(&create_ur_hdkey(&testnet, Network::Testnet).unwrap()).unwrap();
+        assert_ne!(cbor, ur::decode(&ur_string).unwrap().1);
+    }
+
+    #[test]
+    fn test_printable_word_indices() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let column = format_indexed_word_column(&mnemonic);
+        assert_eq!(column.lines().count(), 12);
+        assert!(column.starts_with(" 1. abandon  [0000]\n"));
+        // "about" is the 4th wordlist entry, index 0003
+        assert!(column.ends_with("12. about    [0003]\n"));
+
+        let plain = create_printable_output(&mnemonic, "73c5da0a", "Test", Network::Bitcoin, &[]);
+        assert!(!plain.contains("[0000]"));
+        let indexed = create_localized_printable_output(
+            &mnemonic,
+            "73c5da0a",
+            "Test",
+            Network::Bitcoin,
+            &[],
+            &PrintableOptions {
+                word_indices: true,
+                ..PrintableOptions::default()
+            },
+            &now_timestamp(),
+        );
+        assert!(indexed.contains(&column));
+        assert!(!indexed.contains(&format_word_column(&mnemonic.words().collect::<Vec<_>>())));
+        assert!(indexed.contains(&format_word_grid(&mnemonic.words().collect::<Vec<_>>())));
+
+        let temp_dir = TempDir::new().unwrap();
+        let options = parse_args([
+            "--from-entropy".to_string(),
+            "00".repeat(16),
+            "--with-word-indices".to_string(),
+        ])
+        .unwrap();
+        let output_dir = temp_dir.path().join("output");
+        run(&options, &output_dir).unwrap();
+        let printable = fs::read_to_string(output_dir.join("seed_phrase_printable.txt")).unwrap();
+        assert!(printable.contains(" 1. abandon  [0000]"));
+        assert!(printable.contains("12. about    [0003]"));
+    }
+
+    #[test]
+    fn test_secure_compare() {
+        assert!(secure_compare(b"abandon", b"abandon"));
+        assert!(secure_compare(b"", b""));
+        assert!(!secure_compare(b"abandon", b"abandom"));
+        assert!(!secure_compare(b"abandon", b"bandon"));
+        assert!(!secure_compare(b"abandon", b"abandon "));
+        assert!(!secure_compare(b"abandon", b""));
+
+        let words = ["abandon", "ability", "able"];
+        let mut input = std::io::Cursor::new("ABILITY\nabl\n");
+        assert_eq!(verify_quiz(&words, &[2, 3], &mut input).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_passphrase_matrix() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("passphrases.txt");
+        fs::write(&path, "first secret\r\n\n second \n").unwrap();
+        let passphrases = read_passphrase_list(&path).unwrap();
+        assert_eq!(passphrases.len(), 2);
+        assert_eq!(passphrases[1].as_str(), " second ");
+
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let rows = passphrase_matrix(&mnemonic, Network::Bitcoin, &passphrases).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_ne!(rows[0].fingerprint, rows[1].fingerprint);
+        assert!(rows.iter().all(|keys| keys.fingerprint != "73c5da0a"));
+        let expected =
+            derive_master_key(&generate_seed(&mnemonic, "first secret"), Network::Bitcoin).unwrap();
+        assert_eq!(
+            rows[0].fingerprint,
+            get_hardware_wallet_fingerprint(&expected)
+        );
+
+        let table = format_passphrase_matrix(&rows);
+        assert_eq!(table.lines().count(), 3);
+        for (i, keys) in rows.iter().enumerate() {
+            assert!(table.contains(&format!("#{}", i + 1)));
+            assert!(table.contains(&keys.fingerprint));
+            assert!(table.contains(&keys.first_address));
+        }
+        assert!(!table.contains("secret") && !table.contains("second"));
+
+        // Passphrases only go to the console, never into the output files
+        let options = parse_args([
+            "--from-entropy".to_string(),
+            "00".repeat(16),
+            "--passphrase-list".to_string(),
+            path.to_str().unwrap().to_string(),
+        ])
+        .unwrap();
+        let output_dir = temp_dir.path().join("output");
+        run(&options, &output_dir).unwrap();
+        for entry in fs::read_dir(&output_dir).unwrap() {
+            let contents = fs::read(entry.unwrap().path()).unwrap();
+            assert!(!contents.windows(6).any(|window| window == b"secret"));
+        }
+
+        fs::write(&path, "\n\n").unwrap();
+        assert!(read_passphrase_list(&path).is_err());
+    }
+
+    #[test]
+    fn test_from_xprv() {
+        // Master keys of the all-"abandon" 12-word vector (BIP84 test vector root)
+        let tprv = "tprv8ZgxMBicQKsPe5YMU9gHen4Ez3ApihUfykaqUorj9t6FDqy3nP6eoXiAo2ssvpAjoLroQxHqr3R5nE3a5dU3DHTjTgJDd7zrbniJr6nrCzd";
+        let xprv = "xprv9s21ZrQH143K3GJpoapnV8SFfukcVBSfeCficPSGfubmSFDxo1kuHnLisriDvSnRRuL2Qrg5ggqHKNVpxR86QEC8w35uxmGoggxtQTPvfUu";
+
+        let key = parse_master_xprv(tprv, Network::Testnet).unwrap();
+        assert_eq!(get_hardware_wallet_fingerprint(&key), "73c5da0a");
+        let mismatch = parse_master_xprv(tprv, Network::Bitcoin).unwrap_err();
+        assert!(mismatch.to_string().contains("--network is"));
+        assert!(parse_master_xprv(xprv, Network::Signet).is_err());
+        assert!(
+            parse_args(["--from-xprv", tprv, "--network", "mainnet"].map(String::from)).is_err()
+        );
+        assert!(
+            parse_args(["--from-xprv", xprv, "--recover", "abandon"].map(String::from)).is_err()
+        );
+
+        // Account-level keys are not master keys
+        let secp = Secp256k1::new();
+        let account = key
+            .derive_priv(&secp, &"m/84'/1'/0'".parse::<DerivationPath>().unwrap())
+            .unwrap();
+        assert!(parse_master_xprv(&account.to_string(), Network::Testnet).is_err());
+
+        let temp_dir = TempDir::new().unwrap();
+        let options = parse_args(["--from-xprv", xprv].map(String::from)).unwrap();
+        let output_dir = temp_dir.path().join("output");
+        let keys = run_from_xprv(&options, xprv, &output_dir).unwrap();
+        assert_eq!(keys.fingerprint, "73c5da0a");
+        assert_eq!(
+            keys.first_address,
+            "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu"
+        );
+        let descriptors = fs::read_to_string(output_dir.join("descriptors.txt")).unwrap();
+        assert!(descriptors.contains("wpkh([73c5da0a/84h/0h/0h]xpub6CatWdiZ"));
+        assert!(!output_dir.join("seed_phrase_printable.txt").exists());
+        assert_eq!(verify_manifest(&output_dir).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_redacted_preview() {
+        assert_eq!(redact_word("abandon"), "a*****n");
+        assert_eq!(redact_word("zoo"), "z*o");
+        assert_eq!(redact_word("ab"), "a*b");
+        assert_eq!(
+            redact_word("\u{3042}\u{3044}\u{3053}\u{304f}"),
+            "\u{3042}**\u{304f}"
+        );
+
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let preview = create_redacted_preview(&mnemonic, "73c5da0a");
+        assert!(preview.contains("Fingerprint: 73c5da0a"));
+        assert!(preview.contains(" 1. a*****n"));
+        assert!(preview.contains("12. a***t"));
+        for word in mnemonic.words() {
+            assert!(!preview.contains(word), "{} leaked", word);
+        }
+
+        let mnemonic = generate_mnemonic(24, Language::English).unwrap();
+        let preview = create_redacted_preview(&mnemonic, "0123abcd");
+        // Only the grid lines: the header itself uses wordlist words ("first", "only")
+        let grid: String = preview.lines().skip(1).take(6).collect();
+        let tokens: Vec<&str> = grid
+            .split(|c: char| !c.is_alphabetic() && c != '*')
+            .collect();
+        for word in mnemonic.words() {
+            assert!(!tokens.contains(&word), "{} leaked", word);
+        }
+        assert!(preview.matches('*').count() >= 24);
+
+        assert!(parse_args(["--redact", "--no-write"].map(String::from)).is_err());
+        assert!(parse_args(["--redact".to_string()]).unwrap().redact);
+    }
+
+    #[test]
+    fn test_printable_partial_rows() {
+        for (word_count, rows, bits) in [(15, 4, 160), (21, 6, 224), (24, 6, 256)] {
+            let mnemonic = generate_mnemonic(word_count, Language::English).unwrap();
+            let words: Vec<&str> = mnemonic.words().collect();
+            let grid = format_word_grid(&words);
+
+            let lines: Vec<&str> = grid.lines().collect();
+            assert_eq!(lines.len(), rows, "{} words", word_count);
+            assert!(grid.ends_with('\n') && !grid.ends_with("\n\n"));
+            // Every row, including a short last one, is a whole line
+            assert_eq!(grid.matches('\n').count(), rows);
+            for (row, line) in lines.iter().enumerate() {
+                assert!(!line.trim().is_empty());
+                let expected = words.len().saturating_sub(row * 4).min(4);
+                let tokens: Vec<&str> = line.split_whitespace().collect();
+                assert_eq!(tokens.len(), expected * 2, "{:?}", line);
+                for (col, pair) in tokens.chunks(2).enumerate() {
+                    let index = row * 4 + col;
+                    assert_eq!(pair[0], format!("{}.", index + 1));
+                    assert_eq!(pair[1], words[index]);
+                }
+            }
+
+            let output =
+                create_printable_output(&mnemonic, "73c5da0a", "Test", Network::Bitcoin, &[]);
+            assert!(output.contains(&grid));
+            assert!(output.contains(&format!(
+                "Word Count: {} words ({} bits entropy)",
+                word_count, bits
+            )));
+            for word in &words {
+                assert!(output.contains(word), "Missing word {}", word);
+            }
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path().join("output");
+        let phrase = format!("{} about", ["abandon"; 11].join(" "));
+        let options = parse_args([
+            "--fingerprint-only".to_string(),
+            "--recover".to_string(),
+            phrase.clone(),
+            "--output-dir".to_string(),
+            output_dir.to_str().unwrap().to_string(),
+        ])
+        .unwrap();
+        assert_eq!(recovered_fingerprint(&options).unwrap(), "73c5da0a");
+        assert!(!output_dir.exists(), "No files should be written");
+
+        // The passphrase changes the fingerprint
+        let passphrase_path = temp_dir.path().join("passphrase.txt");
+        fs::write(&passphrase_path, "TREZOR").unwrap();
+        let options = parse_args([
+            "--fingerprint-only".to_string(),
+            "--recover".to_string(),
+            phrase,
+            "--passphrase-file".to_string(),
+            passphrase_path.to_str().unwrap().to_string(),
+        ])
+        .unwrap();
+        assert_ne!(recovered_fingerprint(&options).unwrap(), "73c5da0a");
+
+        assert!(parse_args(["--fingerprint-only".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_recover_stdin() {
+        let phrase = format!("{} about\n", ["abandon"; 11].join(" "));
+        // Parsing only records the flag; the phrase is read when the run starts
+        let parsed = parse_args(["--recover-stdin", "--words", "24"].map(String::from)).unwrap();
+        assert!(parsed.recover_stdin);
+        assert!(parsed.recover.is_none());
+        assert!(parse_args(["--recover-stdin", "--recover", "abandon"].map(String::from)).is_err());
+
+        let mut stdin = std::io::Cursor::new(phrase.into_bytes());
+        let options = read_stdin_phrase(&parsed, &mut stdin).unwrap();
+        assert_eq!(options.word_count, 12);
+        assert_eq!(
+            options
+                .recover
+                .as_deref()
+                .map(|p| p.split_whitespace().count()),
+            Some(12)
+        );
+        let fifteen = format!("{} address\n", ["abandon"; 14].join(" "));
+        let mut stdin = std::io::Cursor::new(fifteen.into_bytes());
+        let seedqr = Options {
+            seedqr: true,
+            ..parsed.clone()
+        };
+        assert!(read_stdin_phrase(&seedqr, &mut stdin).is_err());
+
+        assert_eq!(recovered_fingerprint(&options).unwrap(), "73c5da0a");
+        let temp_dir = TempDir::new().unwrap();
+        let keys = run(&options, temp_dir.path()).unwrap();
+        assert_eq!(keys.fingerprint, "73c5da0a");
+
+        // A numbered word list piped in works the same way
+        let mut stdin = std::io::Cursor::new(" 1. abandon\n 2. ability\n".as_bytes());
+        assert_eq!(
+            read_recover_phrase(&mut stdin).unwrap().as_str(),
+            "abandon ability"
+        );
+        let mut empty = std::io::Cursor::new(Vec::new());
+        assert!(read_recover_phrase(&mut empty).is_err());
+    }
+
+    #[test]
+    fn test_find_adjacent_swaps() {
+        let valid = "legal winner thank year wave sausage worth useful legal winner thank yellow";
+        assert!(recover_mnemonic(valid, Language::English).is_ok());
+
+        let mut words: Vec<&str> = valid.split_whitespace().collect();
+        words.swap(5, 6);
+        let swapped = words.join(" ");
+        let error = recover_mnemonic(&swapped, Language::English).unwrap_err();
+        let candidates = find_adjacent_swaps(&swapped, Language::English);
+        assert!(candidates.contains(&6), "{:?}", candidates);
+        let message = error.to_string();
+        assert!(message.contains("; swapping words "), "{}", message);
+        assert!(
+            message.contains("words 6 'worth' and 7 'sausage'"),
+            "{}",
+            message
+        );
+    }
+
+    #[test]
+    fn test_english_wordlist_hash() {
+        assert_eq!(
+            wordlist_sha256(Language::English).to_string(),
+            BIP39_ENGLISH_SHA256
+        );
+        assert!(check_english_wordlist().is_ok());
+        assert_ne!(
+            wordlist_sha256(Language::Spanish).to_string(),
+            BIP39_ENGLISH_SHA256
+        );
+    }
+
+    #[test]
+    fn test_phonetic_spelling() {
+        let abandon = phonetic_spelling("abandon");
+        assert!(abandon.contains("Alpha"));
+        assert!(abandon.contains("Bravo"));
+        assert_eq!(abandon, "Alpha Bravo Alpha November Delta Oscar November");
+        // Non-ASCII letters are kept as they are
+        assert_eq!(phonetic_spelling("ábaco"), "á Bravo Alpha Charlie Oscar");
 
-    output.push('\n');
-    output.push_str("═══════════════════════════════════════════════════════════════\n");
-    output.push_str("VERIFICATION CHECKLIST:\n");
-    output.push_str("─────────────────────────────────────────────────────────────\n");
-    output.push_str("□ All 24 words are clearly readable\n");
-    output.push_str("□ Words are in correct numerical order (1-24)\n");
-    output.push_str("□ Fingerprint matches hardware wallet device\n");
-    output.push_str("□ Metal plate is stored in secure location\n");
-    output.push_str("□ Backup copy exists in separate location\n");
-    output.push_str("═══════════════════════════════════════════════════════════════\n\n");
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let plain = create_printable_output(&mnemonic, "73c5da0a", "Test", Network::Bitcoin, &[]);
+        assert!(!plain.contains("PHONETIC SPELLING"));
+        let phonetic = create_localized_printable_output(
+            &mnemonic,
+            "73c5da0a",
+            "Test",
+            Network::Bitcoin,
+            &[],
+            &PrintableOptions {
+                phonetic: true,
+                ..PrintableOptions::default()
+            },
+            &now_timestamp(),
+        );
+        assert!(phonetic.contains("12. about    = Alpha Bravo Oscar Uniform Tango\n"));
+        // Placed after the single-column list, before the import instructions
+        let column = format_word_column(&mnemonic.words().collect::<Vec<_>>());
+        let section = phonetic.find("PHONETIC SPELLING").unwrap();
+        assert!(phonetic.find(&column).unwrap() < section);
+        assert!(section < phonetic.find("Coldcard").unwrap());
 
-    // Additional format: Single column for easier punching reference
-    output.push_str("\n\nSINGLE COLUMN FORMAT (Alternative punching reference):\n");
-    output.push_str("═══════════════════════════════════════════════════════════════\n");
-    for (i, word) in words.iter().enumerate() {
-        output.push_str(&format!("{:2}. {}\n", i + 1, word));
+        // With word indices too, the section still follows the (indexed) column
+        let options =
+            parse_args(["--phonetic".to_string(), "--with-word-indices".to_string()]).unwrap();
+        assert!(options.phonetic && options.word_indices);
+        let both = create_localized_printable_output(
+            &mnemonic,
+            "73c5da0a",
+            "Test",
+            Network::Bitcoin,
+            &[],
+            &PrintableOptions {
+                word_indices: true,
+                phonetic: true,
+                ..PrintableOptions::default()
+            },
+            &now_timestamp(),
+        );
+        assert!(both.contains(" 1. abandon  = Alpha"));
+        let indexed = both.find(&format_indexed_word_column(&mnemonic)).unwrap();
+        assert!(indexed < both.find("PHONETIC SPELLING").unwrap());
     }
-    output.push_str("═══════════════════════════════════════════════════════════════\n\n");
 
-    // Hardware wallet import instructions
-    output.push_str("HARDWARE WALLET IMPORT INSTRUCTIONS:\n");
-    output.push_str("─────────────────────────────────────────────────────────────\n");
-    output.push_str("This seed phrase is compatible with all BIP39 hardware wallets\n");
-    output.push_str("(Coldcard, Trezor, Ledger, BitBox, etc.).\n\n");
-    output.push_str("Example - Coldcard:\n");
-    output.push_str("1. Power on your Coldcard device\n");
-    output.push_str("2. Navigate to: Advanced > Danger Zone > Seed Functions > Import Existing\n");
-    output.push_str("3. Select '24 words' when prompted\n");
-    output.push_str("4. Enter the 24 words in order (1-24)\n");
-    output.push_str(&format!(
-        "5. Verify the fingerprint matches: {}\n",
-        fingerprint
-    ));
-    output.push_str("6. Set a secure PIN code\n");
-    output.push_str("7. Test with a small transaction before storing large amounts\n\n");
-    output.push_str("For other hardware wallets, follow their specific recovery/import process.\n");
-    output.push_str("─────────────────────────────────────────────────────────────\n\n");
+    #[test]
+    fn test_first_address_descriptor() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let master = Xpriv::new_master(Network::Bitcoin, &mnemonic.to_seed("")).unwrap();
+        let descriptor = build_first_address_descriptor(&master, Network::Bitcoin).unwrap();
+        let (body, checksum) = descriptor.split_once('#').unwrap();
+        assert!(body.ends_with("/0/0)"));
+        assert!(!body.contains('*'));
+        assert!(body.starts_with("wpkh([73c5da0a/84h/0h/0h]xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V/"));
+        assert_eq!(checksum, descriptor_checksum(body).unwrap());
+        assert_eq!(checksum.len(), 8);
 
-    // Footer
-    output.push_str("Generated by bitcoin-keygen (air-gapped system)\n");
-    output.push_str("═══════════════════════════════════════════════════════════════\n");
+        let temp_dir = TempDir::new().unwrap();
+        let options = parse_args([
+            "--from-entropy".to_string(),
+            "00".repeat(16),
+            "--first-address-watchonly".to_string(),
+        ])
+        .unwrap();
+        let output_dir = temp_dir.path().join("output");
+        run(&options, &output_dir).unwrap();
+        let file = fs::read_to_string(output_dir.join("first_address_watchonly.txt")).unwrap();
+        assert!(file.contains("# Address: bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu\n"));
+        assert!(file.ends_with(&format!("{}\n", descriptor)));
+    }
 
-    output
-}
+    #[test]
+    fn test_clipboard_auto_clear() {
+        struct MockClipboard<'a> {
+            log: &'a RefCell<Vec<String>>,
+        }
+        impl Clipboard for MockClipboard<'_> {
+            fn set_text(&mut self, text: &str) -> Result<(), KeygenError> {
+                self.log.borrow_mut().push(format!("set {}", text));
+                Ok(())
+            }
+            fn clear(&mut self) -> Result<(), KeygenError> {
+                self.log.borrow_mut().push("clear".to_string());
+                Ok(())
+            }
+        }
 
-/// Create a simple text file with just the words (for easy copying)
-fn create_simple_word_list(mnemonic: &Mnemonic) -> String {
-    let words: Vec<&str> = mnemonic.words().collect();
-    let mut output = String::new();
+        let log = RefCell::new(Vec::new());
+        let mut clipboard = MockClipboard { log: &log };
+        copy_with_auto_clear(
+            &mut clipboard,
+            "abandon about",
+            Duration::from_secs(45),
+            |timeout| {
+                log.borrow_mut()
+                    .push(format!("sleep {}", timeout.as_secs()))
+            },
+        )
+        .unwrap();
+        assert_eq!(*log.borrow(), ["set abandon about", "sleep 45", "clear"]);
 
-    // Numbered list
-    for (i, word) in words.iter().enumerate() {
-        output.push_str(&format!("{:2}. {}\n", i + 1, word));
+        let args = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let options = parse_args(args(&["--clipboard", "--clipboard-timeout", "45"])).unwrap();
+        assert!(options.clipboard);
+        assert_eq!(options.clipboard_timeout, 45);
+        assert_eq!(
+            parse_args(args(&[])).unwrap().clipboard_timeout,
+            CLIPBOARD_CLEAR_SECONDS
+        );
+        assert!(parse_args(args(&["--clipboard-timeout", "0"])).is_err());
+        assert!(parse_args(args(&["--clipboard", "--redact"])).is_err());
+        assert!(parse_args(args(&["--clipboard", "--count", "2"])).is_err());
     }
 
-    output
-}
+    #[test]
+    fn test_compare_mnemonics() {
+        let phrase = "abandon ".repeat(11) + "about";
+        let mnemonic = recover_mnemonic(&phrase, Language::English).unwrap();
+        assert!(mnemonic_differences(&mnemonic, &mnemonic).is_empty());
+        let same = format_mnemonic_comparison(&mnemonic, &mnemonic, "73c5da0a", "73c5da0a");
+        assert!(!same.contains("MISMATCH"));
+        assert!(same.contains("Fingerprints: 73c5da0a / 73c5da0a (match)"));
+        assert!(same.contains("identical"));
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    println!("═══════════════════════════════════════════════════════════════");
-    println!("        Bitcoin Key Generator - Air-Gapped Edition");
-    println!("═══════════════════════════════════════════════════════════════");
-    println!();
-    println!("Generating secure BIP39 mnemonic seed phrase...");
-    println!();
+        // "actual" is another valid final word for "abandon" x11
+        let variant =
+            recover_mnemonic(&("abandon ".repeat(11) + "actual"), Language::English).unwrap();
+        assert_eq!(mnemonic_differences(&mnemonic, &variant), [12]);
+        let variant_fingerprint = mnemonic_fingerprint(&variant, "", Network::Bitcoin).unwrap();
+        assert_ne!(variant_fingerprint, "73c5da0a");
+        let table =
+            format_mnemonic_comparison(&mnemonic, &variant, "73c5da0a", &variant_fingerprint);
+        assert_eq!(table.matches("MISMATCH").count(), 1);
+        assert!(table.contains(
+            " 12  about      actual     ✗ MISMATCH
+"
+        ));
+        assert!(table.contains("(DIFFERENT)"));
+        assert!(table.contains("1 of 12 positions differ"));
+        assert_eq!(
+            mnemonic_fingerprint(&mnemonic, "", Network::Bitcoin).unwrap(),
+            "73c5da0a"
+        );
 
-    // Generate mnemonic
-    let mnemonic = generate_mnemonic()?;
-    println!("✓ Generated 24-word BIP39 mnemonic");
+        let options = parse_args(["--compare", &phrase, "abandon"].map(String::from)).unwrap();
+        let (first, second) = options.compare.unwrap();
+        assert_eq!(first.as_str(), phrase);
+        assert_eq!(second.as_str(), "abandon");
+        assert!(parse_args(["--compare", &phrase].map(String::from)).is_err());
+    }
 
-    // Generate seed and master key
-    let seed = generate_seed(&mnemonic, "");
-    let master_key = derive_master_key(&seed, Network::Bitcoin)?;
-    println!("✓ Derived master private key");
+    #[test]
+    fn test_entropy_grid() {
+        let empty = format_entropy_grid(&[0u8; 32]);
+        assert_eq!(empty.lines().count(), 16);
+        assert!(empty
+            .lines()
+            .all(|row| row.split(' ').count() == 16 && row.split(' ').all(|cell| cell == ".")));
 
-    // Get fingerprint
-    let fingerprint = get_hardware_wallet_fingerprint(&master_key);
-    println!("✓ Calculated fingerprint: {}", fingerprint);
+        let full = format_entropy_grid(&[0xffu8; 32]);
+        assert_eq!(full.lines().count(), 16);
+        assert!(full
+            .lines()
+            .all(|row| row.split(' ').count() == 16 && row.split(' ').all(|cell| cell == "#")));
 
-    // Get label from user or use default
-    let label = std::env::args()
-        .nth(1)
-        .unwrap_or_else(|| "Bitcoin Wallet".to_string());
+        // Most significant bit first, 128 bits make 8 rows
+        let half = format_entropy_grid(&[0x80u8; 16]);
+        assert_eq!(half.lines().count(), 8);
+        assert!(half.starts_with("# . . . . . . . # . . . . . . .\n"));
 
-    // Create output directory
-    let output_dir = "output";
-    fs::create_dir_all(output_dir)?;
-
-    // Create printable file
-    let printable_content = create_printable_output(&mnemonic, &fingerprint, &label);
-    let printable_file = format!("{}/seed_phrase_printable.txt", output_dir);
-    let mut file = fs::File::create(&printable_file)?;
-    file.write_all(printable_content.as_bytes())?;
-    println!("✓ Created printable file: {}", printable_file);
-
-    // Create simple word list
-    let word_list = create_simple_word_list(&mnemonic);
-    let word_list_file = format!("{}/seed_words_simple.txt", output_dir);
-    fs::write(&word_list_file, word_list)?;
-    println!("✓ Created simple word list: {}", word_list_file);
-
-    // Create seed words for hardware wallet import (just the words, one per line)
-    let seed_words_file = format!("{}/seed_words_for_coldcard.txt", output_dir);
-    fs::write(
-        &seed_words_file,
-        mnemonic.words().collect::<Vec<_>>().join("\n"),
-    )?;
-    println!("✓ Created Coldcard import file: {}", seed_words_file);
-
-    println!();
-    println!("═══════════════════════════════════════════════════════════════");
-    println!("                    GENERATION COMPLETE");
-    println!("═══════════════════════════════════════════════════════════════");
-    println!();
-    println!("Files created in: {}", output_dir);
-    println!();
-    println!("IMPORTANT SECURITY NOTES:");
-    println!("─────────────────────────────────────────────────────────────");
-    println!("1. Print the 'seed_phrase_printable.txt' file for metal plate");
-    println!("2. Verify all words are correct before punching");
-    println!("3. Store metal plate in secure, fireproof location");
-    println!("4. Create backup copy in separate location");
-    println!("5. Delete all files from this computer after printing");
-    println!("6. Never store seed phrases on internet-connected devices");
-    println!("7. Test import on hardware wallet with small amount first");
-    println!("─────────────────────────────────────────────────────────────");
-    println!();
-    println!("Fingerprint: {}", fingerprint);
-    println!("(Verify this matches your hardware wallet after import)");
-    println!();
+        let temp_dir = TempDir::new().unwrap();
+        let options = parse_args([
+            "--from-entropy".to_string(),
+            "ff".repeat(32),
+            "--entropy-grid".to_string(),
+        ])
+        .unwrap();
+        let output_dir = temp_dir.path().join("output");
+        run(&options, &output_dir).unwrap();
+        let file = fs::read_to_string(output_dir.join("entropy_grid.txt")).unwrap();
+        assert!(file.starts_with("Entropy grid: 256 bits"));
+        assert!(file.ends_with(full.as_str()));
+    }
 
-    Ok(())
-}
+    #[test]
+    fn test_create_seed_card() {
+        let generated = parse_timestamp("2024-01-02T03:04:05+00:00").unwrap();
+        for entropy_len in [16, 20, 32] {
+            let mnemonic = Mnemonic::from_entropy(&vec![0x5au8; entropy_len]).unwrap();
+            let card = create_seed_card(
+                &mnemonic,
+                "0badf00d",
+                "Card",
+                Network::Bitcoin,
+                &generated,
+                false,
+            );
+            assert_eq!(card.matches("CUT HERE").count(), 2);
+            assert_eq!(card.matches("FOLD HERE").count(), 2);
+            assert!(card.contains("Signature:"));
+            assert!(card.contains("□ Signed across the tape and the card"));
+            for (i, word) in mnemonic.words().enumerate() {
+                assert!(card.contains(&format!("{:2}. {}", i + 1, word)), "{}", word);
+            }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::TempDir;
+            // The words sit between the two folds, away from the outer panels
+            let first_fold = card.find("FOLD HERE").unwrap();
+            let last_fold = card.rfind("FOLD HERE").unwrap();
+            let words_at = card.find(" 1. ").unwrap();
+            assert!(first_fold < words_at && words_at < last_fold);
+            assert!(ascii_decorations(&card).is_ascii());
+        }
+
+        let options = parse_args(["--seed-card".to_string()]).unwrap();
+        assert!(options.seed_card);
+    }
 
     #[test]
-    fn test_generate_mnemonic() {
-        let mnemonic = generate_mnemonic().unwrap();
-        let words: Vec<&str> = mnemonic.words().collect();
-        assert_eq!(words.len(), 24, "Mnemonic should have 24 words");
+    fn test_recover_infers_word_count() {
+        assert_eq!(infer_word_count("abandon about"), None);
+        assert_eq!(infer_word_count(&"abandon ".repeat(15)), Some(15));
 
-        // Verify all words are from BIP39 wordlist
-        for word in words {
-            assert!(!word.is_empty(), "Word should not be empty");
-            assert!(
-                word.chars().all(|c| c.is_alphabetic()),
-                "Word should contain only letters"
-            );
+        let temp_dir = TempDir::new().unwrap();
+        for (entropy_len, fingerprint) in [(16, Some("73c5da0a")), (32, None)] {
+            let original = Mnemonic::from_entropy(&vec![0u8; entropy_len]).unwrap();
+            let options = parse_args(["--recover".to_string(), original.to_string()]).unwrap();
+            assert_eq!(options.word_count, original.word_count());
+
+            let output_dir = temp_dir.path().join(format!("output_{}", entropy_len));
+            let keys = run(&options, &output_dir).unwrap();
+            let expected = mnemonic_fingerprint(&original, "", Network::Bitcoin).unwrap();
+            assert_eq!(keys.fingerprint, expected);
+            assert_eq!(keys.fingerprint.len(), 8);
+            if let Some(fingerprint) = fingerprint {
+                assert_eq!(keys.fingerprint, fingerprint);
+            }
+            let printable =
+                fs::read_to_string(output_dir.join("seed_phrase_printable.txt")).unwrap();
+            assert!(printable.contains(&format!("Word Count: {} words", original.word_count())));
         }
+
+        // The phrase, not the default, decides whether --seedqr applies
+        let fifteen = Mnemonic::from_entropy(&[0u8; 20]).unwrap().to_string();
+        assert!(parse_args(["--recover".to_string(), fifteen, "--seedqr".to_string()]).is_err());
+        let twelve = Mnemonic::from_entropy(&[0u8; 16]).unwrap().to_string();
+        assert!(parse_args(["--recover".to_string(), twelve, "--seedqr".to_string()]).is_ok());
     }
 
     #[test]
-    fn test_generate_seed() {
-        let mnemonic = generate_mnemonic().unwrap();
-        let seed = generate_seed(&mnemonic, "");
-        assert_eq!(seed.len(), 64, "Seed should be 64 bytes");
+    fn test_fingerprint_stats() {
+        let sample = ["73c5da0a", "0badf00d", "73c5da0a", "deadbeef"].map(String::from);
+        let stats = fingerprint_stats(&sample);
+        assert_eq!(stats.total, 4);
+        assert_eq!(stats.distinct, 3);
+        assert_eq!(stats.collisions, [("73c5da0a".to_string(), 2)]);
+        assert!(expected_fingerprint_collisions(1) == 0.0);
+        assert!((expected_fingerprint_collisions(100_000) - 1.164).abs() < 0.001);
 
-        // Test with passphrase
-        let seed_with_passphrase = generate_seed(&mnemonic, "test_passphrase");
-        assert_ne!(
-            seed, seed_with_passphrase,
-            "Seed with passphrase should be different"
-        );
+        let options =
+            parse_args(["--fingerprint-stats", "24", "--words", "12"].map(String::from)).unwrap();
+        assert_eq!(options.fingerprint_stats, Some(24));
+        let stats = run_fingerprint_stats(&options, 24).unwrap();
+        assert_eq!(stats.total, 24);
+        assert_eq!(stats.distinct, 24);
+        assert!(stats.collisions.is_empty());
+        assert!(parse_args(["--fingerprint-stats", "0"].map(String::from)).is_err());
     }
 
     #[test]
-    fn test_derive_master_key() {
-        let mnemonic = generate_mnemonic().unwrap();
-        let seed = generate_seed(&mnemonic, "");
-        let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
+    fn test_send_to_printer() {
+        let commands = printer_commands(Some("office"));
+        assert_eq!(commands[0].get_program(), "lp");
+        assert_eq!(commands[0].get_args().collect::<Vec<_>>(), ["-d", "office"]);
+        assert_eq!(commands[1].get_program(), "lpr");
+        assert_eq!(commands[1].get_args().collect::<Vec<_>>(), ["-P", "office"]);
+        assert_eq!(printer_commands(None)[0].get_args().count(), 0);
 
-        // Verify master key is valid
-        assert!(!master_key.to_string().is_empty());
+        // A stand-in spooler that saves its stdin; missing commands are skipped
+        let temp_dir = TempDir::new().unwrap();
+        let spooled = temp_dir.path().join("spooled.txt");
+        let mut fake_lp = std::process::Command::new("sh");
+        fake_lp.args(["-c", "cat > \"$0\""]).arg(&spooled);
+        let missing = std::process::Command::new("bitcoin-keygen-no-such-printer");
+        let printable = "SEED WORDS\n 1. abandon\n";
+        let used = send_to_printer(printable.as_bytes(), vec![missing, fake_lp]).unwrap();
+        assert_eq!(used, "sh");
+        assert_eq!(fs::read_to_string(&spooled).unwrap(), printable);
+
+        // A failing spooler is an error, not a silent skip
+        let mut failing = std::process::Command::new("sh");
+        failing.args(["-c", "cat > /dev/null; exit 3"]);
+        assert!(send_to_printer(b"x", vec![failing]).is_err());
+        let none = vec![std::process::Command::new("bitcoin-keygen-no-such-printer")];
+        assert!(send_to_printer(b"x", none).is_err());
+
+        let options = parse_args(["--printer".to_string(), "office".to_string()]).unwrap();
+        assert!(options.print);
+        assert_eq!(options.printer.as_deref(), Some("office"));
+        assert!(parse_args(["--print", "--format", "json"].map(String::from)).is_err());
     }
 
     #[test]
-    fn test_get_hardware_wallet_fingerprint() {
-        let mnemonic = generate_mnemonic().unwrap();
-        let seed = generate_seed(&mnemonic, "");
-        let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
-        let fingerprint = get_hardware_wallet_fingerprint(&master_key);
+    fn test_explore_paths() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        let account: Xpub = "xpub6CatWdiZiodmUeTDp8LT5or8nmbKNcuyvz7WyksVFkKB4RHwCD3XyuvPEbvqAQY3rAPshWcMLoP2fMFMKHPJ4ZeZXYVUhLv1VMrjPC7PW6V"
+            .parse()
+            .unwrap();
 
-        // Fingerprint should be 8 hex characters
-        assert_eq!(
-            fingerprint.len(),
-            8,
-            "Fingerprint should be 8 hex characters"
-        );
-        assert!(
-            fingerprint.chars().all(|c| c.is_ascii_hexdigit()),
-            "Fingerprint should contain only hex characters"
-        );
+        let mut input =
+            std::io::Cursor::new("m\nm/84'/0'/0'\n\nnot/a/path\nm/84'/0'/0'/0/0\nquit\nm/44'\n");
+        let mut output = Vec::new();
+        explore_paths(&master, Network::Bitcoin, &mut input, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        let fingerprints: Vec<&str> = output
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("fingerprint: "))
+            .map(str::trim)
+            .collect();
+        assert_eq!(fingerprints.len(), 3);
+        assert_eq!(fingerprints[0], "73c5da0a");
+        assert_eq!(fingerprints[1], account.fingerprint().to_string());
+        assert!(output.contains(&format!("  xpub:        {}\n", account)));
+        assert!(output.contains("  address:     bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu\n"));
+        assert!(output.contains("  error: Invalid derivation path 'not/a/path'"));
+        // Input after `quit` is not read
+        assert!(!output.contains("m/44'"));
+
+        // End of input also ends the loop
+        let mut empty = std::io::Cursor::new("");
+        explore_paths(&master, Network::Bitcoin, &mut empty, &mut Vec::new()).unwrap();
+
+        assert!(parse_args(["--explore".to_string()]).is_err());
     }
 
     #[test]
-    fn test_create_printable_output() {
-        let mnemonic = generate_mnemonic().unwrap();
-        let seed = generate_seed(&mnemonic, "");
-        let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
-        let fingerprint = get_hardware_wallet_fingerprint(&master_key);
+    fn test_vanity_fingerprint() {
+        let (mnemonic, attempts) =
+            find_vanity_mnemonic("a", 12, Language::English, Network::Bitcoin, 2000).unwrap();
+        assert!(attempts >= 1);
+        let fingerprint = mnemonic_fingerprint(&mnemonic, "", Network::Bitcoin).unwrap();
+        assert!(fingerprint.starts_with('a'), "{}", fingerprint);
 
-        let output = create_printable_output(&mnemonic, &fingerprint, "Test Wallet");
+        // The cap is honoured: 8 hex digits would take ~4 billion attempts
+        let error = find_vanity_mnemonic("0badf00d", 12, Language::English, Network::Bitcoin, 10)
+            .unwrap_err();
+        assert!(error.to_string().contains("after 10 attempts"));
 
-        // Verify output contains expected sections
-        assert!(
-            output.contains("BITCOIN SEED PHRASE"),
-            "Should contain header"
-        );
-        assert!(output.contains("Test Wallet"), "Should contain label");
-        assert!(output.contains(&fingerprint), "Should contain fingerprint");
-        assert!(
-            output.contains("SECURITY WARNING"),
-            "Should contain security warning"
-        );
-        assert!(
-            output.contains("SEED WORDS"),
-            "Should contain seed words section"
-        );
-        assert!(
-            output.contains("VERIFICATION CHECKLIST"),
-            "Should contain checklist"
-        );
+        assert_eq!(parse_vanity_prefix("BEEF").unwrap(), "beef");
+        assert!(parse_vanity_prefix("").is_err());
+        assert!(parse_vanity_prefix("12345").is_err());
+        assert!(parse_vanity_prefix("xyz").is_err());
         assert!(
-            output.contains("HARDWARE WALLET IMPORT INSTRUCTIONS"),
-            "Should contain instructions"
+            parse_args(["--vanity-fingerprint", "ab", "--passphrase"].map(String::from)).is_err()
         );
 
-        // Verify all 24 words are present
-        let words: Vec<&str> = mnemonic.words().collect();
-        for word in &words {
-            assert!(
-                output.contains(word),
-                "Output should contain word: {}",
-                word
-            );
-        }
-
-        // Verify word count
-        let word_count = output.matches("words").count();
-        assert!(word_count > 0, "Should mention word count");
+        let temp_dir = TempDir::new().unwrap();
+        let options =
+            parse_args(["--vanity-fingerprint", "C", "--words", "12"].map(String::from)).unwrap();
+        let keys = run(&options, &temp_dir.path().join("output")).unwrap();
+        assert!(keys.fingerprint.starts_with('c'));
     }
 
     #[test]
-    fn test_create_simple_word_list() {
-        let mnemonic = generate_mnemonic().unwrap();
-        let output = create_simple_word_list(&mnemonic);
-
-        let words: Vec<&str> = mnemonic.words().collect();
-        assert_eq!(words.len(), 24);
+    fn test_create_index_list() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let indices = create_index_list(&mnemonic);
+        let lines: Vec<&str> = indices.lines().collect();
+        assert_eq!(lines.len(), 12);
+        assert!(lines[..11].iter().all(|&line| line == "0"));
+        let last_word = mnemonic.words().last().unwrap();
+        let expected = Language::English.find_word(last_word).unwrap();
+        assert_eq!(lines[11], expected.to_string());
+        assert_eq!(lines[11], "3");
 
-        // Verify all words are in output
-        for (i, word) in words.iter().enumerate() {
-            assert!(
-                output.contains(word),
-                "Output should contain word: {}",
-                word
-            );
-            // Check numbering
-            let expected_line = format!("{:2}. {}", i + 1, word);
-            assert!(
-                output.contains(&expected_line),
-                "Should contain numbered line"
-            );
-        }
+        let temp_dir = TempDir::new().unwrap();
+        let options = parse_args([
+            "--from-entropy".to_string(),
+            "ff".repeat(32),
+            "--indices".to_string(),
+        ])
+        .unwrap();
+        let output_dir = temp_dir.path().join("output");
+        run(&options, &output_dir).unwrap();
+        let file = fs::read_to_string(output_dir.join("indices.txt")).unwrap();
+        assert_eq!(file.lines().count(), 24);
+        assert!(file.lines().all(|line| line.parse::<u16>().unwrap() < 2048));
+        assert!(file.starts_with("2047\n"));
     }
 
     #[test]
-    fn test_mnemonic_consistency() {
-        // Test that the same mnemonic produces the same seed
-        let test_phrase = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
-        let mnemonic =
-            Mnemonic::parse_in_normalized(bip39::Language::English, test_phrase).unwrap();
+    fn test_recover_tolerant_french() {
+        use unicode_normalization::UnicodeNormalization;
 
-        let seed1 = generate_seed(&mnemonic, "");
-        let seed2 = generate_seed(&mnemonic, "");
-        assert_eq!(seed1, seed2, "Same mnemonic should produce same seed");
+        let entropy = Zeroizing::new((0u8..32).map(|i| i.wrapping_mul(37)).collect::<Vec<_>>());
+        let mnemonic = mnemonic_from_entropy(entropy, Language::French).unwrap();
+        let phrase = mnemonic.to_string();
+        let unaccented: String = phrase
+            .nfd()
+            .filter(|&c| !unicode_normalization::char::is_combining_mark(c))
+            .collect();
+        assert_ne!(unaccented, phrase, "test phrase should contain accents");
 
-        let master_key1 = derive_master_key(&seed1, Network::Bitcoin).unwrap();
-        let master_key2 = derive_master_key(&seed2, Network::Bitcoin).unwrap();
+        let expected = mnemonic_fingerprint(&mnemonic, "", Network::Bitcoin).unwrap();
+        let recovered = recover_mnemonic(&unaccented, Language::French).unwrap();
+        assert_eq!(recovered.to_string(), phrase);
         assert_eq!(
-            master_key1.to_string(),
-            master_key2.to_string(),
-            "Same seed should produce same master key"
+            mnemonic_fingerprint(&recovered, "", Network::Bitcoin).unwrap(),
+            expected
         );
+
+        let prefixes: Vec<String> = unaccented
+            .split_whitespace()
+            .map(|word| word.chars().take(4).collect())
+            .collect();
+        let recovered = recover_mnemonic(&prefixes.join(" "), Language::French).unwrap();
+        assert_eq!(recovered.to_string(), phrase);
+
+        let options = parse_args([
+            "--recover".to_string(),
+            unaccented.to_uppercase(),
+            "--language".to_string(),
+            "french".to_string(),
+        ])
+        .unwrap();
+        let recovered =
+            recover_mnemonic(options.recover.as_deref().unwrap(), options.language).unwrap();
+        assert_eq!(recovered.to_string(), phrase);
+
+        // English recovery stays strict: prefixes are reported, not resolved
+        let english = format!("{}abou", "aban ".repeat(11));
+        assert!(recover_mnemonic(&english, Language::English).is_err());
     }
 
     #[test]
-    fn test_file_generation() {
-        let temp_dir = TempDir::new().unwrap();
-        let output_dir = temp_dir.path();
-
-        // Generate mnemonic and files
-        let mnemonic = generate_mnemonic().unwrap();
-        let seed = generate_seed(&mnemonic, "");
-        let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
-        let fingerprint = get_hardware_wallet_fingerprint(&master_key);
+    fn test_selftest() {
+        let options = parse_args(["--selftest".to_string()]).unwrap();
+        assert!(options.selftest);
 
-        // Create files
-        let printable_content = create_printable_output(&mnemonic, &fingerprint, "Test");
-        let printable_file = output_dir.join("seed_phrase_printable.txt");
-        fs::write(&printable_file, printable_content).unwrap();
+        let checks = run_selftest();
+        assert_eq!(checks.len(), 4);
+        for check in &checks {
+            assert!(check.result.is_ok(), "{}: {:?}", check.name, check.result);
+        }
+        assert!(run_selftest_report(&checks).is_ok());
 
-        let word_list = create_simple_word_list(&mnemonic);
-        let word_list_file = output_dir.join("seed_words_simple.txt");
-        fs::write(&word_list_file, word_list).unwrap();
+        let failing = [SelfTestCheck {
+            name: "stuck RNG",
+            result: Err(KeygenError::Entropy("identical draws".to_string())),
+        }];
+        let err = run_selftest_report(&failing).unwrap_err();
+        assert!(err.to_string().contains("1 of 1"), "{}", err);
+    }
 
-        let seed_words_file = output_dir.join("seed_words_for_coldcard.txt");
-        fs::write(
-            &seed_words_file,
-            mnemonic.words().collect::<Vec<_>>().join("\n"),
-        )
+    #[test]
+    fn test_split_descriptors() {
+        let temp_dir = TempDir::new().unwrap();
+        let options = parse_args([
+            "--recover".to_string(),
+            format!("{}about", "abandon ".repeat(11)),
+            "--split-descriptors".to_string(),
+        ])
         .unwrap();
+        let output_dir = temp_dir.path().join("output");
+        run(&options, &output_dir).unwrap();
 
-        // Verify files exist and have content
-        assert!(printable_file.exists(), "Printable file should exist");
-        assert!(word_list_file.exists(), "Word list file should exist");
-        assert!(seed_words_file.exists(), "Seed words file should exist");
-
-        let printable_content = fs::read_to_string(&printable_file).unwrap();
+        for (file, range) in [
+            ("descriptor_receive.txt", "/0/*"),
+            ("descriptor_change.txt", "/1/*"),
+        ] {
+            let contents = fs::read_to_string(output_dir.join(file)).unwrap();
+            let descriptors: Vec<&str> = contents.lines().filter(|l| !l.starts_with('#')).collect();
+            assert_eq!(descriptors.len(), 4, "{}", file);
+            assert!(descriptors[0].starts_with("wpkh([73c5da0a/84h/0h/0h]xpub6CatWdiZ"));
+            for descriptor in descriptors {
+                let (body, checksum) = descriptor.split_once('#').unwrap();
+                assert!(body.contains(range), "{}", descriptor);
+                assert_eq!(descriptor_checksum(body).unwrap(), checksum);
+            }
+        }
         assert!(
-            !printable_content.is_empty(),
-            "Printable file should not be empty"
+            !fs::read_to_string(output_dir.join("descriptor_change.txt"))
+                .unwrap()
+                .contains("/0/*")
         );
+    }
 
-        let word_list_content = fs::read_to_string(&word_list_file).unwrap();
-        assert!(
-            !word_list_content.is_empty(),
-            "Word list file should not be empty"
+    #[test]
+    fn test_audit_log() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path().join("output");
+        let phrases = [
+            format!("{}about", "abandon ".repeat(11)),
+            format!("{}actual", "abandon ".repeat(11)),
+        ];
+        for phrase in &phrases {
+            let options = parse_args([
+                "--recover".to_string(),
+                phrase.clone(),
+                "--audit-log".to_string(),
+                "Device A".to_string(),
+            ])
+            .unwrap();
+            run(&options, &output_dir).unwrap();
+        }
+
+        let log = fs::read_to_string(output_dir.join(AUDIT_LOG_FILE)).unwrap();
+        let lines: Vec<&str> = log.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("fingerprint=73c5da0a"), "{}", lines[0]);
+        assert!(lines[1].ends_with("fingerprint=88528570"), "{}", lines[1]);
+        for line in &lines {
+            assert!(line.contains(" label=\"Device A\" network=mainnet words=12 "));
+        }
+        assert!(!log.contains("abandon") && !log.contains("about") && !log.contains("actual"));
+        assert!(!log.contains("xpub") && !log.contains("xprv"));
+
+        // Off by default
+        let quiet_dir = temp_dir.path().join("quiet");
+        let options = parse_args(["--recover".to_string(), phrases[0].clone()]).unwrap();
+        run(&options, &quiet_dir).unwrap();
+        assert!(!quiet_dir.join(AUDIT_LOG_FILE).exists());
+    }
+
+    #[test]
+    fn test_verify_xpub() {
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let master = derive_master_key(&generate_seed(&mnemonic, ""), Network::Bitcoin).unwrap();
+        let xpub = derive_account_xpub(&master, Network::Bitcoin, 84, 0).unwrap();
+        assert!(xpub.to_string().starts_with("xpub6CatWdiZ"));
+        assert_eq!(
+            find_xpub_purpose(&master, Network::Bitcoin, &xpub).unwrap(),
+            Some(84)
+        );
+        assert_eq!(
+            account_derivation_path(Network::Bitcoin, 84, 0)
+                .unwrap()
+                .to_string(),
+            "84'/0'/0'"
         );
 
-        let seed_words_content = fs::read_to_string(&seed_words_file).unwrap();
-        assert!(
-            !seed_words_content.is_empty(),
-            "Seed words file should not be empty"
+        // The zpub a hardware wallet shows parses to the same key
+        let zpub = slip132_encode(&xpub, 84);
+        assert!(zpub.starts_with("zpub"));
+        assert_eq!(parse_account_xpub(&zpub).unwrap(), xpub);
+        let taproot = derive_account_xpub(&master, Network::Bitcoin, 86, 0).unwrap();
+        assert_eq!(
+            find_xpub_purpose(&master, Network::Bitcoin, &taproot).unwrap(),
+            Some(86)
         );
 
-        // Verify seed words file has 24 lines
-        let lines: Vec<&str> = seed_words_content.lines().collect();
-        assert_eq!(lines.len(), 24, "Seed words file should have 24 lines");
+        let phrase = format!("{}about", "abandon ".repeat(11));
+        let temp_dir = TempDir::new().unwrap();
+        let options = parse_args([
+            "--recover".to_string(),
+            phrase.clone(),
+            "--verify-xpub".to_string(),
+            zpub,
+            "--no-write".to_string(),
+        ])
+        .unwrap();
+        assert!(run(&options, &temp_dir.path().join("output")).is_ok());
+
+        let other = Mnemonic::parse(format!("{}actual", "abandon ".repeat(11))).unwrap();
+        let other = derive_master_key(&generate_seed(&other, ""), Network::Bitcoin).unwrap();
+        let other_xpub = derive_account_xpub(&other, Network::Bitcoin, 84, 0).unwrap();
+        assert_eq!(
+            find_xpub_purpose(&master, Network::Bitcoin, &other_xpub).unwrap(),
+            None
+        );
+        let options = parse_args([
+            "--recover".to_string(),
+            phrase,
+            "--verify-xpub".to_string(),
+            other_xpub.to_string(),
+            "--no-write".to_string(),
+        ])
+        .unwrap();
+        let err = run(&options, &temp_dir.path().join("output")).unwrap_err();
+        assert!(err.to_string().contains("does not match"), "{}", err);
+
+        assert!(parse_args(["--verify-xpub", "xpub123"].map(String::from)).is_err());
     }
 
     #[test]
-    fn test_fingerprint_format() {
-        // Generate multiple mnemonics and verify fingerprints are unique
-        let mut fingerprints = std::collections::HashSet::new();
+    fn test_label_addresses() {
+        let temp_dir = TempDir::new().unwrap();
+        let options = parse_args([
+            "--recover".to_string(),
+            format!("{}about", "abandon ".repeat(11)),
+            "--label-addresses".to_string(),
+            "25".to_string(),
+        ])
+        .unwrap();
+        let output_dir = temp_dir.path().join("output");
+        run(&options, &output_dir).unwrap();
 
-        for _ in 0..10 {
-            let mnemonic = generate_mnemonic().unwrap();
-            let seed = generate_seed(&mnemonic, "");
-            let master_key = derive_master_key(&seed, Network::Bitcoin).unwrap();
-            let fingerprint = get_hardware_wallet_fingerprint(&master_key);
+        let csv = fs::read_to_string(output_dir.join("address_labels.csv")).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("address,label"));
+        let rows: Vec<&str> = lines.collect();
+        assert_eq!(rows.len(), 25);
+        for row in &rows {
+            let (address, label) = row.split_once(',').unwrap();
+            assert!(label.is_empty());
+            let address: bitcoin::Address<bitcoin::address::NetworkUnchecked> =
+                address.parse().unwrap();
+            assert!(address.is_valid_for_network(Network::Bitcoin));
+        }
+        assert_eq!(rows[0], "bc1qcr8te4kr609gcawutmrza0j4xv80jy8z306fyu,");
+        let unique: std::collections::HashSet<&str> = rows.iter().copied().collect();
+        assert_eq!(unique.len(), 25);
 
-            // Verify format
-            assert_eq!(fingerprint.len(), 8);
-            assert!(fingerprint.chars().all(|c| c.is_ascii_hexdigit()));
+        assert!(parse_args(["--label-addresses", "0"].map(String::from)).is_err());
+        assert!(parse_args(["--label-addresses", "1001"].map(String::from)).is_err());
+    }
 
-            fingerprints.insert(fingerprint);
-        }
+    #[test]
+    fn test_seed_core() {
+        use bitcoin_keygen::seed_core;
 
-        // With high probability, all fingerprints should be unique
-        // (though collisions are possible, they're extremely rare)
-        assert!(
-            !fingerprints.is_empty(),
-            "Should generate at least one fingerprint"
+        // Pure computation on the all-abandon vector: no files, no clock
+        let mnemonic = Mnemonic::from_entropy(&[0u8; 16]).unwrap();
+        let seed = seed_core::generate_seed(&mnemonic, "");
+        assert_eq!(
+            seed.iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<String>(),
+            SELFTEST_SEED_HEX
         );
+        let master = seed_core::derive_master_key(&seed, Network::Bitcoin).unwrap();
+        assert_eq!(
+            seed_core::get_hardware_wallet_fingerprint(&master),
+            "73c5da0a"
+        );
+        let testnet = seed_core::derive_master_key(&seed, Network::Testnet).unwrap();
+        assert!(testnet.to_string().starts_with("tprv"));
+
+        // The module must stay liftable into a no_std + alloc build
+        let source = include_str!("seed_core.rs");
+        for forbidden in ["std::", "chrono", "fs::", "println!"] {
+            assert!(!source.contains(forbidden), "seed_core uses {}", forbidden);
+        }
     }
 }
@@ -0,0 +1,45 @@
+//! Mnemonic-to-key core: BIP39 seed, BIP32 master key and fingerprint.
+//!
+//! Kept free of the filesystem, the clock and anything else `std`-only (it
+//! uses `core` and `alloc` alone) so it can be lifted into a `no_std` + `alloc`
+//! build for an embedded air-gapped signer. File output and formatting live
+//! in the rest of the crate and in the binary. The lints below reject imports
+//! from `std` that `core` or `alloc` also provide.
+
+#![warn(clippy::std_instead_of_core, clippy::std_instead_of_alloc)]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::String;
+use bip39::Mnemonic;
+use bitcoin::bip32::{Error, Xpriv};
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::Network;
+use zeroize::Zeroizing;
+
+/// Generate seed from mnemonic (wiped from memory on drop)
+pub fn generate_seed(mnemonic: &Mnemonic, passphrase: &str) -> Zeroizing<[u8; 64]> {
+    Zeroizing::new(mnemonic.to_seed(passphrase))
+}
+
+/// Derive master private key from seed
+pub fn derive_master_key(seed: &[u8; 64], network: Network) -> Result<Xpriv, Error> {
+    Xpriv::new_master(network, seed)
+}
+
+/// Get master key fingerprint in hardware wallet format (8 hex characters)
+pub fn get_hardware_wallet_fingerprint(key: &Xpriv) -> String {
+    let secp = Secp256k1::new();
+    let fingerprint = key.fingerprint(&secp);
+    let fingerprint_bytes = fingerprint.as_bytes();
+    format!(
+        "{:08x}",
+        u32::from_be_bytes([
+            fingerprint_bytes[0],
+            fingerprint_bytes[1],
+            fingerprint_bytes[2],
+            fingerprint_bytes[3]
+        ])
+    )
+}